@@ -3,9 +3,10 @@
 //! 定义API层的数据传输格式
 
 use rag_deps::*;
-use rag_core::models::{Ticket, NewTicket, TicketStatus, PagedResult, Pagination, QueryFilter, DataSource};
-use rag_core::models::solution::ProcessResult;
+use rag_core::models::{Ticket, NewTicket, TicketStatus, PagedResult, Pagination, QueryFilter, DataSource, CommentRole, NewComment};
+use rag_core::models::solution::{ProcessResult, SimilarTicket, StructuredSolution, OutputStatus, Citation, ContextReport, GuardrailReport, TicketSolution};
 use rag_core::traits::reranking::RerankResult;
+use rag_business::validators::{CommentUpdate, FeedbackUpdate, TicketUpdate};
 
 /// 创建工单请求
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,32 +39,148 @@ pub struct UpdateTicketRequest {
     pub priority: Option<i32>,
     pub status: Option<TicketStatus>,
     pub tags: Option<Vec<String>>,
+    pub resolution_note: Option<String>, // 没有已采纳方案时，标记为已解决必须填写
+    pub version: Option<i64>, // 读取时的工单版本，未提供 `If-Match` 请求头时必填
+}
+
+impl From<UpdateTicketRequest> for TicketUpdate {
+    fn from(request: UpdateTicketRequest) -> Self {
+        TicketUpdate {
+            title: request.title,
+            description: request.description,
+            category: request.category,
+            status: request.status,
+            priority: request.priority,
+            tags: request.tags,
+            resolution_note: request.resolution_note,
+        }
+    }
+}
+
+/// 新增评论请求，评论者取自请求的操作者
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCommentRequest {
+    pub role: CommentRole,
+    pub body: String,
+    #[serde(default)]
+    pub internal: bool, // 内部备注，仅处理人员可见
+}
+
+impl From<CreateCommentRequest> for NewComment {
+    fn from(request: CreateCommentRequest) -> Self {
+        NewComment {
+            role: request.role,
+            body: request.body,
+            internal: request.internal,
+        }
+    }
+}
+
+/// 评论更新请求，未指定的字段保持原值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCommentRequest {
+    pub body: Option<String>,
+    pub internal: Option<bool>,
+}
+
+impl From<UpdateCommentRequest> for CommentUpdate {
+    fn from(request: UpdateCommentRequest) -> Self {
+        CommentUpdate {
+            body: request.body,
+            internal: request.internal,
+        }
+    }
+}
+
+/// 评论列表查询参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ListCommentsParams {
+    pub include_internal: Option<bool>, // 默认包含内部备注
+}
+
+/// 附件上传查询参数，请求体为文件原始内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadAttachmentParams {
+    pub filename: String,
+}
+
+/// 解决方案反馈请求
+/// 
+/// 未指定的字段保持原值；采纳、拒绝接口忽略 `is_accepted`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedbackRequest {
+    pub is_accepted: Option<bool>,
+    pub score: Option<i32>,
+    pub comment: Option<String>,
+}
+
+impl From<FeedbackRequest> for FeedbackUpdate {
+    fn from(request: FeedbackRequest) -> Self {
+        FeedbackUpdate {
+            is_accepted: request.is_accepted,
+            score: request.score,
+            comment: request.comment,
+        }
+    }
 }
 
 /// 工单处理响应
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessTicketResponse {
     pub ticket_id: Uuid,
+    pub solution_id: Uuid, // 已保存的解决方案，用于采纳、拒绝和反馈
     pub solution: String,
     pub confidence: f32,
     pub reasoning: String,
-    pub similar_cases: Vec<RerankResult>,
+    pub similar_cases: Vec<SimilarTicket>,
+    pub structured: Option<StructuredSolution>,
+    pub output_status: OutputStatus,
+    pub citations: Vec<Citation>,
+    pub invalid_citations: Vec<String>,
+    pub context: ContextReport,
+    pub model: String,
+    pub guardrail: GuardrailReport,
+    pub prompt_version: String,
     pub processing_time_ms: u64,
 }
 
-impl From<ProcessResult> for ProcessTicketResponse {
-    fn from(result: ProcessResult) -> Self {
+impl ProcessTicketResponse {
+    /// 由处理结果和保存后的解决方案组装
+    pub fn new(result: ProcessResult, solution: &TicketSolution) -> Self {
         Self {
             ticket_id: result.ticket_id,
+            solution_id: solution.id,
             solution: result.suggested_solution,
             confidence: result.confidence,
             reasoning: result.reasoning,
-            similar_cases: vec![], // 这里需要根据 similar_tickets 转换
+            similar_cases: result.similar_tickets,
+            structured: result.structured,
+            output_status: result.output_status,
+            citations: result.citations,
+            invalid_citations: result.invalid_citations,
+            context: result.context,
+            model: result.model,
+            guardrail: result.guardrail,
+            prompt_version: result.prompt_version,
             processing_time_ms: result.processing_time_ms,
         }
     }
 }
 
+/// Prompt模板预览请求
+/// 
+/// 指定 `ticket_id` 时使用已有工单，否则使用请求中的 `ticket`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewPromptRequest {
+    pub ticket_id: Option<Uuid>,
+    pub ticket: Option<CreateTicketRequest>,
+    #[serde(default)]
+    pub similar_cases: Vec<RerankResult>,
+    #[serde(default)]
+    pub ticket_ids: Vec<Uuid>, // 与 similar_cases 一一对应
+    pub language: Option<String>,
+}
+
 /// 搜索参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchParams {
@@ -71,6 +188,13 @@ pub struct SearchParams {
     pub limit: Option<usize>,
 }
 
+/// 工单处理查询参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessTicketParams {
+    pub model: Option<String>, // 指定模型后端，仅在启用模型路由时生效
+    pub fusion_strategy: Option<String>, // 覆盖配置中的融合策略：rrf, weighted, rerank_only
+}
+
 /// 工单列表查询参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListTicketsParams {
@@ -0,0 +1,31 @@
+//! # API错误映射
+//!
+//! 业务层的 `AppError` 统一映射为HTTP状态码，各处理器共用
+
+use axum::http::StatusCode;
+use rag_deps::*;
+use rag_core::errors::AppError;
+
+/// 业务错误映射为HTTP状态码
+///
+/// 外部模型服务调用失败返回502，无对应状态码的错误返回500，两者都记录日志
+pub fn error_status(err: AppError) -> StatusCode {
+    match err {
+        AppError::NotFound { .. } => StatusCode::NOT_FOUND,
+        AppError::Validation { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        AppError::Permission { .. } => StatusCode::FORBIDDEN,
+        AppError::VersionConflict { .. } => StatusCode::PRECONDITION_FAILED,
+        AppError::BudgetExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
+        e @ (AppError::EmbeddingService { .. }
+            | AppError::RerankService { .. }
+            | AppError::LLMService { .. }
+            | AppError::Network { .. }) => {
+            error!("外部服务调用失败: {}", e);
+            StatusCode::BAD_GATEWAY
+        }
+        e => {
+            error!("请求处理失败: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! # 请求提取器
+//!
+//! 从请求中提取处理器需要的上下文信息
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, StatusCode},
+};
+use std::convert::Infallible;
+
+/// 操作者请求头
+pub const ACTOR_HEADER: &str = "x-user-id";
+
+/// 未提供操作者时记录的名称
+pub const ANONYMOUS_ACTOR: &str = "anonymous";
+
+/// 发起请求的操作者，用于审计记录
+///
+/// 取自 `X-User-Id` 请求头，未提供或为空时为 `anonymous`
+#[derive(Debug, Clone)]
+pub struct Actor(pub String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let actor = parts.headers
+            .get(ACTOR_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .unwrap_or(ANONYMOUS_ACTOR);
+        Ok(Self(actor.to_string()))
+    }
+}
+
+/// 工单版本对应的ETag，如 `"3"`
+pub fn version_etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// `If-Match` 请求头，用于条件更新
+///
+/// 支持单个强ETag（如 `"3"`）和 `*`（任意版本）。工单版本要求强比较，
+/// 弱ETag（`W/"3"`）与格式不符的值一样按前置条件失败返回412
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfMatch {
+    /// 未提供
+    None,
+    /// `*`，不限定版本
+    Any,
+    /// 指定的工单版本
+    Version(i64),
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(header::IF_MATCH) else {
+            return Ok(Self::None);
+        };
+        let etag = value.to_str().map_err(|_| StatusCode::PRECONDITION_FAILED)?.trim();
+        if etag == "*" {
+            return Ok(Self::Any);
+        }
+        if etag.starts_with("W/") {
+            return Err(StatusCode::PRECONDITION_FAILED);
+        }
+        etag.strip_prefix('"')
+            .and_then(|etag| etag.strip_suffix('"')?.parse().ok())
+            .map(Self::Version)
+            .ok_or(StatusCode::PRECONDITION_FAILED)
+    }
+}
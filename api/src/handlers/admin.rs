@@ -1,8 +1,16 @@
 //! # 管理员API处理器
 
 use rag_deps::*;
+use rag_core::{models::Ticket, prompt::{PromptTemplate, RenderedPrompt}};
+use rag_business::{prompts::PromptTemplateSummary, confidence::CalibrationState, indexing::ReconcileReport};
 use rag_infrastructure::ServiceContainer;
-use axum::{extract::State, response::Json};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+};
+use crate::dto::{ApiResponse, PreviewPromptRequest};
+use crate::errors::error_status;
 
 /// 系统健康检查
 pub async fn health_check(
@@ -85,4 +93,94 @@ pub async fn services_status(
             "max_tokens": llm_info.max_tokens
         }
     }))
-} 
\ No newline at end of file
+}
+
+/// 列出Prompt模板版本
+pub async fn list_prompts(
+    State(services): State<ServiceContainer>,
+) -> Result<Json<ApiResponse<Vec<PromptTemplateSummary>>>, StatusCode> {
+    let templates = services.prompt_manager.list().await.map_err(error_status)?;
+    Ok(Json(ApiResponse::success(templates)))
+}
+
+/// 新增Prompt模板版本
+pub async fn create_prompt(
+    State(services): State<ServiceContainer>,
+    Json(template): Json<PromptTemplate>,
+) -> Result<Json<ApiResponse<PromptTemplate>>, StatusCode> {
+    let template = services.prompt_manager.create(template).await.map_err(error_status)?;
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// 获取Prompt模板版本详情
+pub async fn get_prompt(
+    State(services): State<ServiceContainer>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<PromptTemplate>>, StatusCode> {
+    let template = services.prompt_manager.get(&id).await.map_err(error_status)?;
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// 预览Prompt模板渲染结果
+pub async fn preview_prompt(
+    State(services): State<ServiceContainer>,
+    Path(id): Path<String>,
+    Json(request): Json<PreviewPromptRequest>,
+) -> Result<Json<ApiResponse<RenderedPrompt>>, StatusCode> {
+    let template = services.prompt_manager.get(&id).await.map_err(error_status)?;
+    
+    let ticket = match (request.ticket_id, request.ticket) {
+        (Some(ticket_id), _) => services.database.get_ticket(ticket_id).await
+            .map_err(|e| error_status(e.into()))?
+            .ok_or(StatusCode::NOT_FOUND)?,
+        (None, Some(ticket)) => Ticket::new(ticket.into()),
+        (None, None) => return Err(StatusCode::BAD_REQUEST),
+    };
+    
+    let rendered = services.prompt_manager.render_solution(
+        &template,
+        &ticket,
+        &request.similar_cases,
+        &request.ticket_ids,
+        request.language.as_deref(),
+    );
+    Ok(Json(ApiResponse::success(rendered)))
+}
+
+/// 激活Prompt模板版本
+pub async fn activate_prompt(
+    State(services): State<ServiceContainer>,
+    Path(id): Path<String>,
+) -> Result<Json<ApiResponse<PromptTemplate>>, StatusCode> {
+    let template = services.prompt_manager.activate(&id).await.map_err(error_status)?;
+    Ok(Json(ApiResponse::success(template)))
+}
+
+/// 获取置信度校准状态
+pub async fn get_confidence_calibration(
+    State(services): State<ServiceContainer>,
+) -> Json<ApiResponse<CalibrationState>> {
+    Json(ApiResponse::success(services.confidence_calibrator.state()))
+}
+
+/// 基于历史反馈重新拟合置信度校准
+pub async fn recalibrate_confidence(
+    State(services): State<ServiceContainer>,
+) -> Result<Json<ApiResponse<CalibrationState>>, StatusCode> {
+    let state = services.confidence_calibrator
+        .refit(services.database.as_ref())
+        .await
+        .map_err(error_status)?;
+    Ok(Json(ApiResponse::success(state)))
+}
+
+/// 立即对账工单表与向量库，为不一致的工单补发索引事件
+pub async fn reconcile_index(
+    State(services): State<ServiceContainer>,
+) -> Result<Json<ApiResponse<ReconcileReport>>, StatusCode> {
+    let report = services.ticket_indexer
+        .reconcile()
+        .await
+        .map_err(error_status)?;
+    Ok(Json(ApiResponse::success(report)))
+}
@@ -0,0 +1,93 @@
+//! # 工单附件处理器
+//!
+//! 处理工单附件相关的HTTP请求，上传的请求体为文件原始内容
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use rag_deps::*;
+use rag_core::models::Attachment;
+use rag_infrastructure::container::ServiceContainer;
+use crate::dto::{ApiResponse, UploadAttachmentParams};
+use crate::extractors::Actor;
+use crate::errors::error_status;
+
+type AppState = ServiceContainer;
+
+/// 上传附件
+pub async fn upload_attachment(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<Uuid>,
+    Query(params): Query<UploadAttachmentParams>,
+    Actor(actor): Actor,
+    body: Bytes,
+) -> Result<Json<ApiResponse<Attachment>>, StatusCode> {
+    let attachment = state.attachments
+        .upload(ticket_id, &params.filename, &body, &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(attachment)))
+}
+
+/// 获取工单的附件，按上传时间正序
+pub async fn list_attachments(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<Attachment>>>, StatusCode> {
+    let attachments = state.attachments
+        .list(ticket_id)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(attachments)))
+}
+
+/// 下载附件，以识别出的内容类型返回，禁止浏览器再次猜测类型
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path((ticket_id, attachment_id)): Path<(Uuid, Uuid)>,
+) -> Result<Response, StatusCode> {
+    let (attachment, data) = state.attachments
+        .download(ticket_id, attachment_id)
+        .await
+        .map_err(error_status)?;
+    
+    let headers = [
+        (header::CONTENT_TYPE, attachment.content_type.clone()),
+        (header::CONTENT_DISPOSITION, content_disposition(&attachment.filename)),
+        (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+    ];
+    Ok((headers, data).into_response())
+}
+
+/// 删除附件，仅上传者本人可操作
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Path((ticket_id, attachment_id)): Path<(Uuid, Uuid)>,
+    Actor(actor): Actor,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    state.attachments
+        .delete(ticket_id, attachment_id, &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// 下载文件名：ASCII回退名加RFC 5987编码的原始文件名
+fn content_disposition(filename: &str) -> String {
+    let fallback: String = filename.chars()
+        .map(|c| if c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\') { c } else { '_' })
+        .collect();
+    let encoded: String = filename.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
@@ -0,0 +1,75 @@
+//! # 工单评论处理器
+//!
+//! 处理工单评论相关的HTTP请求
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use rag_deps::*;
+use rag_core::models::TicketComment;
+use rag_infrastructure::container::ServiceContainer;
+use crate::dto::{ApiResponse, CreateCommentRequest, ListCommentsParams, UpdateCommentRequest};
+use crate::extractors::Actor;
+use crate::errors::error_status;
+
+type AppState = ServiceContainer;
+
+/// 新增评论
+pub async fn create_comment(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<Uuid>,
+    Actor(actor): Actor,
+    Json(request): Json<CreateCommentRequest>,
+) -> Result<Json<ApiResponse<TicketComment>>, StatusCode> {
+    let comment = state.ticket_processor
+        .add_comment(ticket_id, request.into(), &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(comment)))
+}
+
+/// 获取工单的评论，按时间正序
+pub async fn list_comments(
+    State(state): State<AppState>,
+    Path(ticket_id): Path<Uuid>,
+    Query(params): Query<ListCommentsParams>,
+) -> Result<Json<ApiResponse<Vec<TicketComment>>>, StatusCode> {
+    let comments = state.ticket_processor
+        .list_comments(ticket_id, params.include_internal.unwrap_or(true))
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(comments)))
+}
+
+/// 修改评论，仅评论者本人可操作
+pub async fn update_comment(
+    State(state): State<AppState>,
+    Path((ticket_id, comment_id)): Path<(Uuid, Uuid)>,
+    Actor(actor): Actor,
+    Json(request): Json<UpdateCommentRequest>,
+) -> Result<Json<ApiResponse<TicketComment>>, StatusCode> {
+    let comment = state.ticket_processor
+        .update_comment(ticket_id, comment_id, &request.into(), &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(comment)))
+}
+
+/// 删除评论，仅评论者本人可操作
+pub async fn delete_comment(
+    State(state): State<AppState>,
+    Path((ticket_id, comment_id)): Path<(Uuid, Uuid)>,
+    Actor(actor): Actor,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    state.ticket_processor
+        .delete_comment(ticket_id, comment_id, &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(())))
+}
@@ -4,6 +4,8 @@
 
 pub mod root;
 pub mod tickets;
+pub mod comments;
+pub mod attachments;
 pub mod solutions;
 pub mod search;
 pub mod stats;
@@ -1,29 +1,55 @@
 //! # 解决方案API处理器
 
 use rag_deps::*;
+use rag_core::models::TicketSolution;
 use rag_infrastructure::ServiceContainer;
 use axum::{extract::{State, Path}, response::Json, http::StatusCode};
+use crate::dto::{ApiResponse, FeedbackRequest};
+use crate::extractors::Actor;
+use crate::errors::error_status;
+
+/// 记录反馈并写入审计日志
+async fn record_feedback(
+    services: &ServiceContainer,
+    id: Uuid,
+    request: FeedbackRequest,
+    actor: &str,
+) -> Result<Json<ApiResponse<TicketSolution>>, StatusCode> {
+    let solution = services.ticket_processor
+        .submit_feedback(id, &request.into(), actor)
+        .await
+        .map_err(error_status)?;
+    Ok(Json(ApiResponse::success(solution)))
+}
 
 /// 提交反馈
 pub async fn submit_feedback(
-    State(_services): State<ServiceContainer>,
-    Path(_id): Path<Uuid>,
-) -> Result<StatusCode, String> {
-    Ok(StatusCode::OK)
+    State(services): State<ServiceContainer>,
+    Path(id): Path<Uuid>,
+    Actor(actor): Actor,
+    Json(request): Json<FeedbackRequest>,
+) -> Result<Json<ApiResponse<TicketSolution>>, StatusCode> {
+    record_feedback(&services, id, request, &actor).await
 }
 
-/// 接受解决方案
+/// 接受解决方案，可附带评分和评论
 pub async fn accept_solution(
-    State(_services): State<ServiceContainer>,
-    Path(_id): Path<Uuid>,
-) -> Result<StatusCode, String> {
-    Ok(StatusCode::OK)
+    State(services): State<ServiceContainer>,
+    Path(id): Path<Uuid>,
+    Actor(actor): Actor,
+    request: Option<Json<FeedbackRequest>>,
+) -> Result<Json<ApiResponse<TicketSolution>>, StatusCode> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    record_feedback(&services, id, FeedbackRequest { is_accepted: Some(true), ..request }, &actor).await
 }
 
-/// 拒绝解决方案
+/// 拒绝解决方案，可附带评分和评论
 pub async fn reject_solution(
-    State(_services): State<ServiceContainer>,
-    Path(_id): Path<Uuid>,
-) -> Result<StatusCode, String> {
-    Ok(StatusCode::OK)
-} 
\ No newline at end of file
+    State(services): State<ServiceContainer>,
+    Path(id): Path<Uuid>,
+    Actor(actor): Actor,
+    request: Option<Json<FeedbackRequest>>,
+) -> Result<Json<ApiResponse<TicketSolution>>, StatusCode> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    record_feedback(&services, id, FeedbackRequest { is_accepted: Some(false), ..request }, &actor).await
+}
@@ -1,8 +1,14 @@
 //! # 统计API处理器
 
 use rag_deps::*;
+use rag_core::models::UsageQuery;
 use rag_infrastructure::ServiceContainer;
-use axum::{extract::State, response::Json};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use crate::errors::error_status;
 
 /// 获取概览统计
 pub async fn get_overview(
@@ -17,14 +23,20 @@ pub async fn get_overview(
 }
 
 /// 获取性能统计
+/// 
+/// 返回模型调用用量（按类型、模型、工单、分类和日期汇总）及当日预算状态，
+/// 支持 `from`、`to`、`ticket_id`、`category`、`kind`、`model` 过滤
 pub async fn get_performance(
-    State(_services): State<ServiceContainer>,
-) -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "processing_time_avg": 0.0,
-        "confidence_avg": 0.0,
-        "throughput": 0
-    }))
+    State(services): State<ServiceContainer>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let usage = services.usage_tracker.report(&query).await.map_err(error_status)?;
+    let budget = services.usage_tracker.budget_status().await.map_err(error_status)?;
+    
+    Ok(Json(serde_json::json!({
+        "usage": usage,
+        "budget": budget
+    })))
 }
 
 /// 获取质量统计
@@ -4,27 +4,39 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderName, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json,
+    },
 };
+use futures::{Stream, StreamExt};
 use rag_deps::*;
-use rag_core::models::{Ticket, Pagination, QueryFilter};
+use rag_core::models::{AuditEntry, Ticket, TicketSolution, Pagination, QueryFilter};
+use rag_business::processors::{ProcessEvent, ProcessOptions};
 use rag_infrastructure::container::ServiceContainer;
+use std::convert::Infallible;
 use crate::dto::{
     CreateTicketRequest, UpdateTicketRequest, ProcessTicketResponse,
-    ListTicketsParams, PaginatedResponse, ApiResponse
+    ProcessTicketParams, ListTicketsParams, PaginatedResponse, ApiResponse
 };
+use crate::extractors::{version_etag, Actor, IfMatch};
+use crate::errors::error_status;
 
 type AppState = ServiceContainer;
 
 /// 创建工单
+/// 
+/// 工单保存后即返回；向量索引失败时 `embedding` 为空，由后台重试补建
 pub async fn create_ticket(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    Actor(actor): Actor,
     Json(request): Json<CreateTicketRequest>,
 ) -> Result<Json<ApiResponse<Ticket>>, StatusCode> {
-    // TODO: 实现工单创建逻辑
-    let new_ticket = request.into();
-    let ticket = Ticket::new(new_ticket);
+    let ticket = state.ticket_processor
+        .create_ticket(request.into(), &actor)
+        .await
+        .map_err(error_status)?;
     
     Ok(Json(ApiResponse::success(ticket)))
 }
@@ -50,53 +62,172 @@ pub async fn list_tickets(
     Ok(Json(ApiResponse::success(response)))
 }
 
-/// 获取单个工单
+/// 带ETag响应头的工单
+type VersionedTicket = ([(HeaderName, String); 1], Json<ApiResponse<Ticket>>);
+
+/// 获取单个工单，`ETag` 响应头为当前版本
 pub async fn get_ticket(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<ApiResponse<Ticket>>, StatusCode> {
-    // TODO: 实现获取单个工单逻辑
-    let _ = id;
-    Err(StatusCode::NOT_FOUND)
+) -> Result<VersionedTicket, StatusCode> {
+    let ticket = state.database
+        .get_ticket(id)
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    Ok(([(header::ETAG, version_etag(ticket.version))], Json(ApiResponse::success(ticket))))
 }
 
 /// 更新工单
+/// 
+/// 只修改请求中指定的字段，每项变更以 `X-User-Id` 为操作者记入审计日志。
+/// 读取时的版本通过 `If-Match` 请求头或请求体 `version` 提供，都未提供时返回428，
+/// 与当前版本不一致（已被他人修改）或为弱ETag时返回412；`If-Match: *` 时不校验版本。
+/// 状态流转不合法、解决工单缺少已采纳方案或解决说明时返回422
 pub async fn update_ticket(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    Json(_request): Json<UpdateTicketRequest>,
-) -> Result<Json<ApiResponse<Ticket>>, StatusCode> {
-    // TODO: 实现工单更新逻辑
-    let _ = id;
-    Err(StatusCode::NOT_FOUND)
+    Actor(actor): Actor,
+    if_match: IfMatch,
+    Json(request): Json<UpdateTicketRequest>,
+) -> Result<VersionedTicket, StatusCode> {
+    let expected_version = match (if_match, request.version) {
+        (IfMatch::Version(header), Some(body)) if header != body => return Err(StatusCode::PRECONDITION_FAILED),
+        (IfMatch::Version(version), _) | (_, Some(version)) => Some(version),
+        (IfMatch::Any, None) => None,
+        (IfMatch::None, None) => return Err(StatusCode::PRECONDITION_REQUIRED),
+    };
+    let ticket = state.ticket_processor
+        .update_ticket(id, expected_version, &request.into(), &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(([(header::ETAG, version_etag(ticket.version))], Json(ApiResponse::success(ticket))))
 }
 
 /// 删除工单
+/// 
+/// 向量索引由后台按删除事件同步移除
 pub async fn delete_ticket(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Actor(actor): Actor,
 ) -> Result<Json<ApiResponse<()>>, StatusCode> {
-    // TODO: 实现工单删除逻辑
-    let _ = id;
-    Err(StatusCode::NOT_FOUND)
+    state.ticket_processor
+        .delete_ticket(id, &actor)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(())))
+}
+
+/// 获取工单变更历史
+/// 
+/// 按时间正序返回字段变更、状态流转、方案采纳与反馈记录；工单删除后仍可查询
+pub async fn get_ticket_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<AuditEntry>>>, StatusCode> {
+    let entries = state.ticket_processor
+        .ticket_history(id)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(entries)))
+}
+
+/// 由查询参数生成单次处理参数，融合策略不支持时返回422
+fn process_options(state: &AppState, params: ProcessTicketParams) -> Result<ProcessOptions, StatusCode> {
+    let fusion = params.fusion_strategy
+        .map(|name| state.ticket_processor.fusion_strategy(&name))
+        .transpose()
+        .map_err(error_status)?;
+    Ok(ProcessOptions {
+        fusion,
+        model: params.model,
+        ..Default::default()
+    })
 }
 
 /// 处理工单
+/// 
+/// 检索相似工单并生成解决方案，方案保存后连同相似案例、引用和安全检查结果一并返回
 pub async fn process_ticket(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(params): Query<ProcessTicketParams>,
 ) -> Result<Json<ApiResponse<ProcessTicketResponse>>, StatusCode> {
-    // TODO: 实现工单处理逻辑
-    let _ = id;
-    Err(StatusCode::NOT_IMPLEMENTED)
+    let ticket = state.database
+        .get_ticket(id)
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    let options = process_options(&state, params)?;
+    let result = state.ticket_processor
+        .process_with_options(&ticket, &options)
+        .await
+        .map_err(error_status)?;
+    let solution = state.ticket_processor
+        .save_solution(&result)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(ProcessTicketResponse::new(result, &solution))))
+}
+
+/// 流式处理工单
+/// 
+/// 以SSE推送事件：`similar_tickets` → 若干 `token` → `completed`，出错时推送 `error`；
+/// 生成的方案在 `completed` 之前保存，事件中带有方案ID
+pub async fn process_ticket_stream(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ProcessTicketParams>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, Infallible>>>, StatusCode> {
+    let ticket = state.database
+        .get_ticket(id)
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    let options = process_options(&state, params)?;
+    let events = state.ticket_processor
+        .process_stream(&ticket, &options)
+        .await
+        .map_err(error_status)?;
+    
+    let sse_events = events.map(|event| {
+        let sse_event = match event {
+            Ok(event) => {
+                let name = match &event {
+                    ProcessEvent::SimilarTickets { .. } => "similar_tickets",
+                    ProcessEvent::Token { .. } => "token",
+                    ProcessEvent::Completed { .. } => "completed",
+                };
+                Event::default().event(name).json_data(&event)
+            }
+            Err(e) => {
+                error!("工单流式处理中断: {}", e);
+                Event::default().event("error").json_data(serde_json::json!({ "message": e.to_string() }))
+            }
+        };
+        Ok(sse_event.unwrap_or_else(|_| Event::default().event("error")))
+    });
+    
+    Ok(Sse::new(sse_events).keep_alive(KeepAlive::default()))
 }
 
-/// 获取工单解决方案
+/// 获取工单的全部解决方案，含反馈、引用与安全检查结果
 pub async fn get_solutions(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<ApiResponse<Vec<ProcessTicketResponse>>>, StatusCode> {
-    // TODO: 实现获取解决方案逻辑
-    let _ = id;
-    Err(StatusCode::NOT_FOUND)
+) -> Result<Json<ApiResponse<Vec<TicketSolution>>>, StatusCode> {
+    let solutions = state.ticket_processor
+        .list_solutions(id)
+        .await
+        .map_err(error_status)?;
+    
+    Ok(Json(ApiResponse::success(solutions)))
 } 
\ No newline at end of file
@@ -6,6 +6,8 @@ pub mod handlers;
 pub mod middleware;
 pub mod routes;
 pub mod dto;
+pub mod extractors;
+pub mod errors;
 pub mod server;
 
 // 重新导出核心组件
@@ -9,6 +9,7 @@ use axum::{
     Router,
     routing::{get, post, put, delete},
     middleware as axum_middleware,
+    extract::DefaultBodyLimit,
 };
 use tower::ServiceBuilder;
 use tower_http::{
@@ -34,6 +35,9 @@ pub async fn create_app_router(service_container: ServiceContainer) -> Result<Ro
         .layer(axum_middleware::from_fn(middleware::request_logging))
         .layer(axum_middleware::from_fn(middleware::error_handling));
     
+    // 附件上传的请求体上限跟随附件配置
+    let attachment_limit = service_container.attachments.max_size_bytes();
+    
     // API路由
     let api_routes = Router::new()
         // 工单相关路由
@@ -43,7 +47,20 @@ pub async fn create_app_router(service_container: ServiceContainer) -> Result<Ro
         .route("/tickets/:id", put(handlers::tickets::update_ticket))
         .route("/tickets/:id", delete(handlers::tickets::delete_ticket))
         .route("/tickets/:id/process", post(handlers::tickets::process_ticket))
+        .route("/tickets/:id/process/stream", post(handlers::tickets::process_ticket_stream))
         .route("/tickets/:id/solutions", get(handlers::tickets::get_solutions))
+        .route("/tickets/:id/history", get(handlers::tickets::get_ticket_history))
+        .route("/tickets/:id/comments", get(handlers::comments::list_comments))
+        .route("/tickets/:id/comments", post(handlers::comments::create_comment))
+        .route("/tickets/:id/comments/:comment_id", put(handlers::comments::update_comment))
+        .route("/tickets/:id/comments/:comment_id", delete(handlers::comments::delete_comment))
+        .route(
+            "/tickets/:id/attachments",
+            post(handlers::attachments::upload_attachment).layer(DefaultBodyLimit::max(attachment_limit)),
+        )
+        .route("/tickets/:id/attachments", get(handlers::attachments::list_attachments))
+        .route("/tickets/:id/attachments/:attachment_id", get(handlers::attachments::download_attachment))
+        .route("/tickets/:id/attachments/:attachment_id", delete(handlers::attachments::delete_attachment))
         
         // 解决方案相关路由
         .route("/solutions/:id/feedback", post(handlers::solutions::submit_feedback))
@@ -64,6 +81,14 @@ pub async fn create_app_router(service_container: ServiceContainer) -> Result<Ro
         .route("/admin/metrics", get(handlers::admin::get_metrics))
         .route("/admin/config", get(handlers::admin::get_config))
         .route("/admin/services/status", get(handlers::admin::services_status))
+        .route("/admin/prompts", get(handlers::admin::list_prompts))
+        .route("/admin/prompts", post(handlers::admin::create_prompt))
+        .route("/admin/prompts/:id", get(handlers::admin::get_prompt))
+        .route("/admin/prompts/:id/preview", post(handlers::admin::preview_prompt))
+        .route("/admin/prompts/:id/activate", post(handlers::admin::activate_prompt))
+        .route("/admin/confidence/calibration", get(handlers::admin::get_confidence_calibration))
+        .route("/admin/confidence/calibrate", post(handlers::admin::recalibrate_confidence))
+        .route("/admin/index/reconcile", post(handlers::admin::reconcile_index))
         
         // 微调相关路由
         .route("/finetune/data/export", get(handlers::finetune::export_data))
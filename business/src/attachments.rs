@@ -0,0 +1,287 @@
+//! # 工单附件模块
+//!
+//! 附件内容写入附件存储前先做大小、数量检查和病毒扫描；按文件内容识别类型，
+//! 从文本类附件（.txt/.log/.json/.csv/.md）提取文本，其中的错误行随工单进入向量索引
+
+use rag_deps::*;
+use rag_core::{
+    config::AttachmentConfig,
+    models::*,
+    traits::{AttachmentStorage, Repository, VirusScanner},
+    errors::{AppError, AppResult},
+};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 提取文本的扩展名及对应的内容类型
+pub const TEXT_EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+    ("json", "application/json"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+];
+
+/// 无法识别的二进制内容
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// 常见二进制格式的文件头
+const MAGIC_NUMBERS: &[(&[u8], &str)] = &[
+    (b"%PDF-", "application/pdf"),
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"Rar!\x1a\x07", "application/vnd.rar"),
+    (b"\x7fELF", "application/x-executable"),
+    (b"MZ", "application/x-msdownload"),
+];
+
+/// 判断是否为文本时检查的前缀长度
+const TEXT_SNIFF_BYTES: usize = 8192;
+
+/// 错误行规则：常见的错误、异常、失败关键词
+const ERROR_LINE_PATTERN: &str = r"(?i)\b(?:error|err|exception|fatal|panic(?:ked)?|fail(?:ed|ure)?|traceback|critical|denied|refused|timed?\s*out|unreachable|segfault|oom|killed)\b|错误|异常|失败|超时|拒绝|崩溃";
+
+/// 单条错误行保留的最大字符数
+const MAX_ERROR_LINE_CHARS: usize = 500;
+
+/// 文件名最大字节数
+const MAX_FILENAME_BYTES: usize = 255;
+
+/// 工单附件服务
+///
+/// 职责：
+/// - 检查附件大小、数量，调用病毒扫描钩子
+/// - 识别内容类型，提取文本和错误行
+/// - 保存附件内容与元数据，删除时同步清理存储
+pub struct AttachmentService {
+    storage: Arc<dyn AttachmentStorage>,
+    scanner: Arc<dyn VirusScanner>,
+    database: Arc<dyn Repository>,
+    config: AttachmentConfig,
+    error_pattern: Regex,
+}
+
+impl AttachmentService {
+    pub fn new(
+        storage: Arc<dyn AttachmentStorage>,
+        scanner: Arc<dyn VirusScanner>,
+        database: Arc<dyn Repository>,
+        config: AttachmentConfig,
+    ) -> Self {
+        Self {
+            storage,
+            scanner,
+            database,
+            config,
+            error_pattern: Regex::new(ERROR_LINE_PATTERN).expect("内置错误行规则有效"),
+        }
+    }
+
+    /// 单个附件的大小上限
+    pub fn max_size_bytes(&self) -> usize {
+        self.config.max_size_bytes
+    }
+
+    /// 上传附件
+    ///
+    /// 超出大小或数量限制、未通过病毒扫描时返回 `Validation`，工单不存在时返回 `NotFound`
+    pub async fn upload(&self, ticket_id: Uuid, filename: &str, data: &[u8], actor: &str) -> AppResult<Attachment> {
+        let filename = sanitize_filename(filename);
+        if filename.is_empty() {
+            return Err(AppError::validation("filename", "附件文件名不能为空"));
+        }
+        if filename.len() > MAX_FILENAME_BYTES {
+            return Err(AppError::validation("filename", format!("附件文件名不能超过{}字节", MAX_FILENAME_BYTES)));
+        }
+        if data.is_empty() {
+            return Err(AppError::validation("file", "附件内容不能为空"));
+        }
+        if data.len() > self.config.max_size_bytes {
+            return Err(AppError::validation("file", format!("附件不能超过{}字节", self.config.max_size_bytes)));
+        }
+        if self.database.get_ticket(ticket_id).await?.is_none() {
+            return Err(AppError::not_found("ticket", ticket_id));
+        }
+        if self.database.list_attachments(ticket_id).await?.len() >= self.config.max_per_ticket {
+            return Err(AppError::validation("file", format!("每个工单最多{}个附件", self.config.max_per_ticket)));
+        }
+        if let ScanVerdict::Infected { signature } = self.scanner.scan(&filename, data).await? {
+            warn!("工单 {} 的附件 {} 未通过病毒扫描（{}）: {}", ticket_id, filename, self.scanner.name(), signature);
+            return Err(AppError::validation("file", format!("附件未通过病毒扫描: {}", signature)));
+        }
+
+        let content_type = sniff_content_type(&filename, data);
+        let extracted_text = is_extractable(&filename, content_type)
+            .then(|| extract_text(data, self.config.max_extracted_chars));
+        let error_lines = extracted_text.as_deref()
+            .map(|text| self.error_lines(text))
+            .unwrap_or_default();
+        let id = Uuid::new_v4();
+        let attachment = Attachment {
+            id,
+            ticket_id,
+            filename,
+            content_type: content_type.to_string(),
+            size_bytes: data.len() as i64,
+            storage_key: format!("tickets/{}/{}", ticket_id, id),
+            extracted_text,
+            error_lines,
+            uploaded_by: actor.to_string(),
+            created_at: Utc::now(),
+        };
+
+        self.storage.put(&attachment.storage_key, data, &attachment.content_type).await?;
+        let audit = AuditEntry::new(ticket_id, actor, AuditAction::AttachmentAdded)
+            .with_change("attachment", None, Some(summary(&attachment)));
+        if let Err(e) = self.database.insert_attachment(&attachment, &[audit]).await {
+            self.remove_stored(std::slice::from_ref(&attachment)).await;
+            return Err(e.into());
+        }
+        info!(
+            "工单 {} 上传附件 {}（{}，{} 字节，{} 条错误行）",
+            ticket_id, attachment.filename, attachment.content_type, attachment.size_bytes, attachment.error_lines.len(),
+        );
+        Ok(attachment)
+    }
+
+    /// 工单的附件，按上传时间正序
+    pub async fn list(&self, ticket_id: Uuid) -> AppResult<Vec<Attachment>> {
+        if self.database.get_ticket(ticket_id).await?.is_none() {
+            return Err(AppError::not_found("ticket", ticket_id));
+        }
+        Ok(self.database.list_attachments(ticket_id).await?)
+    }
+
+    /// 读取附件元数据和内容
+    pub async fn download(&self, ticket_id: Uuid, attachment_id: Uuid) -> AppResult<(Attachment, Vec<u8>)> {
+        let attachment = self.get(ticket_id, attachment_id).await?;
+        let data = self.storage.get(&attachment.storage_key).await?;
+        Ok((attachment, data))
+    }
+
+    /// 删除附件，只有上传者本人可以删除
+    pub async fn delete(&self, ticket_id: Uuid, attachment_id: Uuid, actor: &str) -> AppResult<()> {
+        let attachment = self.get(ticket_id, attachment_id).await?;
+        if attachment.uploaded_by != actor {
+            return Err(AppError::Permission {
+                action: "删除附件（仅上传者本人可操作）".to_string(),
+            });
+        }
+        let audit = AuditEntry::new(ticket_id, actor, AuditAction::AttachmentDeleted)
+            .with_change("attachment", Some(summary(&attachment)), None);
+        self.database.delete_attachment(attachment.id, &[audit]).await?;
+        self.remove_stored(std::slice::from_ref(&attachment)).await;
+        Ok(())
+    }
+
+    /// 删除附件存储中的内容，元数据已删除，失败只记录日志
+    pub async fn remove_stored(&self, attachments: &[Attachment]) {
+        for attachment in attachments {
+            if let Err(e) = self.storage.delete(&attachment.storage_key).await {
+                warn!("附件 {} 的内容删除失败（{}）: {}", attachment.id, self.storage.backend(), e);
+            }
+        }
+    }
+
+    /// 文本中的错误行，去除首尾空白后按忽略数字的内容去重，超过上限时只保留前面的
+    pub fn error_lines(&self, text: &str) -> Vec<String> {
+        let mut seen = HashSet::new();
+        text.lines()
+            .map(str::trim)
+            .filter(|line| self.error_pattern.is_match(line))
+            .filter(|line| seen.insert(line.replace(|c: char| c.is_ascii_digit(), "#")))
+            .map(|line| truncate_chars(line, MAX_ERROR_LINE_CHARS).to_string())
+            .take(self.config.max_error_lines)
+            .collect()
+    }
+
+    async fn get(&self, ticket_id: Uuid, attachment_id: Uuid) -> AppResult<Attachment> {
+        self.database.get_attachment(attachment_id).await?
+            .filter(|attachment| attachment.ticket_id == ticket_id)
+            .ok_or_else(|| AppError::not_found("attachment", attachment_id))
+    }
+}
+
+/// 按文件内容识别类型：先匹配常见二进制格式的文件头，文本内容再按扩展名细分
+///
+/// 不采信客户端声明的类型，无法识别的二进制内容为 `application/octet-stream`
+pub fn sniff_content_type(filename: &str, data: &[u8]) -> &'static str {
+    if let Some((_, content_type)) = MAGIC_NUMBERS.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return content_type;
+    }
+    if !looks_like_text(data) {
+        return OCTET_STREAM;
+    }
+    extension(filename)
+        .and_then(|ext| TEXT_EXTENSIONS.iter().find(|(known, _)| *known == ext))
+        .map_or("text/plain", |(_, content_type)| content_type)
+}
+
+/// 扩展名在提取列表中，且内容确实是文本
+pub fn is_extractable(filename: &str, content_type: &str) -> bool {
+    extension(filename).is_some_and(|ext| {
+        TEXT_EXTENSIONS.iter().any(|(known, expected)| *known == ext && *expected == content_type)
+    })
+}
+
+/// 解码为UTF-8文本（非法字节替换），去掉BOM、统一换行，超过 `max_chars` 时截断
+pub fn extract_text(data: &[u8], max_chars: usize) -> String {
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    let text = String::from_utf8_lossy(data).replace("\r\n", "\n");
+    truncate_chars(&text, max_chars).to_string()
+}
+
+/// 去掉路径部分和控制字符
+pub fn sanitize_filename(filename: &str) -> String {
+    filename.rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// 内容前缀不含NUL且是合法UTF-8（允许在截断处有不完整的字符）
+fn looks_like_text(data: &[u8]) -> bool {
+    let data = data.strip_prefix(b"\xef\xbb\xbf").unwrap_or(data);
+    let prefix = &data[..data.len().min(TEXT_SNIFF_BYTES)];
+    if prefix.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(prefix) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && prefix.len() < data.len(),
+    }
+}
+
+/// 小写扩展名
+fn extension(filename: &str) -> Option<String> {
+    filename.rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .filter(|ext| !ext.is_empty())
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> &str {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// 审计记录中的附件摘要，不含提取的文本
+fn summary(attachment: &Attachment) -> serde_json::Value {
+    serde_json::json!({
+        "id": attachment.id,
+        "filename": attachment.filename,
+        "content_type": attachment.content_type,
+        "size_bytes": attachment.size_bytes,
+    })
+}
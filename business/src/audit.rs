@@ -0,0 +1,90 @@
+//! # 工单审计模块
+//!
+//! 比较变更前后的工单、评论和解决方案，生成审计记录
+
+use rag_deps::*;
+use rag_core::models::*;
+
+/// 比较工单变更前后的字段，状态变化记为状态流转，其余记为字段变更
+pub fn ticket_changes(before: &Ticket, after: &Ticket, actor: &str) -> Vec<AuditEntry> {
+    let fields = [
+        ("title", json(&before.title), json(&after.title)),
+        ("description", json(&before.description), json(&after.description)),
+        ("category", json(&before.category), json(&after.category)),
+        ("priority", json(&before.priority), json(&after.priority)),
+        ("tags", json(&before.tags), json(&after.tags)),
+        ("resolution_note", json(&before.resolution_note), json(&after.resolution_note)),
+    ];
+    let mut entries: Vec<AuditEntry> = fields.into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| {
+            AuditEntry::new(after.id, actor, AuditAction::FieldChanged).with_change(field, Some(old), Some(new))
+        })
+        .collect();
+    if before.status != after.status {
+        entries.push(
+            AuditEntry::new(after.id, actor, AuditAction::StatusChanged)
+                .with_change("status", Some(json(&before.status)), Some(json(&after.status))),
+        );
+    }
+    entries
+}
+
+/// 新增评论，`new_value` 为评论全文
+pub fn comment_added(comment: &TicketComment, actor: &str) -> AuditEntry {
+    AuditEntry::new(comment.ticket_id, actor, AuditAction::CommentAdded)
+        .with_change("comment", None, Some(json(comment)))
+}
+
+/// 删除评论，`old_value` 为删除前的评论
+pub fn comment_deleted(comment: &TicketComment, actor: &str) -> AuditEntry {
+    AuditEntry::new(comment.ticket_id, actor, AuditAction::CommentDeleted)
+        .with_change("comment", Some(json(comment)), None)
+}
+
+/// 比较评论变更前后的正文和可见范围，记为修改评论，`field` 为 `comment.<字段名>`
+pub fn comment_changes(before: &TicketComment, after: &TicketComment, actor: &str) -> Vec<AuditEntry> {
+    let fields = [
+        ("comment.body", json(&before.body), json(&after.body)),
+        ("comment.internal", json(&before.internal), json(&after.internal)),
+    ];
+    fields.into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| {
+            AuditEntry::new(after.ticket_id, actor, AuditAction::CommentEdited).with_change(field, Some(old), Some(new))
+        })
+        .collect()
+}
+
+/// 比较反馈前后的解决方案，采纳状态变化记为采纳或拒绝，评分或评论变化记为提交反馈
+pub fn feedback_changes(before: &TicketSolution, feedback: &Feedback, actor: &str) -> Vec<AuditEntry> {
+    let entry = |action| AuditEntry::new(before.ticket_id, actor, action).with_solution(before.id);
+    let mut entries = Vec::new();
+    if before.is_accepted != feedback.is_accepted {
+        let action = if feedback.is_accepted { AuditAction::SolutionAccepted } else { AuditAction::SolutionRejected };
+        entries.push(entry(action).with_change(
+            "is_accepted",
+            Some(json(&before.is_accepted)),
+            Some(json(&feedback.is_accepted)),
+        ));
+    }
+    if before.feedback_score != feedback.score {
+        entries.push(entry(AuditAction::FeedbackSubmitted).with_change(
+            "feedback_score",
+            before.feedback_score.map(|s| json(&s)),
+            feedback.score.map(|s| json(&s)),
+        ));
+    }
+    if before.feedback_comment != feedback.comment {
+        entries.push(entry(AuditAction::FeedbackSubmitted).with_change(
+            "feedback_comment",
+            before.feedback_comment.as_ref().map(json),
+            feedback.comment.as_ref().map(json),
+        ));
+    }
+    entries
+}
+
+fn json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
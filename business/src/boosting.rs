@@ -0,0 +1,210 @@
+//! # 检索结果加权模块
+//!
+//! 在重排序与融合之后，根据工单时效和历史解决方案质量调整排名
+
+use rag_deps::*;
+use rag_core::{
+    config::BoostConfig,
+    models::TicketSolution,
+};
+use crate::fusion::{FusedCandidate, recency_score, min_max_normalize};
+use std::collections::HashMap;
+
+/// 归一化分数的下限，排名最后的候选也保留加权效果
+const NORMALIZED_FLOOR: f32 = 0.05;
+
+/// 工单解决方案质量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionQuality {
+    Accepted,   // 有被采纳且评分不低的方案
+    Rejected,   // 方案被拒绝或评分过低
+    Unreviewed, // 尚无反馈
+}
+
+impl ResolutionQuality {
+    /// 根据历史解决方案判断质量
+    pub fn from_solutions(solutions: &[TicketSolution], low_score_threshold: i32) -> Self {
+        let is_low = |s: &TicketSolution| {
+            s.feedback_score.is_some_and(|score| score <= low_score_threshold)
+        };
+
+        if solutions.iter().any(|s| s.is_accepted && !is_low(s)) {
+            Self::Accepted
+        } else if solutions.iter().any(|s| is_low(s) || (!s.is_accepted && s.feedback_score.is_some())) {
+            Self::Rejected
+        } else {
+            Self::Unreviewed
+        }
+    }
+}
+
+/// 选出最值得引用的已采纳方案：评分最高，其次最新
+pub fn best_accepted_solution(solutions: &[TicketSolution]) -> Option<&TicketSolution> {
+    solutions.iter()
+        .filter(|s| s.is_accepted)
+        .max_by_key(|s| (s.feedback_score.unwrap_or(0), s.created_at))
+}
+
+/// 检索结果加权器
+///
+/// 职责：
+/// - 按工单创建时间做指数衰减，这是检索中唯一的时效性处理
+/// - 对已采纳方案加权、对被拒绝方案降权
+/// - 重新排序并记录加权系数
+pub struct ResultBooster {
+    config: BoostConfig,
+}
+
+impl ResultBooster {
+    pub fn new(config: BoostConfig) -> Self {
+        Self { config }
+    }
+
+    /// 对融合结果加权并重新排序
+    ///
+    /// 融合分数可能为负（如仅重排序策略直接使用模型原始分数），先在候选内min-max归一化，
+    /// 再映射到 [`NORMALIZED_FLOOR`, 1] 后乘以加权系数，保证加权总是提升、降权总是降低排名，
+    /// 包括归一化后排名最后的候选；加权后 `fused` 为加权后的归一化分数
+    pub fn apply(
+        &self,
+        candidates: &mut [FusedCandidate],
+        created_at: &HashMap<Uuid, DateTime<Utc>>,
+        solutions: &HashMap<Uuid, Vec<TicketSolution>>,
+    ) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let fused: Vec<(Uuid, f32)> = candidates.iter().map(|c| (c.id, c.scores.fused)).collect();
+        for (candidate, (_, normalized)) in candidates.iter_mut().zip(min_max_normalize(&fused)) {
+            let decay = created_at.get(&candidate.id).map(|t| self.time_decay(*t));
+            let quality = solutions.get(&candidate.id)
+                .map(|s| self.quality_factor(ResolutionQuality::from_solutions(s, self.config.low_score_threshold)))
+                .unwrap_or(1.0);
+
+            let boost = decay.unwrap_or(1.0) * quality;
+            candidate.scores.recency = decay;
+            candidate.scores.boost = Some(boost);
+            candidate.scores.fused = (NORMALIZED_FLOOR + (1.0 - NORMALIZED_FLOOR) * normalized) * boost;
+        }
+
+        candidates.sort_by(|a, b| b.scores.fused.total_cmp(&a.scores.fused));
+    }
+
+    /// 时间衰减系数，不低于配置的下限
+    fn time_decay(&self, created_at: DateTime<Utc>) -> f32 {
+        recency_score(created_at, self.config.time_decay_half_life_days)
+            .max(self.config.time_decay_floor)
+    }
+
+    /// 方案质量系数
+    fn quality_factor(&self, quality: ResolutionQuality) -> f32 {
+        match quality {
+            ResolutionQuality::Accepted => 1.0 + self.config.accepted_boost,
+            ResolutionQuality::Rejected => (1.0 - self.config.rejected_penalty).max(0.0),
+            ResolutionQuality::Unreviewed => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rag_core::models::ScoreComponents;
+
+    fn booster() -> ResultBooster {
+        ResultBooster::new(BoostConfig::default())
+    }
+
+    fn candidate(fused: f32) -> FusedCandidate {
+        FusedCandidate {
+            id: Uuid::new_v4(),
+            scores: ScoreComponents { fused, ..Default::default() },
+        }
+    }
+
+    fn solution(ticket_id: Uuid, is_accepted: bool, score: Option<i32>) -> TicketSolution {
+        TicketSolution {
+            is_accepted,
+            feedback_score: score,
+            ..TicketSolution::new(ticket_id, "重启服务".to_string(), 0.8, String::new(), None)
+        }
+    }
+
+    fn ids(candidates: &[FusedCandidate]) -> Vec<Uuid> {
+        candidates.iter().map(|c| c.id).collect()
+    }
+
+    #[test]
+    fn accepted_solution_outranks_a_slightly_better_match() {
+        let mut candidates = vec![candidate(0.032), candidate(0.031), candidate(0.010)];
+        let (top, accepted) = (candidates[0].id, candidates[1].id);
+        let solutions = HashMap::from([(accepted, vec![solution(accepted, true, Some(5))])]);
+        booster().apply(&mut candidates, &HashMap::new(), &solutions);
+        assert_eq!(ids(&candidates)[..2], [accepted, top]);
+        assert_eq!(candidates[0].scores.boost, Some(1.2));
+    }
+
+    #[test]
+    fn rejected_solution_is_penalized() {
+        let mut candidates = vec![candidate(0.5), candidate(0.45), candidate(0.0)];
+        let (rejected, other) = (candidates[0].id, candidates[1].id);
+        let solutions = HashMap::from([(rejected, vec![solution(rejected, false, Some(1))])]);
+        booster().apply(&mut candidates, &HashMap::new(), &solutions);
+        assert_eq!(ids(&candidates)[..2], [other, rejected]);
+    }
+
+    #[test]
+    fn lowest_candidate_still_receives_its_boost() {
+        let mut candidates = vec![candidate(-1.0), candidate(-3.0), candidate(-3.0)];
+        let (boosted, plain) = (candidates[1].id, candidates[2].id);
+        let solutions = HashMap::from([(boosted, vec![solution(boosted, true, None)])]);
+        booster().apply(&mut candidates, &HashMap::new(), &solutions);
+        let score = |id: Uuid| candidates.iter().find(|c| c.id == id).unwrap().scores.fused;
+        assert!(score(boosted) > score(plain), "{} <= {}", score(boosted), score(plain));
+        assert!(score(plain) > 0.0);
+    }
+
+    #[test]
+    fn time_decay_prefers_recent_tickets_and_is_recorded() {
+        let mut candidates = vec![candidate(0.5), candidate(0.5)];
+        let (old, recent) = (candidates[0].id, candidates[1].id);
+        let created_at = HashMap::from([
+            (old, Utc::now() - std::time::Duration::from_secs(730 * 86_400)),
+            (recent, Utc::now()),
+        ]);
+        booster().apply(&mut candidates, &created_at, &HashMap::new());
+        assert_eq!(ids(&candidates), [recent, old]);
+        let old_decay = candidates[1].scores.recency.unwrap();
+        assert!((old_decay - 0.25).abs() < 0.01, "{}", old_decay);
+    }
+
+    #[test]
+    fn decay_never_drops_below_the_floor() {
+        let mut candidates = vec![candidate(1.0)];
+        let id = candidates[0].id;
+        let created_at = HashMap::from([(id, Utc::now() - std::time::Duration::from_secs(20 * 365 * 86_400))]);
+        booster().apply(&mut candidates, &created_at, &HashMap::new());
+        assert_eq!(candidates[0].scores.recency, Some(0.2));
+    }
+
+    #[test]
+    fn single_candidate_and_empty_lists_are_finite() {
+        let mut single = vec![candidate(-7.5)];
+        booster().apply(&mut single, &HashMap::new(), &HashMap::new());
+        assert!(single[0].scores.fused.is_finite() && single[0].scores.fused > 0.0);
+
+        let mut empty: Vec<FusedCandidate> = Vec::new();
+        booster().apply(&mut empty, &HashMap::new(), &HashMap::new());
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn disabled_booster_leaves_scores_untouched() {
+        let config = BoostConfig { enabled: false, ..BoostConfig::default() };
+        let mut candidates = vec![candidate(0.1), candidate(0.3)];
+        ResultBooster::new(config).apply(&mut candidates, &HashMap::new(), &HashMap::new());
+        assert_eq!(candidates[0].scores.fused, 0.1);
+        assert!(candidates[0].scores.boost.is_none());
+    }
+}
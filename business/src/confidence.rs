@@ -0,0 +1,442 @@
+//! # 置信度评估模块
+//!
+//! 由检索与生成过程中的多种信号计算原始置信度，并基于历史采纳反馈校准
+
+use rag_deps::*;
+use rag_core::{
+    config::ConfidenceConfig,
+    models::{ConfidenceSignals, OutputStatus, SimilarTicket},
+    keyword::tokenize,
+    traits::{Repository, SolutionRepository},
+    errors::{AppError, AppResult},
+};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// 没有任何可用信号时的置信度
+pub const DEFAULT_CONFIDENCE: f32 = 0.5;
+
+/// 结构化输出无法解析时的置信度上限
+pub const FALLBACK_CONFIDENCE: f32 = 0.2;
+
+/// 计算方案一致性时最多比较的已采纳方案数
+const AGREEMENT_MAX_SOLUTIONS: usize = 5;
+
+/// 校准方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationMethod {
+    Isotonic,
+    Platt,
+    None,
+}
+
+impl CalibrationMethod {
+    pub fn from_name(name: &str) -> AppResult<Self> {
+        match name {
+            "isotonic" => Ok(Self::Isotonic),
+            "platt" => Ok(Self::Platt),
+            "none" => Ok(Self::None),
+            _ => Err(AppError::Configuration {
+                message: format!("不支持的置信度校准方法: {}", name),
+            }),
+        }
+    }
+}
+
+/// 校准映射
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum Calibration {
+    /// 不做校准
+    Identity,
+    /// Platt缩放：sigmoid(a * x + b)
+    Platt { a: f32, b: f32 },
+    /// 保序回归：按原始置信度升序的 (原始值, 采纳率) 分段线性映射
+    Isotonic { points: Vec<(f32, f32)> },
+}
+
+impl Calibration {
+    /// 用 (原始置信度, 是否采纳) 样本拟合校准映射
+    ///
+    /// 少于两个样本或全部样本结果相同时无法区分高低置信度，不做校准
+    pub fn fit(method: CalibrationMethod, samples: &[(f32, bool)]) -> Self {
+        let single_outcome = samples.windows(2).all(|pair| pair[0].1 == pair[1].1);
+        if samples.len() < 2 || single_outcome {
+            return Self::Identity;
+        }
+        match method {
+            CalibrationMethod::Isotonic => Self::Isotonic { points: fit_isotonic(samples) },
+            CalibrationMethod::Platt => {
+                let (a, b) = fit_platt(samples);
+                Self::Platt { a, b }
+            }
+            CalibrationMethod::None => Self::Identity,
+        }
+    }
+
+    /// 将原始置信度映射为校准后的置信度
+    pub fn apply(&self, raw: f32) -> f32 {
+        let calibrated = match self {
+            Self::Identity => raw,
+            Self::Platt { a, b } => sigmoid(a * raw + b),
+            Self::Isotonic { points } => interpolate(points, raw),
+        };
+        calibrated.clamp(0.0, 1.0)
+    }
+}
+
+/// 校准状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationState {
+    pub calibration: Calibration,
+    pub samples: usize,
+    pub acceptance_rate: Option<f32>,
+    pub fitted_at: Option<DateTime<Utc>>,
+}
+
+/// 置信度校准器
+///
+/// 职责：
+/// - 按配置的权重合并各路信号得到原始置信度
+/// - 用有反馈的历史方案拟合校准映射，使置信度接近实际采纳率
+pub struct ConfidenceCalibrator {
+    config: ConfidenceConfig,
+    method: CalibrationMethod,
+    state: RwLock<CalibrationState>,
+}
+
+impl ConfidenceCalibrator {
+    pub fn new(config: ConfidenceConfig) -> AppResult<Self> {
+        let method = CalibrationMethod::from_name(&config.calibration)?;
+        Ok(Self {
+            config,
+            method,
+            state: RwLock::new(CalibrationState {
+                calibration: Calibration::Identity,
+                samples: 0,
+                acceptance_rate: None,
+                fitted_at: None,
+            }),
+        })
+    }
+
+    /// 生成时是否请求token对数概率
+    pub fn requests_logprobs(&self) -> bool {
+        self.config.request_logprobs && self.config.token_prob_weight > 0.0
+    }
+
+    /// 当前校准状态
+    pub fn state(&self) -> CalibrationState {
+        self.state.read().unwrap().clone()
+    }
+
+    /// 计算原始置信度和校准后的置信度
+    pub fn score(&self, signals: &ConfidenceSignals, status: OutputStatus) -> (f32, f32) {
+        let raw = self.raw_confidence(signals, status);
+        (raw, self.state.read().unwrap().calibration.apply(raw))
+    }
+
+    /// 按权重合并信号，输出无法解析时不超过回退置信度
+    pub fn raw_confidence(&self, signals: &ConfidenceSignals, status: OutputStatus) -> f32 {
+        let weighted = [
+            (signals.top_rerank, self.config.rerank_weight),
+            (signals.agreement, self.config.agreement_weight),
+            (signals.self_rating, self.config.self_rating_weight),
+            (signals.token_prob, self.config.token_prob_weight),
+        ];
+        let (sum, weight) = weighted.iter()
+            .filter_map(|(value, weight)| value.filter(|_| *weight > 0.0).map(|v| (v.clamp(0.0, 1.0), *weight)))
+            .fold((0.0, 0.0), |(sum, total), (value, weight)| (sum + value * weight, total + weight));
+
+        let raw = if weight > 0.0 { sum / weight } else { DEFAULT_CONFIDENCE };
+        match status {
+            OutputStatus::Fallback => raw.min(FALLBACK_CONFIDENCE),
+            _ => raw,
+        }
+    }
+
+    /// 基于历史反馈重新拟合校准映射，样本不足时不做校准
+    pub async fn refit(&self, history: &dyn SolutionRepository) -> AppResult<CalibrationState> {
+        let samples: Vec<(f32, bool)> = history
+            .list_reviewed_solutions(self.config.max_samples)
+            .await?
+            .iter()
+            .filter_map(|s| s.raw_confidence.map(|raw| (raw, s.is_accepted)))
+            .collect();
+
+        let acceptance_rate = (!samples.is_empty())
+            .then(|| samples.iter().filter(|(_, accepted)| *accepted).count() as f32 / samples.len() as f32);
+        let calibration = if samples.len() < self.config.min_samples {
+            info!("置信度校准样本不足（{} < {}），不做校准", samples.len(), self.config.min_samples);
+            Calibration::Identity
+        } else {
+            Calibration::fit(self.method, &samples)
+        };
+
+        let state = CalibrationState {
+            calibration,
+            samples: samples.len(),
+            acceptance_rate,
+            fitted_at: Some(Utc::now()),
+        };
+        info!("置信度校准完成: {:?}", state.calibration);
+        *self.state.write().unwrap() = state.clone();
+        Ok(state)
+    }
+
+    /// 启动校准任务：立即拟合一次，之后按间隔定期重新拟合；间隔为0时只拟合一次
+    pub fn spawn_recalibrator(self: &Arc<Self>, history: Arc<dyn Repository>) -> tokio::task::JoinHandle<()> {
+        let calibrator = self.clone();
+        let interval = Duration::from_secs(self.config.refit_interval_secs);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = calibrator.refit(history.as_ref()).await {
+                    warn!("置信度校准失败: {}", e);
+                }
+                if interval.is_zero() {
+                    break;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
+
+/// 由检索结果和生成结果收集置信度信号
+pub fn collect_signals(
+    similar_tickets: &[SimilarTicket],
+    self_rating: Option<f32>,
+    avg_logprob: Option<f32>,
+) -> ConfidenceSignals {
+    ConfidenceSignals {
+        top_rerank: top_rerank_probability(similar_tickets),
+        agreement: solution_agreement(similar_tickets),
+        self_rating,
+        token_prob: avg_logprob.map(|logprob| logprob.exp().clamp(0.0, 1.0)),
+    }
+}
+
+/// 最高的重排序分数，映射为0-1的相关概率
+///
+/// 分数都在 [0, 1] 内时视为模型已输出概率，直接使用；否则视为未归一化的logit，经sigmoid映射，
+/// 避免负分被截断为0后各候选失去区分
+fn top_rerank_probability(similar_tickets: &[SimilarTicket]) -> Option<f32> {
+    let scores: Vec<f32> = similar_tickets.iter().filter_map(|t| t.scores.rerank).collect();
+    let top = scores.iter().copied().reduce(f32::max)?;
+    if scores.iter().all(|score| (0.0..=1.0).contains(score)) {
+        Some(top)
+    } else {
+        Some(sigmoid(top))
+    }
+}
+
+/// 相似工单已采纳方案之间的平均两两词项Jaccard相似度，少于两个方案时为空
+fn solution_agreement(similar_tickets: &[SimilarTicket]) -> Option<f32> {
+    let token_sets: Vec<HashSet<String>> = similar_tickets.iter()
+        .filter_map(|t| t.solution.as_deref())
+        .take(AGREEMENT_MAX_SOLUTIONS)
+        .map(|solution| tokenize(solution).into_iter().collect::<HashSet<_>>())
+        .filter(|tokens| !tokens.is_empty())
+        .collect();
+    if token_sets.len() < 2 {
+        return None;
+    }
+
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for (i, a) in token_sets.iter().enumerate() {
+        for b in &token_sets[i + 1..] {
+            total += a.intersection(b).count() as f32 / a.union(b).count() as f32;
+            pairs += 1;
+        }
+    }
+    Some(total / pairs as f32)
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Platt缩放：带目标平滑的逻辑回归，牛顿法求解
+fn fit_platt(samples: &[(f32, bool)]) -> (f32, f32) {
+    let positives = samples.iter().filter(|(_, accepted)| *accepted).count() as f64;
+    let negatives = samples.len() as f64 - positives;
+    let target_hi = (positives + 1.0) / (positives + 2.0);
+    let target_lo = 1.0 / (negatives + 2.0);
+
+    let (mut a, mut b) = (1.0_f64, 0.0_f64);
+    for _ in 0..100 {
+        let (mut g_a, mut g_b, mut h_aa, mut h_ab, mut h_bb) = (0.0, 0.0, 1e-6, 0.0, 1e-6);
+        for (x, accepted) in samples {
+            let x = *x as f64;
+            let target = if *accepted { target_hi } else { target_lo };
+            let p = 1.0 / (1.0 + (-(a * x + b)).exp());
+            let d = p - target;
+            let w = p * (1.0 - p);
+            g_a += d * x;
+            g_b += d;
+            h_aa += w * x * x;
+            h_ab += w * x;
+            h_bb += w;
+        }
+
+        let det = h_aa * h_bb - h_ab * h_ab;
+        if det.abs() < 1e-12 {
+            break;
+        }
+        let delta_a = (h_bb * g_a - h_ab * g_b) / det;
+        let delta_b = (h_aa * g_b - h_ab * g_a) / det;
+        a -= delta_a;
+        b -= delta_b;
+        if delta_a.abs() < 1e-7 && delta_b.abs() < 1e-7 {
+            break;
+        }
+    }
+    (a as f32, b as f32)
+}
+
+/// 保序回归（PAV算法），返回每个合并区间的 (平均原始值, 采纳率)
+fn fit_isotonic(samples: &[(f32, bool)]) -> Vec<(f32, f32)> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // (原始值之和, 采纳数, 样本数)
+    let mut blocks: Vec<(f32, f32, f32)> = Vec::new();
+    for (x, accepted) in sorted {
+        blocks.push((x, if accepted { 1.0 } else { 0.0 }, 1.0));
+        while blocks.len() > 1 {
+            let (x2, y2, n2) = blocks[blocks.len() - 1];
+            let (x1, y1, n1) = blocks[blocks.len() - 2];
+            if y1 / n1 <= y2 / n2 {
+                break;
+            }
+            blocks.truncate(blocks.len() - 2);
+            blocks.push((x1 + x2, y1 + y2, n1 + n2));
+        }
+    }
+
+    blocks.into_iter().map(|(x, y, n)| (x / n, y / n)).collect()
+}
+
+/// 分段线性插值，超出范围时取端点值
+fn interpolate(points: &[(f32, f32)], x: f32) -> f32 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else { return x };
+    if x <= first.0 {
+        return first.1;
+    }
+    if x >= last.0 {
+        return last.1;
+    }
+    for window in points.windows(2) {
+        let ((x1, y1), (x2, y2)) = (window[0], window[1]);
+        if x <= x2 {
+            if x2 - x1 <= f32::EPSILON {
+                return y2;
+            }
+            return y1 + (y2 - y1) * (x - x1) / (x2 - x1);
+        }
+    }
+    last.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rag_core::models::ScoreComponents;
+
+    fn similar(rerank: f32) -> SimilarTicket {
+        SimilarTicket {
+            ticket_id: Uuid::new_v4(),
+            title: String::new(),
+            description: String::new(),
+            similarity_score: 0.0,
+            rerank_score: rerank,
+            scores: ScoreComponents { rerank: Some(rerank), ..Default::default() },
+            solution_id: None,
+            solution: None,
+            quarantined: false,
+        }
+    }
+
+    /// 原始置信度越高采纳率越高，中间有交错
+    fn samples() -> Vec<(f32, bool)> {
+        (0..40)
+            .map(|i| {
+                let raw = i as f32 / 40.0;
+                (raw, i % 4 != 0 && (raw > 0.3 || i % 3 == 0))
+            })
+            .collect()
+    }
+
+    fn assert_monotonic(calibration: &Calibration) {
+        let mapped: Vec<f32> = (0..=20).map(|i| calibration.apply(i as f32 / 20.0)).collect();
+        assert!(mapped.windows(2).all(|pair| pair[0] <= pair[1] + 1e-6), "{:?}", mapped);
+        assert!(mapped.iter().all(|value| (0.0..=1.0).contains(value)));
+    }
+
+    #[test]
+    fn negative_rerank_logits_keep_their_order() {
+        let low = collect_signals(&[similar(-4.0), similar(-6.0)], None, None).top_rerank.unwrap();
+        let high = collect_signals(&[similar(-1.0), similar(-6.0)], None, None).top_rerank.unwrap();
+        assert!(low > 0.0 && low < high, "{} {}", low, high);
+        assert!((high - sigmoid(-1.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rerank_probabilities_are_used_directly() {
+        let signals = collect_signals(&[similar(0.35), similar(0.8)], None, None);
+        assert_eq!(signals.top_rerank, Some(0.8));
+        assert_eq!(collect_signals(&[], None, None).top_rerank, None);
+    }
+
+    #[test]
+    fn isotonic_fit_is_monotonic() {
+        let calibration = Calibration::fit(CalibrationMethod::Isotonic, &samples());
+        let Calibration::Isotonic { points } = &calibration else { panic!("应为保序回归: {:?}", calibration) };
+        assert!(points.windows(2).all(|pair| pair[0].0 <= pair[1].0 && pair[0].1 <= pair[1].1), "{:?}", points);
+        assert_monotonic(&calibration);
+    }
+
+    #[test]
+    fn isotonic_pools_violating_neighbours() {
+        let points = fit_isotonic(&[(0.1, true), (0.2, false), (0.3, true)]);
+        assert_eq!(points.len(), 2);
+        assert!((points[0].0 - 0.15).abs() < 1e-6 && (points[0].1 - 0.5).abs() < 1e-6);
+        assert_eq!(points[1], (0.3, 1.0));
+    }
+
+    #[test]
+    fn platt_fit_is_increasing() {
+        let calibration = Calibration::fit(CalibrationMethod::Platt, &samples());
+        let Calibration::Platt { a, .. } = calibration else { panic!("应为Platt缩放: {:?}", calibration) };
+        assert!(a > 0.0, "{}", a);
+        assert_monotonic(&calibration);
+    }
+
+    #[test]
+    fn degenerate_samples_are_not_calibrated() {
+        let cases: [&[(f32, bool)]; 4] = [
+            &[],
+            &[(0.7, true)],
+            &[(0.2, true), (0.5, true), (0.9, true)],
+            &[(0.2, false), (0.5, false), (0.9, false)],
+        ];
+        for samples in cases {
+            for method in [CalibrationMethod::Isotonic, CalibrationMethod::Platt] {
+                let calibration = Calibration::fit(method, samples);
+                assert!(matches!(calibration, Calibration::Identity), "{:?} {:?}", samples, calibration);
+                assert_eq!(calibration.apply(0.42), 0.42);
+            }
+        }
+    }
+
+    #[test]
+    fn interpolation_clamps_to_end_points() {
+        let calibration = Calibration::Isotonic { points: vec![(0.2, 0.1), (0.8, 0.9)] };
+        assert_eq!(calibration.apply(0.0), 0.1);
+        assert_eq!(calibration.apply(1.0), 0.9);
+        assert!((calibration.apply(0.5) - 0.5).abs() < 1e-6);
+    }
+}
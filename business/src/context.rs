@@ -0,0 +1,131 @@
+//! # Prompt上下文组装模块
+//!
+//! 在模型上下文窗口的token预算内挑选和截断相似案例
+
+use rag_deps::*;
+use rag_core::{
+    config::ContextConfig,
+    models::{ContextReport, DroppedCase, SimilarTicket},
+    prompt::{format_context, TokenEstimator},
+    traits::reranking::RerankResult,
+};
+
+/// 组装好的上下文
+#[derive(Debug, Clone)]
+pub struct PackedContext {
+    pub cases: Vec<RerankResult>, // index 指向输入的相似工单
+    pub ticket_ids: Vec<Uuid>,    // 与 cases 一一对应
+    pub report: ContextReport,
+}
+
+/// 上下文组装器
+///
+/// 职责：
+/// - 由上下文窗口扣除预留输出计算可用预算
+/// - 按融合排名依次放入相似案例及其已采纳方案，过长的描述和方案截断保留首尾
+/// - 预算不足时截断最后一个案例或直接丢弃，并记录原因
+/// - 隔离的疑似提示注入工单不放入上下文
+#[derive(Debug, Clone)]
+pub struct ContextBuilder {
+    config: ContextConfig,
+    reserved_output_tokens: usize,
+}
+
+impl ContextBuilder {
+    /// `default_output_tokens` 在未配置预留输出时使用，通常为LLM的 `max_tokens`
+    pub fn new(config: ContextConfig, default_output_tokens: usize) -> Self {
+        let reserved_output_tokens = config.reserved_output_tokens.unwrap_or(default_output_tokens);
+        Self { config, reserved_output_tokens }
+    }
+
+    /// Prompt可用token预算，单次请求指定了输出长度时按该长度预留
+    pub fn budget(&self, context_window: usize, max_output_tokens: Option<usize>) -> usize {
+        context_window.saturating_sub(max_output_tokens.unwrap_or(self.reserved_output_tokens))
+    }
+
+    /// 在预算内组装相似案例，`base_tokens` 为不含案例的Prompt本身的开销
+    pub fn pack(
+        &self,
+        similar_tickets: &[SimilarTicket],
+        estimator: &TokenEstimator,
+        budget: usize,
+        base_tokens: usize,
+    ) -> PackedContext {
+        let mut remaining = budget.saturating_sub(base_tokens);
+        let mut packed = PackedContext {
+            cases: Vec::new(),
+            ticket_ids: Vec::new(),
+            report: ContextReport {
+                budget_tokens: budget,
+                ..Default::default()
+            },
+        };
+
+        for (index, ticket) in similar_tickets.iter().enumerate() {
+            if ticket.quarantined {
+                packed.report.dropped.push(DroppedCase {
+                    ticket_id: ticket.ticket_id,
+                    estimated_tokens: 0,
+                    reason: "疑似包含提示注入，已隔离".to_string(),
+                });
+                continue;
+            }
+            let (mut document, mut truncated) = self.document(ticket, estimator);
+            let mut case = RerankResult { index, score: ticket.scores.fused, document: document.clone() };
+            let cost = case_tokens(&case, ticket.ticket_id, estimator);
+
+            if cost > remaining {
+                if remaining < self.config.min_case_tokens {
+                    packed.report.dropped.push(DroppedCase {
+                        ticket_id: ticket.ticket_id,
+                        estimated_tokens: cost,
+                        reason: format!("超出上下文预算（需要 {}，剩余 {}）", cost, remaining),
+                    });
+                    continue;
+                }
+                let overhead = cost.saturating_sub(estimator.estimate(&document));
+                document = estimator.trim_middle(&document, remaining.saturating_sub(overhead));
+                truncated = true;
+                case.document = document;
+            }
+
+            let cost = case_tokens(&case, ticket.ticket_id, estimator).min(remaining);
+            remaining -= cost;
+            packed.report.used_tokens += cost;
+            packed.report.included.push(ticket.ticket_id);
+            if truncated {
+                packed.report.truncated.push(ticket.ticket_id);
+            }
+            packed.ticket_ids.push(ticket.ticket_id);
+            packed.cases.push(case);
+        }
+
+        packed.report.used_tokens += base_tokens;
+        if !packed.report.dropped.is_empty() {
+            info!(
+                "上下文预算 {} tokens，放入 {} 个案例，丢弃 {} 个",
+                budget, packed.report.included.len(), packed.report.dropped.len(),
+            );
+        }
+        packed
+    }
+
+    /// 相似案例文本：标题、描述与已采纳方案，返回是否发生截断
+    fn document(&self, ticket: &SimilarTicket, estimator: &TokenEstimator) -> (String, bool) {
+        let description = estimator.trim_middle(&ticket.description, self.config.max_description_tokens);
+        let mut truncated = description.len() != ticket.description.len();
+        let mut document = format!("{} {}", ticket.title, description);
+
+        if let Some(solution) = &ticket.solution {
+            let trimmed = estimator.trim_middle(solution, self.config.max_solution_tokens);
+            truncated |= trimmed.len() != solution.len();
+            document = format!("{}\n历史方案: {}", document, trimmed);
+        }
+        (document, truncated)
+    }
+}
+
+/// 单个案例在上下文中的token开销，含序号、工单ID等标注
+fn case_tokens(case: &RerankResult, ticket_id: Uuid, estimator: &TokenEstimator) -> usize {
+    estimator.estimate(&format_context(std::slice::from_ref(case), &[ticket_id]))
+}
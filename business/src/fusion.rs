@@ -0,0 +1,313 @@
+//! # 分数融合模块
+//!
+//! 将向量检索、关键词检索、重排序等多路排序结果融合为最终排名；时效性由加权阶段处理
+
+use rag_deps::*;
+use rag_core::{
+    config::RetrievalConfig,
+    models::ScoreComponents,
+    errors::{AppError, AppResult},
+};
+use std::collections::HashMap;
+
+/// 排序来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RankingSource {
+    Dense,   // 向量相似度
+    Keyword, // 关键词/BM25
+    Rerank,  // 重排序模型
+}
+
+/// 单路排序结果
+///
+/// `scores` 中分数越高表示越相关，顺序不要求有序
+#[derive(Debug, Clone)]
+pub struct Ranking {
+    pub source: RankingSource,
+    pub scores: Vec<(Uuid, f32)>,
+}
+
+/// 各路排序的权重
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionWeights {
+    pub dense: f32,
+    pub keyword: f32,
+    pub rerank: f32,
+}
+
+/// 融合策略
+///
+/// 职责：
+/// - RRF：只依赖名次，对各路分数尺度不敏感
+/// - 加权线性：对每路分数做min-max归一化后加权求和
+/// - 仅重排序：直接使用重排序分数，缺失时回退到向量分数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FusionStrategy {
+    ReciprocalRank { k: f32, weights: FusionWeights },
+    WeightedLinear { weights: FusionWeights },
+    RerankOnly,
+}
+
+/// 融合后的候选
+#[derive(Debug, Clone)]
+pub struct FusedCandidate {
+    pub id: Uuid,
+    pub scores: ScoreComponents,
+}
+
+impl FusionWeights {
+    fn weight(&self, source: RankingSource) -> f32 {
+        match source {
+            RankingSource::Dense => self.dense,
+            RankingSource::Keyword => self.keyword,
+            RankingSource::Rerank => self.rerank,
+        }
+    }
+}
+
+impl From<&RetrievalConfig> for FusionWeights {
+    fn from(config: &RetrievalConfig) -> Self {
+        Self {
+            dense: config.dense_weight,
+            keyword: config.keyword_weight,
+            rerank: config.rerank_weight,
+        }
+    }
+}
+
+impl FusionStrategy {
+    /// 根据配置创建融合策略
+    pub fn from_config(config: &RetrievalConfig) -> AppResult<Self> {
+        Self::from_name(&config.fusion_strategy, config)
+    }
+
+    /// 根据策略名称创建融合策略，参数取自配置
+    pub fn from_name(name: &str, config: &RetrievalConfig) -> AppResult<Self> {
+        match name {
+            "rrf" => Ok(Self::ReciprocalRank {
+                k: config.rrf_k,
+                weights: config.into(),
+            }),
+            "weighted" => Ok(Self::WeightedLinear {
+                weights: config.into(),
+            }),
+            "rerank_only" => Ok(Self::RerankOnly),
+            _ => Err(AppError::Configuration {
+                message: format!("不支持的融合策略: {}", name),
+            }),
+        }
+    }
+
+    /// 融合多路排序，按融合分数降序返回
+    pub fn fuse(&self, rankings: &[Ranking]) -> Vec<FusedCandidate> {
+        let mut candidates: HashMap<Uuid, ScoreComponents> = HashMap::new();
+
+        // 记录每一路的原始分数
+        for ranking in rankings {
+            for (id, score) in &ranking.scores {
+                let components = candidates.entry(*id).or_default();
+                let slot = match ranking.source {
+                    RankingSource::Dense => &mut components.dense,
+                    RankingSource::Keyword => &mut components.keyword,
+                    RankingSource::Rerank => &mut components.rerank,
+                };
+                *slot = Some(*score);
+            }
+        }
+
+        // 计算融合分数
+        match self {
+            Self::ReciprocalRank { k, weights } => {
+                for ranking in rankings {
+                    let weight = weights.weight(ranking.source);
+                    if weight == 0.0 {
+                        continue;
+                    }
+                    for (rank, (id, _)) in sorted_desc(&ranking.scores).into_iter().enumerate() {
+                        if let Some(components) = candidates.get_mut(&id) {
+                            components.fused += weight / (k + rank as f32 + 1.0);
+                        }
+                    }
+                }
+            }
+            Self::WeightedLinear { weights } => {
+                for ranking in rankings {
+                    let weight = weights.weight(ranking.source);
+                    if weight == 0.0 {
+                        continue;
+                    }
+                    for (id, normalized) in min_max_normalize(&ranking.scores) {
+                        if let Some(components) = candidates.get_mut(&id) {
+                            components.fused += weight * normalized;
+                        }
+                    }
+                }
+            }
+            Self::RerankOnly => {
+                let has_rerank = rankings.iter()
+                    .any(|r| r.source == RankingSource::Rerank && !r.scores.is_empty());
+                for components in candidates.values_mut() {
+                    components.fused = if has_rerank {
+                        components.rerank.unwrap_or(0.0)
+                    } else {
+                        components.dense.unwrap_or(0.0)
+                    };
+                }
+            }
+        }
+
+        let mut fused: Vec<FusedCandidate> = candidates.into_iter()
+            .map(|(id, scores)| FusedCandidate { id, scores })
+            .collect();
+        fused.sort_by(|a, b| b.scores.fused.total_cmp(&a.scores.fused));
+        fused
+    }
+}
+
+/// 时效性分数：按半衰期指数衰减，新工单接近1
+pub fn recency_score(created_at: DateTime<Utc>, half_life_days: f32) -> f32 {
+    if half_life_days <= 0.0 {
+        return 1.0;
+    }
+    let age_days = (Utc::now() - created_at).num_seconds().max(0) as f32 / 86_400.0;
+    0.5_f32.powf(age_days / half_life_days)
+}
+
+/// 按分数降序排列
+fn sorted_desc(scores: &[(Uuid, f32)]) -> Vec<(Uuid, f32)> {
+    let mut sorted = scores.to_vec();
+    sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+    sorted
+}
+
+/// min-max归一化到[0, 1]，分数全部相同时视为1
+pub(crate) fn min_max_normalize(scores: &[(Uuid, f32)]) -> Vec<(Uuid, f32)> {
+    let min = scores.iter().map(|(_, s)| *s).fold(f32::INFINITY, f32::min);
+    let max = scores.iter().map(|(_, s)| *s).fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+
+    scores.iter()
+        .map(|(id, s)| {
+            let normalized = if range > f32::EPSILON { (s - min) / range } else { 1.0 };
+            (*id, normalized)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights() -> FusionWeights {
+        FusionWeights { dense: 1.0, keyword: 1.0, rerank: 1.0 }
+    }
+
+    fn ranking(source: RankingSource, scores: &[(Uuid, f32)]) -> Ranking {
+        Ranking { source, scores: scores.to_vec() }
+    }
+
+    fn fused(candidates: &[FusedCandidate], id: Uuid) -> &ScoreComponents {
+        &candidates.iter().find(|c| c.id == id).expect("候选应存在").scores
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn rrf_sums_weighted_reciprocal_ranks() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let strategy = FusionStrategy::ReciprocalRank {
+            k: 60.0,
+            weights: FusionWeights { keyword: 2.0, ..weights() },
+        };
+        let candidates = strategy.fuse(&[
+            ranking(RankingSource::Dense, &[(b, 0.7), (a, 0.9), (c, 0.1)]),
+            ranking(RankingSource::Keyword, &[(b, 3.0), (a, 1.5)]),
+        ]);
+
+        assert!(close(fused(&candidates, a).fused, 1.0 / 61.0 + 2.0 / 62.0));
+        assert!(close(fused(&candidates, b).fused, 1.0 / 62.0 + 2.0 / 61.0));
+        assert_eq!(candidates.iter().map(|c| c.id).collect::<Vec<_>>(), [b, a, c]);
+    }
+
+    #[test]
+    fn rrf_counts_a_single_list_candidate_once() {
+        let (a, only_keyword) = (Uuid::new_v4(), Uuid::new_v4());
+        let strategy = FusionStrategy::ReciprocalRank { k: 60.0, weights: weights() };
+        let candidates = strategy.fuse(&[
+            ranking(RankingSource::Dense, &[(a, 0.9)]),
+            ranking(RankingSource::Keyword, &[(only_keyword, 2.0), (a, 1.0)]),
+        ]);
+
+        let scores = fused(&candidates, only_keyword);
+        assert!(close(scores.fused, 1.0 / 61.0));
+        assert_eq!((scores.dense, scores.keyword), (None, Some(2.0)));
+        assert!(close(fused(&candidates, a).fused, 1.0 / 61.0 + 1.0 / 62.0));
+    }
+
+    #[test]
+    fn rrf_ignores_zero_weight_rankings() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let strategy = FusionStrategy::ReciprocalRank {
+            k: 60.0,
+            weights: FusionWeights { rerank: 0.0, ..weights() },
+        };
+        let candidates = strategy.fuse(&[
+            ranking(RankingSource::Dense, &[(a, 0.9), (b, 0.8)]),
+            ranking(RankingSource::Rerank, &[(b, 5.0), (a, 1.0)]),
+        ]);
+        assert_eq!(candidates[0].id, a);
+        assert_eq!(fused(&candidates, b).rerank, Some(5.0));
+    }
+
+    #[test]
+    fn weighted_fusion_normalizes_each_ranking() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let strategy = FusionStrategy::WeightedLinear {
+            weights: FusionWeights { dense: 1.0, keyword: 0.5, rerank: 2.0 },
+        };
+        let candidates = strategy.fuse(&[
+            ranking(RankingSource::Dense, &[(a, 0.9), (b, 0.5), (c, 0.1)]),
+            ranking(RankingSource::Keyword, &[(a, 12.0), (c, 4.0)]),
+            ranking(RankingSource::Rerank, &[(a, -2.0), (b, 6.0), (c, 2.0)]),
+        ]);
+
+        assert!(close(fused(&candidates, a).fused, 1.0 + 0.5));
+        assert!(close(fused(&candidates, b).fused, 0.5 + 2.0));
+        assert!(close(fused(&candidates, c).fused, 0.0 + 0.0 + 2.0 * 0.5));
+        assert_eq!(candidates.iter().map(|c| c.id).collect::<Vec<_>>(), [b, a, c]);
+    }
+
+    #[test]
+    fn weighted_fusion_treats_equal_scores_as_full_match() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let strategy = FusionStrategy::WeightedLinear { weights: weights() };
+        let candidates = strategy.fuse(&[ranking(RankingSource::Keyword, &[(a, 3.0), (b, 3.0)])]);
+        assert!(candidates.iter().all(|c| close(c.scores.fused, 1.0)));
+    }
+
+    #[test]
+    fn rerank_only_falls_back_to_dense_scores() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let dense = ranking(RankingSource::Dense, &[(a, 0.9), (b, 0.4)]);
+
+        let reranked = FusionStrategy::RerankOnly.fuse(&[
+            dense.clone(),
+            ranking(RankingSource::Rerank, &[(b, 0.8)]),
+        ]);
+        assert_eq!(reranked.iter().map(|c| (c.id, c.scores.fused)).collect::<Vec<_>>(), [(b, 0.8), (a, 0.0)]);
+
+        let dense_only = FusionStrategy::RerankOnly.fuse(&[dense, ranking(RankingSource::Rerank, &[])]);
+        assert_eq!(dense_only.iter().map(|c| (c.id, c.scores.fused)).collect::<Vec<_>>(), [(a, 0.9), (b, 0.4)]);
+    }
+
+    #[test]
+    fn strategies_are_created_by_name() {
+        let config = RetrievalConfig::default();
+        assert!(matches!(FusionStrategy::from_name("rrf", &config), Ok(FusionStrategy::ReciprocalRank { .. })));
+        assert!(matches!(FusionStrategy::from_name("weighted", &config), Ok(FusionStrategy::WeightedLinear { .. })));
+        assert!(matches!(FusionStrategy::from_name("rerank_only", &config), Ok(FusionStrategy::RerankOnly)));
+        assert!(FusionStrategy::from_name("borda", &config).is_err());
+    }
+}
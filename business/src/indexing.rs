@@ -0,0 +1,259 @@
+//! # 工单向量索引模块
+//!
+//! 以关系库为准：工单变更与索引事件（outbox）在同一事务中写入，后台按事件把变更
+//! 幂等地同步到向量库，失败时指数退避重试。定期对账比较工单ID与向量ID，修复
+//! 事件丢失或重试耗尽造成的不一致
+
+use rag_deps::*;
+use rag_core::{
+    config::IndexingConfig,
+    models::{IndexEvent, IndexEventKind, IndexEventStatus, Ticket},
+    traits::*,
+    traits::vector_db::{VectorMetadata, content_hash},
+    errors::AppResult,
+};
+use crate::usage::{UsageScope, with_scope};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 对账结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReconcileReport {
+    pub tickets_scanned: usize,
+    pub vectors_scanned: usize,
+    pub missing_vectors: usize, // 缺少向量或向量内容过期的工单
+    pub orphan_vectors: usize,  // 工单已删除的向量
+    pub orphan_scan_skipped: bool, // 向量库不支持遍历或遍历失败，未完成孤立向量检查
+}
+
+/// 工单向量索引
+///
+/// 职责：
+/// - 向量化工单全文并写入向量库，成功后回写 `Ticket::embedding`
+/// - 消费索引事件，按工单当前状态幂等地同步向量库
+/// - 对账工单表与向量库，为不一致的工单补发事件
+pub struct TicketIndexer {
+    embedding_service: Arc<dyn EmbeddingService + Send + Sync>,
+    vector_db: Arc<dyn VectorDatabase + Send + Sync>,
+    database: Arc<dyn Repository>,
+    config: IndexingConfig,
+}
+
+impl TicketIndexer {
+    pub fn new(
+        embedding_service: Arc<dyn EmbeddingService + Send + Sync>,
+        vector_db: Arc<dyn VectorDatabase + Send + Sync>,
+        database: Arc<dyn Repository>,
+        config: IndexingConfig,
+    ) -> Self {
+        Self { embedding_service, vector_db, database, config }
+    }
+
+    /// 向量化工单及其评论、附件错误行并写入向量库，全部成功后才更新 `ticket`
+    ///
+    /// 向量库写入按ID覆盖，回写关系库失败时重试不会产生重复向量
+    pub async fn index(&self, ticket: &mut Ticket) -> AppResult<()> {
+        let (text, metadata) = self.indexed_content(ticket).await?;
+        self.write(ticket, &text, metadata).await
+    }
+
+    /// 按工单当前状态同步向量库
+    ///
+    /// 工单不存在时删除向量；向量化文本未变化时只更新元数据，避免重复向量化
+    pub async fn sync(&self, ticket_id: Uuid) -> AppResult<()> {
+        let Some(mut ticket) = self.database.get_ticket(ticket_id).await? else {
+            self.vector_db.delete(ticket_id).await?;
+            debug!("工单 {} 已从向量索引删除", ticket_id);
+            return Ok(());
+        };
+        let (text, metadata) = self.indexed_content(&ticket).await?;
+        let record = match self.vector_db.get(ticket_id).await? {
+            Some(record) if is_current(&record.metadata, &ticket, &text) => record,
+            _ => return self.write(&mut ticket, &text, metadata).await,
+        };
+        if record.metadata.category != ticket.category
+            || record.metadata.priority != ticket.priority
+            || record.metadata.content_hash.is_none()
+        {
+            self.vector_db.update(ticket_id, &record.vector, Some(metadata)).await?;
+        }
+        if ticket.embedding.is_none() {
+            self.database.set_ticket_embedding(ticket_id, Some(&record.vector)).await?;
+        }
+        Ok(())
+    }
+
+    /// 处理一批到期的索引事件，返回成功数量
+    pub async fn process_outbox(&self) -> AppResult<usize> {
+        let events = self.database.due_index_events(Utc::now(), self.config.batch_size.max(1)).await?;
+        let mut processed = 0;
+        for mut event in events {
+            match self.sync(event.ticket_id).await {
+                Ok(()) => {
+                    self.database.complete_index_event(event.id).await?;
+                    processed += 1;
+                }
+                Err(e) => {
+                    self.record_failure(&mut event, &e.to_string());
+                    self.database.update_index_event(&event).await?;
+                }
+            }
+        }
+        Ok(processed)
+    }
+
+    /// 启动后台事件处理任务，按配置间隔轮询
+    pub fn spawn_outbox_worker(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let indexer = self.clone();
+        let interval = Duration::from_millis(self.config.poll_interval_ms.max(1));
+        tokio::spawn(async move {
+            loop {
+                match indexer.process_outbox().await {
+                    // 满批时可能还有积压，立即继续
+                    Ok(processed) if processed >= indexer.config.batch_size.max(1) => continue,
+                    Ok(_) => {}
+                    Err(e) => warn!("处理索引事件失败: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    /// 对账工单表与向量库，为不一致的工单补发索引事件
+    ///
+    /// 先遍历工单检查向量是否存在且内容最新，再遍历向量检查工单是否已删除；
+    /// 向量库不支持遍历时跳过孤立向量检查，只记录警告
+    pub async fn reconcile(&self) -> AppResult<ReconcileReport> {
+        let batch_size = self.config.reconcile_batch_size.max(1);
+        let mut report = ReconcileReport::default();
+
+        let mut after = None;
+        loop {
+            let tickets = self.database.scroll_tickets(after, batch_size).await?;
+            for ticket in &tickets {
+                let (text, _) = self.indexed_content(ticket).await?;
+                let fresh = self.vector_db.get(ticket.id).await?
+                    .is_some_and(|record| is_current(&record.metadata, ticket, &text));
+                if !fresh {
+                    self.enqueue(ticket.id, IndexEventKind::Upsert).await?;
+                    report.missing_vectors += 1;
+                }
+            }
+            report.tickets_scanned += tickets.len();
+            match tickets.last() {
+                Some(last) if tickets.len() == batch_size => after = Some(last.id),
+                _ => break,
+            }
+        }
+
+        let mut after = None;
+        loop {
+            let ids = match self.vector_db.scroll(after, batch_size).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    warn!("无法遍历向量库，跳过孤立向量检查: {}", e);
+                    report.orphan_scan_skipped = true;
+                    break;
+                }
+            };
+            for &id in &ids {
+                if self.database.get_ticket(id).await?.is_none() {
+                    self.enqueue(id, IndexEventKind::Delete).await?;
+                    report.orphan_vectors += 1;
+                }
+            }
+            report.vectors_scanned += ids.len();
+            match ids.last() {
+                Some(&last) if ids.len() == batch_size => after = Some(last),
+                _ => break,
+            }
+        }
+
+        if report.missing_vectors + report.orphan_vectors > 0 {
+            info!(
+                "向量索引对账发现 {} 个缺失或过期、{} 个孤立向量，已补发索引事件",
+                report.missing_vectors, report.orphan_vectors,
+            );
+        }
+        Ok(report)
+    }
+
+    /// 启动定期对账任务，间隔为0时不启动
+    pub fn spawn_reconciler(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        if self.config.reconcile_interval_secs == 0 {
+            return None;
+        }
+        let indexer = self.clone();
+        let interval = Duration::from_secs(self.config.reconcile_interval_secs);
+        Some(tokio::spawn(async move {
+            loop {
+                if let Err(e) = indexer.reconcile().await {
+                    warn!("向量索引对账失败: {}", e);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }))
+    }
+
+    /// 工单的向量化文本：工单全文、公开评论与附件中的错误行，不含内部备注
+    ///
+    /// 检索元数据的描述附带公开的沟通记录和错误行，供检索结果进入Prompt和关键词索引
+    async fn indexed_content(&self, ticket: &Ticket) -> AppResult<(String, VectorMetadata)> {
+        let comments = self.database.list_comments(ticket.id).await?;
+        let attachments = self.database.list_attachments(ticket.id).await?;
+        let text = ticket.get_full_text_with(&comments, &attachments);
+        let metadata = VectorMetadata::from_ticket(&ticket.with_details(&comments, &attachments), &text);
+        Ok((text, metadata))
+    }
+
+    /// 向量化文本并写入向量库，成功后回写工单向量
+    async fn write(&self, ticket: &mut Ticket, text: &str, metadata: VectorMetadata) -> AppResult<()> {
+        let vector = with_scope(UsageScope::for_ticket(ticket), self.embedding_service.embed(text)).await?;
+        self.vector_db.insert(ticket.id, &vector, metadata).await?;
+        self.database.set_ticket_embedding(ticket.id, Some(&vector)).await?;
+        ticket.embedding = Some(vector);
+        debug!("工单 {} 已写入向量索引", ticket.id);
+        Ok(())
+    }
+
+    async fn enqueue(&self, ticket_id: Uuid, kind: IndexEventKind) -> AppResult<()> {
+        self.database.enqueue_index_event(&IndexEvent::new(ticket_id, kind)).await?;
+        Ok(())
+    }
+
+    /// 记录失败并安排下次重试，超过上限后标记为失败，留待对账修复
+    fn record_failure(&self, event: &mut IndexEvent, message: &str) {
+        event.attempts += 1;
+        event.last_error = Some(message.to_string());
+        if event.attempts > self.config.max_retries {
+            event.status = IndexEventStatus::Failed;
+            error!("工单 {} 索引事件重试 {} 次仍失败，等待对账修复: {}", event.ticket_id, self.config.max_retries, message);
+        } else {
+            let delay = self.retry_delay(event.attempts);
+            event.next_attempt_at = Utc::now() + delay;
+            warn!("工单 {} 第 {} 次同步向量索引失败，稍后重试: {}", event.ticket_id, event.attempts, message);
+        }
+    }
+
+    /// 指数退避间隔
+    fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.config.retry_initial_delay_ms.saturating_mul(factor);
+        Duration::from_millis(delay.min(self.config.retry_max_delay_ms))
+    }
+}
+
+/// 向量是否由当前的向量化文本生成
+///
+/// 早期写入的向量没有指纹，工单没有评论且标题、描述、标签未变时视为最新
+fn is_current(metadata: &VectorMetadata, ticket: &Ticket, text: &str) -> bool {
+    match &metadata.content_hash {
+        Some(hash) => *hash == content_hash(text),
+        None => {
+            text == ticket.get_full_text()
+                && metadata.title == ticket.title
+                && metadata.description == ticket.description
+                && metadata.tags == ticket.tags
+        }
+    }
+}
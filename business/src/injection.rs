@@ -0,0 +1,174 @@
+//! # 提示注入检测模块
+//!
+//! 历史工单内容由用户填写，检索后会放入Prompt。入库时检测类似指令的文本，
+//! 可疑工单打上隔离标记，检索时不放入LLM上下文
+
+use rag_deps::*;
+use rag_core::{
+    config::InjectionConfig,
+    traits::VectorDatabase,
+    traits::vector_db::{VectorRecord, VectorMetadata, SearchResult, VectorFilter, DatabaseStats, DatabaseInfo},
+    errors::{AppError, AppResult},
+};
+use regex::Regex;
+use std::sync::Arc;
+
+/// 提示注入规则
+pub const INJECTION_PATTERNS: &[(&str, &str)] = &[
+    ("ignore_instructions", r"(?i)\b(?:ignore|disregard|forget)\s+(?:all\s+)?(?:the\s+|your\s+)?(?:previous|above|prior|earlier)\s+(?:instructions|prompts?|rules)"),
+    ("ignore_instructions_zh", r"(?:忽略|无视|忘记|不要理会)(?:之前|以上|上面|前面|先前)的?(?:所有)?(?:指令|指示|提示|规则|要求)"),
+    ("role_override", r"(?i)\byou\s+are\s+now\b|从现在开始你是|你现在是一个|扮演一个"),
+    ("system_prompt_probe", r"(?i)\bsystem\s+prompt\b|系统提示词|(?:输出|打印|泄露)(?:你的)?(?:提示词|指令)"),
+    ("new_instructions", r"(?i)\bnew\s+instructions?\s*:|新的?指令\s*[:：]"),
+    ("chat_markup", r"<\|im_(?:start|end)\|>|\[/?INST\]|<</?SYS>>"),
+];
+
+/// 提示注入检测器
+///
+/// 职责：
+/// - 按内置和自定义规则检测类似指令的文本
+/// - 命中的不同规则数达到阈值时隔离工单
+pub struct InjectionDetector {
+    enabled: bool,
+    threshold: usize,
+    rules: Vec<(String, Regex)>,
+}
+
+impl InjectionDetector {
+    pub fn new(config: &InjectionConfig) -> AppResult<Self> {
+        let custom = config.custom_patterns.iter()
+            .enumerate()
+            .map(|(i, pattern)| (format!("custom_{}", i + 1), pattern.as_str()));
+        let rules = INJECTION_PATTERNS.iter()
+            .map(|(name, pattern)| (name.to_string(), *pattern))
+            .chain(custom)
+            .map(|(name, pattern)| {
+                let regex = Regex::new(pattern).map_err(|e| AppError::Configuration {
+                    message: format!("提示注入检测规则 {} 无效: {}", name, e),
+                })?;
+                Ok((name, regex))
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Self {
+            enabled: config.enabled,
+            threshold: config.quarantine_threshold.max(1),
+            rules,
+        })
+    }
+
+    /// 返回命中的规则名
+    pub fn scan(&self, text: &str) -> Vec<&str> {
+        self.rules.iter()
+            .filter(|(_, regex)| regex.is_match(text))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// 检测工单元数据，可疑时设置隔离标记，返回是否隔离
+    pub fn screen(&self, id: Uuid, metadata: &mut VectorMetadata) -> bool {
+        if !self.enabled || metadata.quarantined {
+            return metadata.quarantined;
+        }
+        let text = format!("{}\n{}\n{}", metadata.title, metadata.description, metadata.tags.join(" "));
+        let hits = self.scan(&text);
+        if hits.len() >= self.threshold {
+            warn!("工单 {} 疑似包含提示注入，已隔离: {:?}", id, hits);
+            metadata.quarantined = true;
+        }
+        metadata.quarantined
+    }
+}
+
+/// 入库时检测提示注入的向量数据库
+///
+/// 写入前为可疑工单设置隔离标记；对检测上线前入库的数据，检索结果也会补充检测
+pub struct ScreeningVectorDatabase {
+    inner: Arc<dyn VectorDatabase + Send + Sync>,
+    detector: Arc<InjectionDetector>,
+}
+
+impl ScreeningVectorDatabase {
+    pub fn new(inner: Arc<dyn VectorDatabase + Send + Sync>, detector: Arc<InjectionDetector>) -> Self {
+        Self { inner, detector }
+    }
+
+    fn screen_results(&self, mut results: Vec<SearchResult>) -> Vec<SearchResult> {
+        for result in &mut results {
+            self.detector.screen(result.id, &mut result.metadata);
+        }
+        results
+    }
+}
+
+#[async_trait]
+impl VectorDatabase for ScreeningVectorDatabase {
+    async fn insert(&self, id: Uuid, vector: &[f32], mut metadata: VectorMetadata) -> Result<()> {
+        self.detector.screen(id, &mut metadata);
+        self.inner.insert(id, vector, metadata).await
+    }
+
+    async fn insert_batch(&self, records: &[VectorRecord]) -> Result<()> {
+        let records: Vec<VectorRecord> = records.iter()
+            .cloned()
+            .map(|mut record| {
+                self.detector.screen(record.id, &mut record.metadata);
+                record
+            })
+            .collect();
+        self.inner.insert_batch(&records).await
+    }
+
+    async fn search(
+        &self,
+        query_vector: &[f32],
+        limit: usize,
+        filter: Option<VectorFilter>,
+    ) -> Result<Vec<SearchResult>> {
+        let results = self.inner.search(query_vector, limit, filter).await?;
+        Ok(self.screen_results(results))
+    }
+
+    async fn hybrid_search(
+        &self,
+        query_vector: &[f32],
+        keywords: &[String],
+        limit: usize,
+        filter: Option<VectorFilter>,
+    ) -> Result<Vec<SearchResult>> {
+        let results = self.inner.hybrid_search(query_vector, keywords, limit, filter).await?;
+        Ok(self.screen_results(results))
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        self.inner.delete(id).await
+    }
+
+    async fn update(&self, id: Uuid, vector: &[f32], metadata: Option<VectorMetadata>) -> Result<()> {
+        let metadata = metadata.map(|mut metadata| {
+            self.detector.screen(id, &mut metadata);
+            metadata
+        });
+        self.inner.update(id, vector, metadata).await
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<VectorRecord>> {
+        self.inner.get(id).await
+    }
+
+    async fn scroll(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Uuid>> {
+        self.inner.scroll(after, limit).await
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats> {
+        self.inner.stats().await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+
+    fn database_info(&self) -> DatabaseInfo {
+        self.inner.database_info()
+    }
+}
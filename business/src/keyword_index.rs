@@ -0,0 +1,213 @@
+//! # 关键词索引模块
+//!
+//! 不支持原生混合检索的向量数据库由内存中的倒排索引补足关键词召回：
+//! 启动时由工单表重建，此后随向量的写入、更新和删除同步维护，检索时关键词与向量各自独立召回，再做RRF融合
+
+use rag_deps::*;
+use rag_core::{
+    keyword::InvertedIndex,
+    traits::{Repository, VectorDatabase},
+    traits::vector_db::{VectorRecord, VectorMetadata, SearchResult, VectorFilter, DatabaseStats, DatabaseInfo},
+};
+use futures::future;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// 关键词召回数相对于返回数量的倍数，抵消过滤条件排除的命中
+const KEYWORD_CANDIDATE_FACTOR: usize = 3;
+
+/// RRF平滑常数
+const RRF_K: f32 = 60.0;
+
+/// 预热时每页遍历的工单数
+const WARM_UP_PAGE_SIZE: usize = 500;
+
+/// 带关键词倒排索引的向量数据库
+///
+/// 职责：
+/// - 写入、更新和删除向量时同步维护倒排索引（标题、描述和标签）
+/// - 混合检索时关键词独立召回，只被关键词命中的工单也能进入候选
+/// - 关键词与向量两路排名做RRF融合
+pub struct KeywordIndexedVectorDatabase {
+    inner: Arc<dyn VectorDatabase + Send + Sync>,
+    index: RwLock<InvertedIndex>,
+}
+
+impl KeywordIndexedVectorDatabase {
+    pub fn new(inner: Arc<dyn VectorDatabase + Send + Sync>) -> Self {
+        Self {
+            inner,
+            index: RwLock::new(InvertedIndex::new()),
+        }
+    }
+
+    /// 遍历工单表重建倒排索引，返回索引的文档数
+    ///
+    /// 索引只在内存中，重启后需重新建立。工单表是向量的来源，不依赖向量库是否支持遍历；
+    /// 只索引已建立向量的工单，待重试的工单在索引器写入向量时加入
+    pub async fn warm_up(&self, database: &dyn Repository) -> Result<usize> {
+        let mut after = None;
+        loop {
+            let tickets = database.scroll_tickets(after, WARM_UP_PAGE_SIZE).await?;
+            let Some(last) = tickets.last().map(|ticket| ticket.id) else { break };
+            for ticket in tickets.iter().filter(|ticket| ticket.embedding.is_some()) {
+                let comments = database.list_comments(ticket.id).await?;
+                let attachments = database.list_attachments(ticket.id).await?;
+                // 关键词文本与索引器写入的元数据一致，不需要内容指纹
+                let metadata = VectorMetadata::from_ticket(&ticket.with_details(&comments, &attachments), "");
+                self.index_metadata(ticket.id, &metadata);
+            }
+            after = Some(last);
+        }
+        Ok(self.index.read().unwrap().len())
+    }
+
+    fn index_metadata(&self, id: Uuid, metadata: &VectorMetadata) {
+        self.index.write().unwrap().add_document(id, &metadata.keyword_text());
+    }
+
+    /// 查询关键词召回结果中不在向量召回里的记录，按过滤条件筛选；向量库中已不存在的从索引移除
+    async fn resolve_keyword_hits(
+        &self,
+        query_vector: &[f32],
+        hits: &[(Uuid, f32)],
+        filter: Option<&VectorFilter>,
+    ) -> Result<HashMap<Uuid, SearchResult>> {
+        let records = future::try_join_all(hits.iter().map(|(id, _)| self.inner.get(*id))).await?;
+        let mut resolved = HashMap::new();
+        for ((id, keyword_score), record) in hits.iter().zip(records) {
+            let Some(record) = record else {
+                self.index.write().unwrap().remove_document(*id);
+                continue;
+            };
+            if filter.is_some_and(|filter| !filter.matches(&record.metadata)) {
+                continue;
+            }
+            resolved.insert(*id, SearchResult {
+                id: *id,
+                score: cosine_similarity(query_vector, &record.vector),
+                keyword_score: Some(*keyword_score),
+                metadata: record.metadata,
+                vector: None,
+            });
+        }
+        Ok(resolved)
+    }
+}
+
+#[async_trait]
+impl VectorDatabase for KeywordIndexedVectorDatabase {
+    async fn insert(&self, id: Uuid, vector: &[f32], metadata: VectorMetadata) -> Result<()> {
+        let text = metadata.keyword_text();
+        self.inner.insert(id, vector, metadata).await?;
+        self.index.write().unwrap().add_document(id, &text);
+        Ok(())
+    }
+
+    async fn insert_batch(&self, records: &[VectorRecord]) -> Result<()> {
+        self.inner.insert_batch(records).await?;
+        let mut index = self.index.write().unwrap();
+        for record in records {
+            index.add_document(record.id, &record.metadata.keyword_text());
+        }
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query_vector: &[f32],
+        limit: usize,
+        filter: Option<VectorFilter>,
+    ) -> Result<Vec<SearchResult>> {
+        self.inner.search(query_vector, limit, filter).await
+    }
+
+    async fn hybrid_search(
+        &self,
+        query_vector: &[f32],
+        keywords: &[String],
+        limit: usize,
+        filter: Option<VectorFilter>,
+    ) -> Result<Vec<SearchResult>> {
+        let dense = self.inner.search(query_vector, limit, filter.clone()).await?;
+        if keywords.is_empty() {
+            return Ok(dense);
+        }
+        let hits = self.index.read().unwrap().search(keywords, limit * KEYWORD_CANDIDATE_FACTOR);
+        let missing: Vec<(Uuid, f32)> = hits.iter()
+            .filter(|(id, _)| !dense.iter().any(|result| result.id == *id))
+            .copied()
+            .collect();
+        let mut keyword_only = self.resolve_keyword_hits(query_vector, &missing, filter.as_ref()).await?;
+
+        // 过滤后的关键词排名，向量召回中的命中直接计入
+        let keyword_ranks: HashMap<Uuid, (usize, f32)> = hits.into_iter()
+            .filter(|(id, _)| keyword_only.contains_key(id) || dense.iter().any(|result| result.id == *id))
+            .take(limit)
+            .enumerate()
+            .map(|(rank, (id, score))| (id, (rank, score)))
+            .collect();
+        let mut fused: HashMap<Uuid, (f32, SearchResult)> = HashMap::new();
+        for (dense_rank, mut result) in dense.into_iter().enumerate() {
+            result.keyword_score = keyword_ranks.get(&result.id).map(|(_, score)| *score);
+            fused.insert(result.id, (1.0 / (RRF_K + dense_rank as f32 + 1.0), result));
+        }
+        for (id, (rank, _)) in &keyword_ranks {
+            let keyword_rrf = 1.0 / (RRF_K + *rank as f32 + 1.0);
+            if let Some((score, _)) = fused.get_mut(id) {
+                *score += keyword_rrf;
+            } else if let Some(result) = keyword_only.remove(id) {
+                fused.insert(*id, (keyword_rrf, result));
+            }
+        }
+
+        let mut fused: Vec<(f32, SearchResult)> = fused.into_values().collect();
+        fused.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.id.cmp(&b.1.id)));
+        Ok(fused.into_iter().take(limit).map(|(_, result)| result).collect())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        self.inner.delete(id).await?;
+        self.index.write().unwrap().remove_document(id);
+        Ok(())
+    }
+
+    async fn update(&self, id: Uuid, vector: &[f32], metadata: Option<VectorMetadata>) -> Result<()> {
+        let text = metadata.as_ref().map(VectorMetadata::keyword_text);
+        self.inner.update(id, vector, metadata).await?;
+        if let Some(text) = text {
+            self.index.write().unwrap().add_document(id, &text);
+        }
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<VectorRecord>> {
+        self.inner.get(id).await
+    }
+
+    async fn scroll(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Uuid>> {
+        self.inner.scroll(after, limit).await
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats> {
+        self.inner.stats().await
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+
+    fn database_info(&self) -> DatabaseInfo {
+        self.inner.database_info()
+    }
+}
+
+/// 余弦相似度，向量为零或维度不一致时为0
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
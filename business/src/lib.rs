@@ -9,4 +9,19 @@
 
 pub mod processors;
 pub mod workflows;
-pub mod validators; 
\ No newline at end of file
+pub mod validators;
+pub mod fusion;
+pub mod boosting;
+pub mod prompts;
+pub mod solution_output;
+pub mod confidence;
+pub mod context;
+pub mod usage;
+pub mod redaction;
+pub mod guardrails;
+pub mod injection;
+pub mod keyword_index;
+pub mod indexing;
+pub mod audit;
+pub mod lifecycle;
+pub mod attachments;
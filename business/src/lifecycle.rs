@@ -0,0 +1,157 @@
+//! # 工单状态流转模块
+//!
+//! 流转规则由 [`TicketStatus::allowed_transitions`] 声明，本模块负责流转后的钩子：
+//! 变更提交后按注册顺序在后台执行，失败只记录日志，不影响已生效的流转
+
+use rag_deps::*;
+use rag_core::{
+    config::LifecycleConfig,
+    models::*,
+    traits::Repository,
+    errors::AppResult,
+};
+use crate::processors::TicketProcessor;
+use std::sync::Arc;
+
+/// 一次状态流转，`from` 为空表示新建工单
+#[derive(Debug, Clone)]
+pub struct StatusTransition {
+    pub ticket_id: Uuid,
+    pub from: Option<TicketStatus>,
+    pub to: TicketStatus,
+    pub actor: String,
+}
+
+/// 状态流转钩子
+#[async_trait]
+pub trait TransitionHook: Send + Sync {
+    /// 钩子名称，用于日志
+    fn name(&self) -> &'static str;
+
+    /// 是否在该流转后执行
+    fn applies_to(&self, transition: &StatusTransition) -> bool;
+
+    /// 执行钩子，`ticket` 为流转后的工单
+    async fn on_transition(
+        &self,
+        processor: &TicketProcessor,
+        ticket: &Ticket,
+        transition: &StatusTransition,
+    ) -> AppResult<()>;
+}
+
+/// 新建工单后自动生成并保存解决方案
+pub struct AutoProcessHook;
+
+#[async_trait]
+impl TransitionHook for AutoProcessHook {
+    fn name(&self) -> &'static str {
+        "auto_process"
+    }
+
+    fn applies_to(&self, transition: &StatusTransition) -> bool {
+        transition.from.is_none() && transition.to == TicketStatus::New
+    }
+
+    async fn on_transition(
+        &self,
+        processor: &TicketProcessor,
+        ticket: &Ticket,
+        _transition: &StatusTransition,
+    ) -> AppResult<()> {
+        let result = processor.process(ticket).await?;
+        let solution = processor.save_solution(&result).await?;
+        info!("工单 {} 已自动生成解决方案 {}", ticket.id, solution.id);
+        Ok(())
+    }
+}
+
+/// 工单解决后生成微调数据
+///
+/// 优先使用已采纳的解决方案（来源为用户反馈），没有时使用解决说明（来源为专家标注）
+pub struct FinetuneDataHook {
+    database: Arc<dyn Repository>,
+}
+
+impl FinetuneDataHook {
+    pub fn new(database: Arc<dyn Repository>) -> Self {
+        Self { database }
+    }
+}
+
+#[async_trait]
+impl TransitionHook for FinetuneDataHook {
+    fn name(&self) -> &'static str {
+        "finetune_data"
+    }
+
+    fn applies_to(&self, transition: &StatusTransition) -> bool {
+        transition.to == TicketStatus::Resolved && transition.from != Some(TicketStatus::Resolved)
+    }
+
+    async fn on_transition(
+        &self,
+        _processor: &TicketProcessor,
+        ticket: &Ticket,
+        _transition: &StatusTransition,
+    ) -> AppResult<()> {
+        let solutions = self.database.get_solutions_by_ticket(ticket.id).await?;
+        let accepted = solutions.iter().find(|s| s.is_accepted);
+        let (target_output, data_source, quality_score) = match (accepted, &ticket.resolution_note) {
+            (Some(solution), _) => (
+                solution.solution.clone(),
+                DataSource::UserFeedback,
+                solution.feedback_score.map_or(1.0, |score| score as f32 / 5.0),
+            ),
+            (None, Some(note)) => (note.clone(), DataSource::ExpertLabeling, 1.0),
+            (None, None) => return Ok(()),
+        };
+
+        self.database.insert_finetune_data(&FinetuneData {
+            id: Uuid::new_v4(),
+            input_text: format!("{}\n{}", ticket.title, ticket.description),
+            target_output,
+            data_source,
+            quality_score,
+            created_at: Utc::now(),
+        }).await?;
+        debug!("工单 {} 已生成微调数据", ticket.id);
+        Ok(())
+    }
+}
+
+/// 工单状态流转钩子注册表
+///
+/// 职责：
+/// - 保存已注册的流转钩子
+/// - 按流转筛选需要执行的钩子
+#[derive(Clone, Default)]
+pub struct TicketLifecycle {
+    hooks: Vec<Arc<dyn TransitionHook>>,
+}
+
+impl TicketLifecycle {
+    pub fn new(hooks: Vec<Arc<dyn TransitionHook>>) -> Self {
+        Self { hooks }
+    }
+
+    /// 按配置注册内置钩子
+    pub fn from_config(config: &LifecycleConfig, database: Arc<dyn Repository>) -> Self {
+        let mut hooks: Vec<Arc<dyn TransitionHook>> = Vec::new();
+        if config.auto_process_on_new {
+            hooks.push(Arc::new(AutoProcessHook));
+        }
+        if config.finetune_on_resolved {
+            hooks.push(Arc::new(FinetuneDataHook::new(database)));
+        }
+        Self::new(hooks)
+    }
+
+    /// 该流转需要执行的钩子，按注册顺序
+    pub fn hooks_for(&self, transition: &StatusTransition) -> Vec<Arc<dyn TransitionHook>> {
+        self.hooks.iter()
+            .filter(|hook| hook.applies_to(transition))
+            .cloned()
+            .collect()
+    }
+}
@@ -5,9 +5,31 @@
 use rag_deps::*;
 use rag_core::{
     traits::*,
+    traits::llm::{GenerationOptions, LLMResponse, LLMStreamEvent, ResponseFormat, RouteHints, TokenUsage, solution_reasoning},
+    prompt::RenderedPrompt,
     models::*,
-    errors::AppResult,
+    config::RetrievalConfig,
+    errors::{AppError, AppResult},
+    keyword::extract_keywords,
 };
+use crate::fusion::{FusionStrategy, Ranking, RankingSource};
+use crate::boosting::{ResultBooster, best_accepted_solution};
+use crate::prompts::PromptManager;
+use crate::context::ContextBuilder;
+use crate::guardrails::{Guardrails, StreamGuard};
+use crate::indexing::TicketIndexer;
+use crate::validators::{CommentUpdate, FeedbackUpdate, TicketUpdate, TicketValidator};
+use crate::audit::{comment_added, comment_changes, comment_deleted, feedback_changes, ticket_changes};
+use crate::lifecycle::{StatusTransition, TicketLifecycle};
+use crate::attachments::AttachmentService;
+use crate::usage::{BudgetDecision, UsageScope, UsageTracker, with_scope};
+use crate::confidence::{ConfidenceCalibrator, FALLBACK_CONFIDENCE, collect_signals};
+use crate::solution_output::{
+    parse_structured_solution, repair_json, reprompt_for_format, resolve_citations, with_output_contract,
+};
+use futures::{future, stream::{self, Stream, StreamExt}};
+use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::Arc;
 
 /// 工单处理器
@@ -21,6 +43,69 @@ pub struct TicketProcessor {
     rerank_service: Arc<dyn RerankService + Send + Sync>,
     vector_db: Arc<dyn VectorDatabase + Send + Sync>,
     llm_service: Arc<dyn LLMService + Send + Sync>,
+    solution_generator: SolutionGenerator,
+    database: Arc<dyn Repository>,
+    indexer: Arc<TicketIndexer>,
+    lifecycle: Arc<TicketLifecycle>,
+    attachments: Arc<AttachmentService>,
+    prompts: Arc<PromptManager>,
+    confidence: Arc<ConfidenceCalibrator>,
+    context: ContextBuilder,
+    usage: Arc<UsageTracker>,
+    guardrails: Arc<Guardrails>,
+    retrieval: RetrievalConfig,
+}
+
+/// 方案生成与工单入库相关组件
+#[derive(Clone)]
+pub struct GenerationComponents {
+    pub prompts: Arc<PromptManager>,
+    pub confidence: Arc<ConfidenceCalibrator>,
+    pub context: ContextBuilder,
+    pub usage: Arc<UsageTracker>,
+    pub guardrails: Arc<Guardrails>,
+    pub indexer: Arc<TicketIndexer>,
+    pub lifecycle: Arc<TicketLifecycle>,
+    pub attachments: Arc<AttachmentService>,
+}
+
+/// 流式处理事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProcessEvent {
+    /// 检索到的相似工单及Prompt上下文组装情况
+    SimilarTickets { tickets: Vec<SimilarTicket>, context: ContextReport },
+    /// 方案增量文本
+    Token { content: String },
+    /// 处理完成；增量文本已经过规则检查，仅LLM复核结果为 block 时客户端需丢弃已展示的方案
+    Completed {
+        solution_id: Uuid,
+        model: String,
+        confidence: f32,
+        reasoning: String,
+        token_usage: Option<TokenUsage>,
+        guardrail: GuardrailReport,
+        prompt_version: String,
+        processing_time_ms: u64,
+    },
+}
+
+/// 流式处理结果
+pub type ProcessEventStream = Pin<Box<dyn Stream<Item = AppResult<ProcessEvent>> + Send>>;
+
+/// 单次处理的可选参数
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOptions {
+    /// 覆盖配置中的融合策略
+    pub fusion: Option<FusionStrategy>,
+    /// 方案生成参数
+    pub generation: GenerationOptions,
+    /// 指定Prompt模板版本，默认使用激活版本
+    pub prompt_template: Option<String>,
+    /// 覆盖配置中的输出语言
+    pub language: Option<String>,
+    /// 指定模型后端，仅在启用模型路由时生效
+    pub model: Option<String>,
 }
 
 impl TicketProcessor {
@@ -29,29 +114,510 @@ impl TicketProcessor {
         rerank_service: Arc<dyn RerankService + Send + Sync>,
         vector_db: Arc<dyn VectorDatabase + Send + Sync>,
         llm_service: Arc<dyn LLMService + Send + Sync>,
+        database: Arc<dyn Repository>,
+        generation: GenerationComponents,
+        retrieval: RetrievalConfig,
     ) -> Self {
         Self {
             embedding_service,
             rerank_service,
             vector_db,
+            solution_generator: SolutionGenerator::new(llm_service.clone()),
             llm_service,
+            database,
+            indexer: generation.indexer,
+            lifecycle: generation.lifecycle,
+            attachments: generation.attachments,
+            prompts: generation.prompts,
+            confidence: generation.confidence,
+            context: generation.context,
+            usage: generation.usage,
+            guardrails: generation.guardrails,
+            retrieval,
         }
     }
     
     /// 创建新工单
-    pub async fn create_ticket(&self, new_ticket: NewTicket) -> AppResult<Ticket> {
-        let ticket = Ticket::new(new_ticket);
+    /// 
+    /// 校验后先写入数据库（同时写入索引事件）再尝试立即建立向量索引；
+    /// 向量化失败不影响创建，由后台按索引事件重试。创建后执行进入 New 状态的钩子
+    pub async fn create_ticket(self: &Arc<Self>, new_ticket: NewTicket, actor: &str) -> AppResult<Ticket> {
+        TicketValidator::validate_new_ticket(&new_ticket)?;
+        let mut ticket = Ticket::new(new_ticket);
+        self.database.insert_ticket(&ticket, &[AuditEntry::new(ticket.id, actor, AuditAction::Created)]).await?;
         
-        // TODO: 保存到数据库
-        // self.database.insert_ticket(&ticket).await?;
+        let indexed = match self.indexer.index(&mut ticket).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("工单 {} 向量索引失败，由后台重试: {}", ticket.id, e);
+                false
+            }
+        };
+        info!("新工单创建成功: {}（向量索引{}）", ticket.id, if indexed { "已完成" } else { "待重试" });
         
-        info!("新工单创建成功: {}", ticket.id);
+        self.run_transition_hooks(&ticket, StatusTransition {
+            ticket_id: ticket.id,
+            from: None,
+            to: ticket.status,
+            actor: actor.to_string(),
+        });
         Ok(ticket)
     }
     
+    /// 更新工单，逐字段记录审计日志；没有实际变化时原样返回
+    /// 
+    /// `expected_version` 为客户端读取时的版本，与当前版本不一致、或读取后被其他更新抢先时
+    /// 返回 `VersionConflict`；为 `None` 时不校验客户端版本，仍以读取到的版本防止并发覆盖。状态变化需符合状态流转表，
+    /// 标记为已解决时需有已采纳的解决方案或解决说明（否则返回 `Validation`）；状态变化后执行对应钩子
+    pub async fn update_ticket(
+        self: &Arc<Self>,
+        id: Uuid,
+        expected_version: Option<i64>,
+        update: &TicketUpdate,
+        actor: &str,
+    ) -> AppResult<Ticket> {
+        let before = self.database.get_ticket(id).await?
+            .ok_or_else(|| AppError::not_found("ticket", id))?;
+        if let Some(expected) = expected_version.filter(|expected| *expected != before.version) {
+            return Err(AppError::VersionConflict { expected, actual: before.version });
+        }
+        TicketValidator::validate_ticket_update(&before, update)?;
+        
+        let mut ticket = before.clone();
+        update.apply_to(&mut ticket);
+        if ticket.status == TicketStatus::Resolved && before.status != TicketStatus::Resolved {
+            let has_accepted = self.database.get_solutions_by_ticket(id).await?
+                .iter()
+                .any(|s| s.is_accepted);
+            TicketValidator::validate_resolution(&ticket, has_accepted)?;
+        }
+        let changes = ticket_changes(&before, &ticket, actor);
+        if changes.is_empty() {
+            return Ok(before);
+        }
+        ticket.updated_at = Utc::now();
+        self.database.update_ticket(&ticket, &changes).await?;
+        ticket.version += 1;
+        
+        if ticket.status != before.status {
+            info!("工单 {} 状态 {} -> {}（{}）", id, before.status.as_str(), ticket.status.as_str(), actor);
+            self.run_transition_hooks(&ticket, StatusTransition {
+                ticket_id: id,
+                from: Some(before.status),
+                to: ticket.status,
+                actor: actor.to_string(),
+            });
+        }
+        Ok(ticket)
+    }
+    
+    /// 删除工单及其附件内容，审计记录保留
+    pub async fn delete_ticket(&self, id: Uuid, actor: &str) -> AppResult<()> {
+        let attachments = self.database.list_attachments(id).await?;
+        self.database.delete_ticket(id, &[AuditEntry::new(id, actor, AuditAction::Deleted)]).await?;
+        self.attachments.remove_stored(&attachments).await;
+        info!("工单已删除: {}", id);
+        Ok(())
+    }
+    
+    /// 为工单添加评论，评论随工单重新向量化
+    pub async fn add_comment(&self, ticket_id: Uuid, request: NewComment, actor: &str) -> AppResult<TicketComment> {
+        TicketValidator::validate_comment_body(&request.body)?;
+        let comment = TicketComment::new(ticket_id, actor, request);
+        self.database.insert_comment(&comment, &[comment_added(&comment, actor)]).await?;
+        debug!("工单 {} 新增评论 {}", ticket_id, comment.id);
+        Ok(comment)
+    }
+    
+    /// 工单的评论，按创建时间正序；`include_internal` 为false时不含内部备注
+    pub async fn list_comments(&self, ticket_id: Uuid, include_internal: bool) -> AppResult<Vec<TicketComment>> {
+        if self.database.get_ticket(ticket_id).await?.is_none() {
+            return Err(AppError::not_found("ticket", ticket_id));
+        }
+        let mut comments = self.database.list_comments(ticket_id).await?;
+        comments.retain(|comment| include_internal || !comment.internal);
+        Ok(comments)
+    }
+    
+    /// 修改评论，只有评论者本人可以修改；没有实际变化时原样返回
+    pub async fn update_comment(
+        &self,
+        ticket_id: Uuid,
+        comment_id: Uuid,
+        update: &CommentUpdate,
+        actor: &str,
+    ) -> AppResult<TicketComment> {
+        let before = self.comment_by_author(ticket_id, comment_id, actor, "修改评论").await?;
+        if let Some(body) = &update.body {
+            TicketValidator::validate_comment_body(body)?;
+        }
+        let mut comment = before.clone();
+        update.apply_to(&mut comment);
+        let changes = comment_changes(&before, &comment, actor);
+        if changes.is_empty() {
+            return Ok(before);
+        }
+        comment.updated_at = Utc::now();
+        self.database.update_comment(&comment, &changes).await?;
+        Ok(comment)
+    }
+    
+    /// 删除评论，只有评论者本人可以删除
+    pub async fn delete_comment(&self, ticket_id: Uuid, comment_id: Uuid, actor: &str) -> AppResult<()> {
+        let comment = self.comment_by_author(ticket_id, comment_id, actor, "删除评论").await?;
+        self.database.delete_comment(comment.id, &[comment_deleted(&comment, actor)]).await?;
+        Ok(())
+    }
+    
+    /// 查询工单下的评论并检查操作者是否为评论者
+    async fn comment_by_author(
+        &self,
+        ticket_id: Uuid,
+        comment_id: Uuid,
+        actor: &str,
+        action: &str,
+    ) -> AppResult<TicketComment> {
+        let comment = self.database.get_comment(comment_id).await?
+            .filter(|comment| comment.ticket_id == ticket_id)
+            .ok_or_else(|| AppError::not_found("comment", comment_id))?;
+        if comment.author != actor {
+            return Err(AppError::Permission {
+                action: format!("{}（仅评论者本人可操作）", action),
+            });
+        }
+        Ok(comment)
+    }
+    
+    /// 记录解决方案的采纳状态、评分和评论，返回更新后的方案
+    pub async fn submit_feedback(
+        &self,
+        solution_id: Uuid,
+        update: &FeedbackUpdate,
+        actor: &str,
+    ) -> AppResult<TicketSolution> {
+        if update.score.is_some_and(|score| !(1..=5).contains(&score)) {
+            return Err(AppError::validation("score", "反馈评分必须在1-5之间"));
+        }
+        let before = self.database.get_solution(solution_id).await?
+            .ok_or_else(|| AppError::not_found("solution", solution_id))?;
+        let feedback = update.merge(&before);
+        self.database.update_solution_feedback(before.id, &feedback, &feedback_changes(&before, &feedback, actor)).await?;
+        
+        Ok(TicketSolution {
+            is_accepted: feedback.is_accepted,
+            feedback_score: feedback.score,
+            feedback_comment: feedback.comment,
+            ..before
+        })
+    }
+    
+    /// 工单变更历史，按时间正序；工单已删除时仍可查询
+    pub async fn ticket_history(&self, id: Uuid) -> AppResult<Vec<AuditEntry>> {
+        let entries = self.database.list_audit_entries(id).await?;
+        if entries.is_empty() && self.database.get_ticket(id).await?.is_none() {
+            return Err(AppError::not_found("ticket", id));
+        }
+        Ok(entries)
+    }
+    
+    /// 工单的全部解决方案
+    pub async fn list_solutions(&self, ticket_id: Uuid) -> AppResult<Vec<TicketSolution>> {
+        if self.database.get_ticket(ticket_id).await?.is_none() {
+            return Err(AppError::not_found("ticket", ticket_id));
+        }
+        Ok(self.database.get_solutions_by_ticket(ticket_id).await?)
+    }
+    
+    /// 保存处理结果中的解决方案
+    pub async fn save_solution(&self, result: &ProcessResult) -> AppResult<TicketSolution> {
+        let solution = TicketSolution {
+            citations: result.citations.clone(),
+            raw_confidence: Some(result.raw_confidence),
+            guardrail: Some(result.guardrail.clone()),
+            ..TicketSolution::new(
+                result.ticket_id,
+                result.suggested_solution.clone(),
+                result.confidence,
+                result.reasoning.clone(),
+                Some(result.prompt_version.clone()),
+            )
+        };
+        self.database.insert_solution(&solution).await?;
+        Ok(solution)
+    }
+    
+    /// 在后台依次执行该流转的钩子，失败只记录日志
+    fn run_transition_hooks(self: &Arc<Self>, ticket: &Ticket, transition: StatusTransition) {
+        let hooks = self.lifecycle.hooks_for(&transition);
+        if hooks.is_empty() {
+            return;
+        }
+        let processor = self.clone();
+        let ticket = ticket.clone();
+        tokio::spawn(async move {
+            for hook in hooks {
+                if let Err(e) = hook.on_transition(&processor, &ticket, &transition).await {
+                    warn!("工单 {} 状态流转钩子 {} 执行失败: {}", ticket.id, hook.name(), e);
+                }
+            }
+        });
+    }
+    
+    /// 按名称创建融合策略，参数取自检索配置；名称不支持时返回 `Validation`
+    pub fn fusion_strategy(&self, name: &str) -> AppResult<FusionStrategy> {
+        FusionStrategy::from_name(name, &self.retrieval)
+            .map_err(|_| AppError::validation("fusion_strategy", format!("不支持的融合策略: {}", name)))
+    }
+    
     /// 处理工单 - 生成解决方案
     pub async fn process(&self, ticket: &Ticket) -> AppResult<ProcessResult> {
+        self.process_with_options(ticket, &ProcessOptions::default()).await
+    }
+    
+    /// 使用指定参数处理工单
+    pub async fn process_with_options(
+        &self,
+        ticket: &Ticket,
+        options: &ProcessOptions,
+    ) -> AppResult<ProcessResult> {
+        let options = self.apply_budget(ticket, options).await?;
+        let ticket = &self.with_details(ticket).await?;
+        with_scope(UsageScope::for_ticket(ticket), self.generate_result(ticket, &options)).await
+    }
+    
+    /// 检索、生成并整理处理结果
+    async fn generate_result(
+        &self,
+        ticket: &Ticket,
+        options: &ProcessOptions,
+    ) -> AppResult<ProcessResult> {
+        let start_time = std::time::Instant::now();
+        let similar_tickets = self.retrieve(ticket, options).await?;
+        let (prompt, context) = self.prepare_prompt(ticket, &similar_tickets, options).await?;
+        
+        info!("开始LLM推理生成建议, Prompt版本: {}", prompt.template_id);
+        let retrieval_signals = collect_signals(&similar_tickets, None, None);
+        let mut generation = self.generation_options(ticket, &retrieval_signals, options);
+        generation.logprobs |= self.confidence.requests_logprobs();
+        let generated = self.solution_generator
+            .generate(&prompt, &generation)
+            .await?;
+        info!("工单 {} 使用模型 {} 生成方案", ticket.id, generated.model);
+        
+        let signals = collect_signals(
+            &similar_tickets,
+            generated.structured.as_ref().map(|s| s.confidence),
+            generated.avg_logprob,
+        );
+        let (raw_confidence, confidence) = self.confidence.score(&signals, generated.status);
+        
+        let cited_ids = generated.structured.as_ref()
+            .map(|s| s.cited_ticket_ids.as_slice())
+            .unwrap_or_default();
+        let in_context: Vec<SimilarTicket> = similar_tickets.iter()
+            .filter(|t| context.included.contains(&t.ticket_id))
+            .cloned()
+            .collect();
+        let (citations, invalid_citations) = resolve_citations(cited_ids, &in_context);
+        if !invalid_citations.is_empty() {
+            warn!("工单 {} 的生成方案引用了上下文之外的工单，已丢弃: {:?}", ticket.id, invalid_citations);
+        }
+        
+        let guarded = self.guardrails
+            .guard(ticket, generated.content, generated.reasoning, generated.structured, confidence)
+            .await;
+        
+        Ok(ProcessResult {
+            ticket_id: ticket.id,
+            similar_tickets,
+            suggested_solution: guarded.content,
+            confidence: guarded.confidence,
+            reasoning: guarded.reasoning,
+            raw_confidence,
+            confidence_signals: signals,
+            structured: guarded.structured,
+            output_status: generated.status,
+            citations,
+            invalid_citations,
+            context,
+            model: generated.model,
+            guardrail: guarded.report,
+            prompt_version: prompt.template_id,
+            processing_time_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+    
+    /// 流式处理工单
+    /// 
+    /// 先输出检索到的相似工单，再逐段输出生成的方案，最后输出置信度、安全检查结果和耗时。
+    /// 流式输出直接面向用户展示，不使用结构化JSON输出约定；方案按整行经过规则检查后才输出，
+    /// 命中的片段已隐藏或拦截，LLM复核在生成结束后对全文进行。复核后的方案随完成事件保存，
+    /// 保存失败时以错误结束
+    pub async fn process_stream(
+        &self,
+        ticket: &Ticket,
+        options: &ProcessOptions,
+    ) -> AppResult<ProcessEventStream> {
+        let options = self.apply_budget(ticket, options).await?;
+        let ticket = &self.with_details(ticket).await?;
+        with_scope(UsageScope::for_ticket(ticket), self.start_stream(ticket, &options)).await
+    }
+    
+    /// 附加工单的公开沟通记录和附件错误行，检索与生成都基于完整的对话；内部备注不进入Prompt
+    async fn with_details(&self, ticket: &Ticket) -> AppResult<Ticket> {
+        let comments = self.database.list_comments(ticket.id).await?;
+        let attachments = self.database.list_attachments(ticket.id).await?;
+        Ok(ticket.with_details(&comments, &attachments))
+    }
+    
+    /// 检索并发起流式生成
+    async fn start_stream(
+        &self,
+        ticket: &Ticket,
+        options: &ProcessOptions,
+    ) -> AppResult<ProcessEventStream> {
         let start_time = std::time::Instant::now();
+        let similar_tickets = self.retrieve(ticket, options).await?;
+        let (prompt, context) = self.prepare_prompt(ticket, &similar_tickets, options).await?;
+        let prompt_version = prompt.template_id.clone();
+        let reasoning = solution_reasoning(context.included.len());
+        let confidence = self.confidence.clone();
+        let retrieval_signals = collect_signals(&similar_tickets, None, None);
+        
+        info!("开始LLM流式推理生成建议, Prompt版本: {}", prompt_version);
+        let generation = self.generation_options(ticket, &retrieval_signals, options);
+        let llm_stream = self.llm_service
+            .generate_stream(&prompt, &generation)
+            .await?;
+        
+        let head = stream::once(future::ready(Ok(ProcessEvent::SimilarTickets {
+            tickets: similar_tickets,
+            context,
+        })));
+        let guardrails = self.guardrails.clone();
+        let database = self.database.clone();
+        let ticket = ticket.clone();
+        let mut guard = StreamGuard::new(guardrails.clone(), ticket.clone());
+        let body = llm_stream.then(move |event| {
+            let (model, token_usage) = match event {
+                Ok(LLMStreamEvent::Delta(delta)) => {
+                    let token = guard.push(&delta).map(|content| Ok(ProcessEvent::Token { content }));
+                    return future::Either::Left(future::ready(token.into_iter().collect::<Vec<_>>()));
+                }
+                Ok(LLMStreamEvent::Done { model, token_usage, .. }) => (model, token_usage),
+                Err(e) => return future::Either::Left(future::ready(vec![Err(AppError::from(e))])),
+            };
+            let rest = guard.finish().map(|content| Ok(ProcessEvent::Token { content }));
+            let text = guard.text().to_string();
+            // 流式输出没有模型自评，仅使用检索信号
+            let (raw_confidence, confidence) = confidence.score(&retrieval_signals, OutputStatus::Parsed);
+            let (guardrails, database, ticket, reasoning, prompt_version) =
+                (guardrails.clone(), database.clone(), ticket.clone(), reasoning.clone(), prompt_version.clone());
+            future::Either::Right(async move {
+                let mut guardrail = guardrails.review(&ticket, &text).await;
+                let confidence = guardrails.adjust_confidence(&mut guardrail, confidence);
+                let solution = TicketSolution {
+                    raw_confidence: Some(raw_confidence),
+                    guardrail: Some(guardrail.clone()),
+                    ..TicketSolution::new(
+                        ticket.id,
+                        guardrails.enforce(&guardrail, &text),
+                        confidence,
+                        reasoning.clone(),
+                        Some(prompt_version.clone()),
+                    )
+                };
+                if let Err(e) = database.insert_solution(&solution).await {
+                    return rest.into_iter().chain([Err(AppError::from(e))]).collect::<Vec<_>>();
+                }
+                let completed = Ok(ProcessEvent::Completed {
+                    solution_id: solution.id,
+                    model,
+                    confidence,
+                    reasoning,
+                    token_usage,
+                    guardrail,
+                    prompt_version,
+                    processing_time_ms: start_time.elapsed().as_millis() as u64,
+                });
+                rest.into_iter().chain([completed]).collect::<Vec<_>>()
+            })
+        }).flat_map(stream::iter);
+        
+        Ok(Box::pin(head.chain(body)))
+    }
+    
+    /// 按每日预算调整处理参数，超出预算时改用便宜模型或拒绝处理
+    async fn apply_budget(&self, ticket: &Ticket, options: &ProcessOptions) -> AppResult<ProcessOptions> {
+        let mut options = options.clone();
+        if let BudgetDecision::Downgrade(backend) = self.usage.check_budget(ticket).await? {
+            options.model = Some(backend);
+        }
+        Ok(options)
+    }
+    
+    /// 本次生成的参数，附带模型路由依据
+    fn generation_options(
+        &self,
+        ticket: &Ticket,
+        retrieval_signals: &ConfidenceSignals,
+        options: &ProcessOptions,
+    ) -> GenerationOptions {
+        let mut generation = options.generation.clone();
+        if options.model.is_some() {
+            generation.model = options.model.clone();
+        }
+        generation.route = Some(RouteHints {
+            ticket_id: Some(ticket.id),
+            category: Some(ticket.category.clone()),
+            priority: Some(ticket.priority),
+            ticket_chars: ticket.title.chars().count() + ticket.description.chars().count(),
+            retrieval_confidence: Some(self.confidence.score(retrieval_signals, OutputStatus::Parsed).1),
+        });
+        generation
+    }
+    
+    /// 按指定或激活的模板版本渲染解决方案Prompt，相似案例在模型上下文预算内组装
+    async fn prepare_prompt(
+        &self,
+        ticket: &Ticket,
+        similar_tickets: &[SimilarTicket],
+        options: &ProcessOptions,
+    ) -> AppResult<(RenderedPrompt, ContextReport)> {
+        let template = match &options.prompt_template {
+            Some(id) => self.prompts.get(id).await?,
+            None => self.prompts.active().await?,
+        };
+        let language = options.language.as_deref();
+        let estimator = self.llm_service.token_estimator();
+        let budget = self.context.budget(
+            self.llm_service.model_info().max_tokens,
+            options.generation.max_tokens,
+        );
+        
+        // 不含案例的Prompt开销，按附加输出格式要求后的长度估算
+        let base = with_output_contract(&self.prompts.render_solution(&template, ticket, &[], &[], language));
+        let base_tokens = estimator.estimate(&base.system) + estimator.estimate(&base.user);
+        
+        let packed = self.context.pack(similar_tickets, &estimator, budget, base_tokens);
+        let prompt = self.prompts.render_solution(
+            &template,
+            ticket,
+            &packed.cases,
+            &packed.ticket_ids,
+            language,
+        );
+        Ok((prompt, packed.report))
+    }
+    
+    /// 检索相似工单：向量/混合检索、重排序、融合与加权
+    /// 
+    /// 工单创建时即已写入向量库，候选中排除工单自身，避免把自己当作相似案例引用
+    async fn retrieve(&self, ticket: &Ticket, options: &ProcessOptions) -> AppResult<Vec<SimilarTicket>> {
+        let fusion = match &options.fusion {
+            Some(strategy) => strategy.clone(),
+            None => FusionStrategy::from_config(&self.retrieval)?,
+        };
         
         // 1. 向量化
         info!("开始向量化工单: {}", ticket.id);
@@ -60,11 +626,22 @@ impl TicketProcessor {
             .embed(&text)
             .await?;
         
-        // 2. 向量检索
-        info!("开始向量检索相似工单");
-        let candidates = self.vector_db
-            .search(&embedding, 100, None) // 获取Top 100候选
-            .await?;
+        // 2. 向量检索，提取到关键词时使用混合检索；多取一条以抵消排除的工单自身
+        let keywords = extract_keywords(&text, &ticket.tags, self.retrieval.max_keywords);
+        let limit = self.retrieval.candidate_limit + 1;
+        let mut candidates = if keywords.is_empty() {
+            info!("开始向量检索相似工单");
+            self.vector_db
+                .search(&embedding, limit, None)
+                .await?
+        } else {
+            info!("开始混合检索相似工单，关键词: {:?}", keywords);
+            self.vector_db
+                .hybrid_search(&embedding, &keywords, limit, None)
+                .await?
+        };
+        candidates.retain(|candidate| candidate.id != ticket.id);
+        candidates.truncate(self.retrieval.candidate_limit);
         
         // 3. Rerank重排序
         info!("开始重排序候选工单");
@@ -78,36 +655,65 @@ impl TicketProcessor {
             .rerank(&text, &documents)
             .await?;
         
-        // 4. LLM生成建议
-        info!("开始LLM推理生成建议");
-        let llm_response = self.llm_service
-            .generate_solution(ticket, &reranked)
-            .await?;
+        // 4. 多路排序融合
+        let rankings = vec![
+            Ranking {
+                source: RankingSource::Dense,
+                scores: candidates.iter().map(|c| (c.id, c.score)).collect(),
+            },
+            Ranking {
+                source: RankingSource::Keyword,
+                scores: candidates.iter()
+                    .filter_map(|c| c.keyword_score.map(|score| (c.id, score)))
+                    .collect(),
+            },
+            Ranking {
+                source: RankingSource::Rerank,
+                scores: reranked.iter()
+                    .filter_map(|r| candidates.get(r.index).map(|c| (c.id, r.score)))
+                    .collect(),
+            },
+        ];
+        let mut fused = fusion.fuse(&rankings);
+        
+        // 5. 时效与方案质量加权
+        let candidate_ids: Vec<Uuid> = fused.iter().map(|f| f.id).collect();
+        // 方案质量只影响加权，查询失败时按无历史方案处理，不中断检索
+        let solutions = match self.database.solutions_for_tickets(&candidate_ids).await {
+            Ok(solutions) => solutions,
+            Err(e) => {
+                warn!("查询候选工单的历史方案失败，跳过方案质量加权: {}", e);
+                HashMap::new()
+            }
+        };
+        let created_at: HashMap<Uuid, DateTime<Utc>> = candidates.iter()
+            .map(|c| (c.id, c.metadata.created_at))
+            .collect();
+        ResultBooster::new(self.retrieval.boost.clone())
+            .apply(&mut fused, &created_at, &solutions);
+        fused.truncate(self.retrieval.top_k);
         
-        let processing_time = start_time.elapsed().as_millis() as u64;
-        
-        // 5. 构建返回结果
-        let similar_tickets: Vec<SimilarTicket> = candidates.iter()
-            .zip(reranked.iter())
-            .take(10) // Top 10
-            .map(|(candidate, rerank_result)| SimilarTicket {
-                ticket_id: candidate.id,
-                title: candidate.metadata.title.clone(),
-                description: candidate.metadata.description.clone(),
-                similarity_score: candidate.score,
-                rerank_score: rerank_result.score,
-                solution: None, // TODO: 从数据库获取历史解决方案
+        // 6. 按融合排名整理相似工单，附带已采纳的历史方案
+        let similar_tickets = fused.iter()
+            .filter_map(|fused| {
+                let candidate = candidates.iter().find(|c| c.id == fused.id)?;
+                let solution = solutions.get(&candidate.id).and_then(|s| best_accepted_solution(s));
+                
+                Some(SimilarTicket {
+                    ticket_id: candidate.id,
+                    title: candidate.metadata.title.clone(),
+                    description: candidate.metadata.description.clone(),
+                    similarity_score: candidate.score,
+                    rerank_score: fused.scores.rerank.unwrap_or(0.0),
+                    scores: fused.scores.clone(),
+                    solution_id: solution.map(|s| s.id),
+                    solution: solution.map(|s| s.solution.clone()),
+                    quarantined: candidate.metadata.quarantined,
+                })
             })
             .collect();
         
-        Ok(ProcessResult {
-            ticket_id: ticket.id,
-            similar_tickets,
-            suggested_solution: llm_response.content,
-            confidence: llm_response.confidence,
-            reasoning: llm_response.reasoning,
-            processing_time_ms: processing_time,
-        })
+        Ok(similar_tickets)
     }
     
     /// 批量处理工单
@@ -132,9 +738,116 @@ impl TicketProcessor {
 /// 
 /// 职责：
 /// - 基于历史案例生成解决方案
-/// - 管理解决方案的质量评估
+/// - 要求模型按约定输出JSON并校验结构
+/// - 解析失败时依次尝试修复格式、重新请求一次，最后回退为原始文本
 pub struct SolutionGenerator {
-    // TODO: 实现解决方案生成逻辑
+    llm_service: Arc<dyn LLMService + Send + Sync>,
+}
+
+/// 生成的解决方案
+#[derive(Debug, Clone)]
+pub struct GeneratedSolution {
+    pub content: String,
+    pub confidence: f32,
+    pub reasoning: String,
+    pub structured: Option<StructuredSolution>,
+    pub status: OutputStatus,
+    pub token_usage: Option<TokenUsage>,
+    pub avg_logprob: Option<f32>,
+    pub model: String,
+}
+
+impl SolutionGenerator {
+    pub fn new(llm_service: Arc<dyn LLMService + Send + Sync>) -> Self {
+        Self { llm_service }
+    }
+    
+    /// 按结构化输出约定生成解决方案
+    pub async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> AppResult<GeneratedSolution> {
+        let prompt = with_output_contract(prompt);
+        let mut options = options.clone();
+        options.response_format.get_or_insert(ResponseFormat::JsonObject);
+        
+        let response = self.llm_service.generate(&prompt, &options).await?;
+        let mut token_usage = response.token_usage.clone();
+        
+        let error = match parse_structured_solution(&response.content) {
+            Ok(solution) => return Ok(Self::structured(solution, OutputStatus::Parsed, token_usage, &response)),
+            Err(error) => error,
+        };
+        if let Ok(solution) = parse_structured_solution(&repair_json(&response.content)) {
+            debug!("模型输出经格式修复后解析成功");
+            return Ok(Self::structured(solution, OutputStatus::Repaired, token_usage, &response));
+        }
+        
+        warn!("模型输出不符合JSON约定，重新请求: {}", error);
+        let retry_prompt = reprompt_for_format(&prompt, &response.content, &error);
+        let mut raw_output = response.content;
+        let mut avg_logprob = response.avg_logprob;
+        let mut model = response.model;
+        match self.llm_service.generate(&retry_prompt, &options).await {
+            Ok(retry) => {
+                token_usage = merge_usage(token_usage, retry.token_usage.clone());
+                let parsed = parse_structured_solution(&retry.content)
+                    .or_else(|_| parse_structured_solution(&repair_json(&retry.content)));
+                if let Ok(solution) = parsed {
+                    return Ok(Self::structured(solution, OutputStatus::Reprompted, token_usage, &retry));
+                }
+                if !retry.content.trim().is_empty() {
+                    raw_output = retry.content;
+                    avg_logprob = retry.avg_logprob;
+                    model = retry.model;
+                }
+            }
+            Err(e) => warn!("重新请求失败: {}", e),
+        }
+        
+        warn!("模型输出无法解析为结构化结果，按原始文本处理");
+        Ok(GeneratedSolution {
+            content: raw_output,
+            confidence: FALLBACK_CONFIDENCE,
+            reasoning: "模型输出无法解析为结构化结果".to_string(),
+            structured: None,
+            status: OutputStatus::Fallback,
+            token_usage,
+            avg_logprob,
+            model,
+        })
+    }
+    
+    fn structured(
+        solution: StructuredSolution,
+        status: OutputStatus,
+        token_usage: Option<TokenUsage>,
+        response: &LLMResponse,
+    ) -> GeneratedSolution {
+        GeneratedSolution {
+            content: solution.steps_text(),
+            confidence: solution.confidence,
+            reasoning: solution.reasoning.clone(),
+            structured: Some(solution),
+            status,
+            token_usage,
+            avg_logprob: response.avg_logprob,
+            model: response.model.clone(),
+        }
+    }
+}
+
+/// 累加多次调用的token统计
+fn merge_usage(a: Option<TokenUsage>, b: Option<TokenUsage>) -> Option<TokenUsage> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(TokenUsage {
+            prompt_tokens: a.prompt_tokens + b.prompt_tokens,
+            completion_tokens: a.completion_tokens + b.completion_tokens,
+            total_tokens: a.total_tokens + b.total_tokens,
+        }),
+        (a, b) => a.or(b),
+    }
 }
 
 /// 反馈处理器
@@ -0,0 +1,294 @@
+//! # Prompt模板管理模块
+//!
+//! 管理解决方案生成所用的Prompt模板版本，支持列出、预览和激活
+
+use rag_deps::*;
+use rag_core::{
+    models::Ticket,
+    prompt::{PromptTemplate, RenderedPrompt, BUILTIN_SOLUTION_TEMPLATE_ID},
+    traits::reranking::RerankResult,
+    errors::{AppError, AppResult},
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+/// 激活版本记录文件名
+const ACTIVE_FILE: &str = "active";
+
+/// Prompt模板存储
+///
+/// 职责：
+/// - 保存模板版本，版本一经保存不可修改
+/// - 记录当前激活的版本
+#[async_trait]
+pub trait PromptStore: Send + Sync {
+    /// 列出所有模板版本
+    async fn list(&self) -> AppResult<Vec<PromptTemplate>>;
+
+    /// 按版本ID获取模板
+    async fn get(&self, id: &str) -> AppResult<Option<PromptTemplate>>;
+
+    /// 保存新版本
+    async fn save(&self, template: &PromptTemplate) -> AppResult<()>;
+
+    /// 当前激活的版本ID
+    async fn active_id(&self) -> AppResult<Option<String>>;
+
+    /// 设置激活版本
+    async fn set_active(&self, id: &str) -> AppResult<()>;
+}
+
+/// 基于文件目录的模板存储
+///
+/// 每个版本保存为 `<目录>/<版本ID>.toml`，激活的版本ID记录在 `<目录>/active`
+pub struct FilePromptStore {
+    directory: PathBuf,
+}
+
+impl FilePromptStore {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self { directory: directory.into() }
+    }
+
+    fn template_path(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{}.toml", id))
+    }
+}
+
+fn io_error(action: &str, err: std::io::Error) -> AppError {
+    AppError::Internal {
+        message: format!("{}失败: {}", action, err),
+    }
+}
+
+#[async_trait]
+impl PromptStore for FilePromptStore {
+    async fn list(&self) -> AppResult<Vec<PromptTemplate>> {
+        let mut entries = match tokio::fs::read_dir(&self.directory).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(io_error("读取模板目录", e)),
+        };
+
+        let mut templates = Vec::new();
+        while let Some(entry) = entries.next_entry().await.map_err(|e| io_error("读取模板目录", e))? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+                continue;
+            }
+            let content = tokio::fs::read_to_string(&path).await
+                .map_err(|e| io_error("读取模板文件", e))?;
+            match toml::from_str::<PromptTemplate>(&content) {
+                Ok(template) => templates.push(template),
+                Err(e) => warn!("跳过无法解析的模板文件 {}: {}", path.display(), e),
+            }
+        }
+        Ok(templates)
+    }
+
+    async fn get(&self, id: &str) -> AppResult<Option<PromptTemplate>> {
+        match tokio::fs::read_to_string(self.template_path(id)).await {
+            Ok(content) => toml::from_str(&content)
+                .map(Some)
+                .map_err(|e| AppError::Configuration {
+                    message: format!("模板 {} 格式错误: {}", id, e),
+                }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("读取模板文件", e)),
+        }
+    }
+
+    async fn save(&self, template: &PromptTemplate) -> AppResult<()> {
+        let content = toml::to_string_pretty(template).map_err(|e| AppError::Internal {
+            message: format!("模板序列化失败: {}", e),
+        })?;
+        tokio::fs::create_dir_all(&self.directory).await
+            .map_err(|e| io_error("创建模板目录", e))?;
+        tokio::fs::write(self.template_path(&template.id), content).await
+            .map_err(|e| io_error("写入模板文件", e))
+    }
+
+    async fn active_id(&self) -> AppResult<Option<String>> {
+        match tokio::fs::read_to_string(self.directory.join(ACTIVE_FILE)).await {
+            Ok(content) => Ok(Some(content.trim().to_string()).filter(|id| !id.is_empty())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("读取激活版本", e)),
+        }
+    }
+
+    async fn set_active(&self, id: &str) -> AppResult<()> {
+        tokio::fs::create_dir_all(&self.directory).await
+            .map_err(|e| io_error("创建模板目录", e))?;
+        tokio::fs::write(self.directory.join(ACTIVE_FILE), id).await
+            .map_err(|e| io_error("写入激活版本", e))
+    }
+}
+
+/// 内存模板存储，未配置模板目录时使用，重启后失效
+#[derive(Default)]
+pub struct InMemoryPromptStore {
+    templates: RwLock<HashMap<String, PromptTemplate>>,
+    active: RwLock<Option<String>>,
+}
+
+impl InMemoryPromptStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl PromptStore for InMemoryPromptStore {
+    async fn list(&self) -> AppResult<Vec<PromptTemplate>> {
+        Ok(self.templates.read().unwrap().values().cloned().collect())
+    }
+
+    async fn get(&self, id: &str) -> AppResult<Option<PromptTemplate>> {
+        Ok(self.templates.read().unwrap().get(id).cloned())
+    }
+
+    async fn save(&self, template: &PromptTemplate) -> AppResult<()> {
+        self.templates.write().unwrap().insert(template.id.clone(), template.clone());
+        Ok(())
+    }
+
+    async fn active_id(&self) -> AppResult<Option<String>> {
+        Ok(self.active.read().unwrap().clone())
+    }
+
+    async fn set_active(&self, id: &str) -> AppResult<()> {
+        *self.active.write().unwrap() = Some(id.to_string());
+        Ok(())
+    }
+}
+
+/// 模板版本摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub version: u32,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub active: bool,
+}
+
+/// Prompt模板管理器
+///
+/// 职责：
+/// - 在存储的模板之外始终提供内置模板
+/// - 解析当前激活的模板，激活版本缺失时回退到内置模板
+/// - 按输出语言渲染解决方案Prompt
+pub struct PromptManager {
+    store: Arc<dyn PromptStore>,
+    output_language: String,
+}
+
+impl PromptManager {
+    pub fn new(store: Arc<dyn PromptStore>, output_language: String) -> Self {
+        Self { store, output_language }
+    }
+
+    /// 列出所有模板版本，按名称和版本号排序
+    pub async fn list(&self) -> AppResult<Vec<PromptTemplateSummary>> {
+        let active_id = self.active_id().await?;
+        let mut templates = self.store.list().await?;
+        if !templates.iter().any(|t| t.id == BUILTIN_SOLUTION_TEMPLATE_ID) {
+            templates.push(PromptTemplate::builtin_solution());
+        }
+        templates.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+
+        Ok(templates.into_iter()
+            .map(|t| PromptTemplateSummary {
+                active: t.id == active_id,
+                id: t.id,
+                name: t.name,
+                version: t.version,
+                description: t.description,
+                created_at: t.created_at,
+            })
+            .collect())
+    }
+
+    /// 获取指定版本，版本ID不合法时返回 `Validation`
+    pub async fn get(&self, id: &str) -> AppResult<PromptTemplate> {
+        validate_template_id(id)?;
+        if let Some(template) = self.store.get(id).await? {
+            return Ok(template);
+        }
+        if id == BUILTIN_SOLUTION_TEMPLATE_ID {
+            return Ok(PromptTemplate::builtin_solution());
+        }
+        Err(AppError::not_found("prompt_template", id))
+    }
+
+    /// 当前激活的模板
+    pub async fn active(&self) -> AppResult<PromptTemplate> {
+        let id = self.active_id().await?;
+        match self.get(&id).await {
+            Err(AppError::NotFound { .. } | AppError::Validation { .. }) => {
+                warn!("激活的Prompt模板 {} 不存在或版本ID不合法，使用内置模板", id);
+                Ok(PromptTemplate::builtin_solution())
+            }
+            result => result,
+        }
+    }
+
+    /// 激活指定版本，版本ID在访问存储前校验
+    pub async fn activate(&self, id: &str) -> AppResult<PromptTemplate> {
+        let template = self.get(id).await?;
+        self.store.set_active(id).await?;
+        info!("已激活Prompt模板: {}", id);
+        Ok(template)
+    }
+
+    /// 保存新版本，已存在的版本ID不可覆盖
+    pub async fn create(&self, template: PromptTemplate) -> AppResult<PromptTemplate> {
+        validate_template_id(&template.id)?;
+        if template.user.trim().is_empty() {
+            return Err(AppError::validation("user", "用户提示词不能为空"));
+        }
+        if template.id == BUILTIN_SOLUTION_TEMPLATE_ID || self.store.get(&template.id).await?.is_some() {
+            return Err(AppError::validation("id", format!("模板版本 {} 已存在", template.id)));
+        }
+
+        self.store.save(&template).await?;
+        info!("已保存Prompt模板: {}", template.id);
+        Ok(template)
+    }
+
+    /// 渲染解决方案Prompt，未指定语言时使用配置的输出语言
+    pub fn render_solution(
+        &self,
+        template: &PromptTemplate,
+        ticket: &Ticket,
+        similar_cases: &[RerankResult],
+        ticket_ids: &[Uuid],
+        language: Option<&str>,
+    ) -> RenderedPrompt {
+        template.render_solution(
+            ticket,
+            similar_cases,
+            ticket_ids,
+            language.unwrap_or(&self.output_language),
+        )
+    }
+
+    async fn active_id(&self) -> AppResult<String> {
+        Ok(self.store.active_id().await?
+            .unwrap_or_else(|| BUILTIN_SOLUTION_TEMPLATE_ID.to_string()))
+    }
+}
+
+/// 版本ID只能包含字母、数字、-、_、.，且不能以.开头；文件存储以ID作文件名，校验须在访问存储前进行
+fn validate_template_id(id: &str) -> AppResult<()> {
+    let valid = !id.is_empty()
+        && !id.starts_with('.')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::validation("id", "版本ID只能包含字母、数字、-、_、.，且不能以.开头"))
+    }
+}
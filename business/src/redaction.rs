@@ -0,0 +1,596 @@
+//! # 敏感信息脱敏模块
+//!
+//! 文本发往外部AI服务前，将手机号、身份证号、银行卡号、邮箱、IP和内部主机名替换为占位符，
+//! 生成结果中的占位符再还原为原文
+
+use rag_deps::*;
+use rag_core::{
+    config::PrivacyConfig,
+    models::Ticket,
+    prompt::{RenderedPrompt, TokenEstimator},
+    traits::*,
+    traits::embedding::ModelInfo,
+    traits::reranking::RerankResult,
+    traits::llm::{GenerationOptions, LLMResponse, LLMStream, LLMStreamEvent, ResponseFormat},
+    errors::{AppError, AppResult},
+};
+use futures::{stream, StreamExt};
+use regex::Regex;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+
+/// 身份证校验码加权因子
+const ID_CARD_WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+
+/// 身份证校验码，按加权和模11取值
+const ID_CARD_CHECK_CODES: &[u8; 11] = b"10X98765432";
+
+/// 敏感信息检测器
+struct Detector {
+    label: String, // 占位符前缀
+    regex: Regex,
+    validate: fn(&str) -> bool,
+    bounded: bool, // 匹配前后不能紧邻字母或数字
+}
+
+impl Detector {
+    fn new(label: &str, pattern: &str, validate: fn(&str) -> bool, bounded: bool) -> AppResult<Self> {
+        let regex = Regex::new(pattern).map_err(|e| AppError::Configuration {
+            message: format!("脱敏规则 {} 无效: {}", label, e),
+        })?;
+        Ok(Self { label: label.to_string(), regex, validate, bounded })
+    }
+
+    /// 内置规则只匹配ASCII数字，Unicode数字（如全角数字）不会进入校验码计算
+    fn builtin(name: &str, hostname_suffixes: &[String]) -> AppResult<Option<Self>> {
+        let detector = match name {
+            "mobile" => Self::new("PHONE", r"(?:\+?86[- ]?)?1[3-9][0-9]{9}", |_| true, true)?,
+            "id_card" => Self::new("ID_CARD", r"[1-9][0-9]{16}[0-9Xx]", valid_id_card, true)?,
+            "bank_card" => Self::new("BANK_CARD", r"[0-9]{4}(?:[ -]?[0-9]{4}){3}(?:[ -]?[0-9]{1,3})?", valid_bank_card, true)?,
+            "email" => Self::new("EMAIL", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}", |_| true, true)?,
+            "ipv4" => Self::new("IP", r"(?:[0-9]{1,3}\.){3}[0-9]{1,3}", |s| s.parse::<Ipv4Addr>().is_ok(), true)?,
+            "ipv6" => Self::new("IP", r"[0-9A-Fa-f]{0,4}(?::[0-9A-Fa-f]{0,4}){2,7}", valid_ipv6, true)?,
+            "hostname" if hostname_suffixes.is_empty() => return Ok(None),
+            "hostname" => {
+                let suffixes = hostname_suffixes.iter()
+                    .map(|suffix| regex::escape(suffix.trim_start_matches('.')))
+                    .collect::<Vec<_>>()
+                    .join("|");
+                let pattern = format!(r"(?i)[A-Za-z0-9](?:[A-Za-z0-9-]*[A-Za-z0-9])?(?:\.[A-Za-z0-9-]+)*\.(?:{})", suffixes);
+                Self::new("HOST", &pattern, |_| true, true)?
+            }
+            other => return Err(AppError::Configuration {
+                message: format!("不支持的脱敏检测器: {}", other),
+            }),
+        };
+        Ok(Some(detector))
+    }
+}
+
+/// 18位身份证号校验码
+fn valid_id_card(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    let sum: u32 = bytes[..17].iter()
+        .zip(ID_CARD_WEIGHTS)
+        .map(|(digit, weight)| (digit - b'0') as u32 * weight)
+        .sum();
+    ID_CARD_CHECK_CODES[(sum % 11) as usize] == bytes[17].to_ascii_uppercase()
+}
+
+/// IPv6地址，至少包含一个十六进制位，不把 `::` 这类纯分隔符当作地址
+fn valid_ipv6(value: &str) -> bool {
+    value.chars().any(|c| c.is_ascii_hexdigit()) && value.parse::<Ipv6Addr>().is_ok()
+}
+
+/// 16-19位银行卡号Luhn校验
+fn valid_bank_card(value: &str) -> bool {
+    let digits: Vec<u32> = value.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(16..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits.iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match i % 2 {
+            1 if d * 2 > 9 => d * 2 - 9,
+            1 => d * 2,
+            _ => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// 匹配前后是否紧邻字母或数字
+fn is_bounded(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.is_some_and(|c| c.is_ascii_alphanumeric()) && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+}
+
+/// 检测到的敏感信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiiMatch {
+    pub label: String, // 占位符前缀，如 PHONE、EMAIL
+    pub value: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 一次调用的脱敏映射
+///
+/// 同一原文在同一次调用中使用相同的占位符，便于模型理解指代关系
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    entries: Vec<(String, String)>, // (占位符, 原文)
+    counters: HashMap<String, usize>,
+}
+
+impl Redaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已替换的敏感信息数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn placeholder(&mut self, label: &str, original: &str) -> String {
+        if let Some((placeholder, _)) = self.entries.iter().find(|(_, o)| o == original) {
+            return placeholder.clone();
+        }
+        let counter = self.counters.entry(label.to_string()).or_default();
+        *counter += 1;
+        let placeholder = format!("[{}_{}]", label, counter);
+        self.entries.push((placeholder.clone(), original.to_string()));
+        placeholder
+    }
+
+    /// 将文本中的占位符还原为原文
+    pub fn restore(&self, text: &str) -> String {
+        if self.entries.is_empty() {
+            return text.to_string();
+        }
+        self.entries.iter()
+            .fold(text.to_string(), |text, (placeholder, original)| text.replace(placeholder, original))
+    }
+
+    /// 将JSON文本中的占位符还原为原文，原文按JSON字符串转义，引号或反斜杠不会破坏结构
+    ///
+    /// 占位符只会出现在字符串值中
+    pub fn restore_json(&self, text: &str) -> String {
+        self.entries.iter().fold(text.to_string(), |text, (placeholder, original)| {
+            let quoted = serde_json::to_string(original).unwrap_or_default();
+            text.replace(placeholder, quoted.get(1..quoted.len() - 1).unwrap_or(original.as_str()))
+        })
+    }
+}
+
+/// 敏感信息脱敏器
+///
+/// 职责：
+/// - 按配置组合内置检测器（正则加校验码）和自定义正则
+/// - 将匹配内容替换为可还原的占位符
+/// - 判断某个服务提供商是否需要脱敏
+pub struct Redactor {
+    detectors: Vec<Detector>,
+    providers: Vec<String>,
+    max_placeholder_len: usize,
+}
+
+impl Redactor {
+    pub fn new(config: &PrivacyConfig) -> AppResult<Self> {
+        let mut detectors = Vec::new();
+        // 先匹配的检测器优先，邮箱和主机名放在数字类规则之前，避免其中的数字被拆开替换
+        for name in ["email", "hostname", "id_card", "bank_card", "mobile", "ipv6", "ipv4"] {
+            if config.detectors.iter().any(|d| d == name) {
+                detectors.extend(Detector::builtin(name, &config.hostname_suffixes)?);
+            }
+        }
+        if let Some(unknown) = config.detectors.iter().find(|d| Detector::builtin(d, &[]).is_err()) {
+            return Err(AppError::Configuration {
+                message: format!("不支持的脱敏检测器: {}", unknown),
+            });
+        }
+        for custom in &config.custom_patterns {
+            detectors.push(Detector::new(&custom.name.to_uppercase(), &custom.pattern, |_| true, false)?);
+        }
+
+        let max_placeholder_len = detectors.iter().map(|d| d.label.len()).max().unwrap_or(0) + 12;
+        Ok(Self {
+            detectors,
+            providers: config.providers.clone(),
+            max_placeholder_len,
+        })
+    }
+
+    /// 发往该提供商的文本是否需要脱敏
+    pub fn applies_to(&self, provider: &str) -> bool {
+        self.providers.iter().any(|p| p == provider)
+    }
+
+    /// 检测文本中的敏感信息，按出现位置排序且互不重叠
+    pub fn detect(&self, text: &str) -> Vec<PiiMatch> {
+        // (起始, 结束, 检测器序号)
+        let mut matches: Vec<(usize, usize, usize)> = Vec::new();
+        for (index, detector) in self.detectors.iter().enumerate() {
+            for m in detector.regex.find_iter(text) {
+                if (!detector.bounded || is_bounded(text, m.start(), m.end())) && (detector.validate)(m.as_str()) {
+                    matches.push((m.start(), m.end(), index));
+                }
+            }
+        }
+        // 重叠时保留起始更早、其次检测器优先级更高的匹配
+        matches.sort_by_key(|&(start, _, index)| (start, index));
+
+        let mut detected: Vec<PiiMatch> = Vec::new();
+        for (start, end, index) in matches {
+            if detected.last().is_some_and(|last| start < last.end) {
+                continue;
+            }
+            detected.push(PiiMatch {
+                label: self.detectors[index].label.clone(),
+                value: text[start..end].to_string(),
+                start,
+                end,
+            });
+        }
+        detected
+    }
+
+    /// 替换文本中的敏感信息，映射记录到 `redaction`
+    pub fn redact(&self, text: &str, redaction: &mut Redaction) -> String {
+        let detected = self.detect(text);
+        if detected.is_empty() {
+            return text.to_string();
+        }
+
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for pii in detected {
+            output.push_str(&text[cursor..pii.start]);
+            output.push_str(&redaction.placeholder(&pii.label, &pii.value));
+            cursor = pii.end;
+        }
+        output.push_str(&text[cursor..]);
+        output
+    }
+
+    fn redact_prompt(&self, prompt: &RenderedPrompt, redaction: &mut Redaction) -> RenderedPrompt {
+        RenderedPrompt {
+            template_id: prompt.template_id.clone(),
+            system: self.redact(&prompt.system, redaction),
+            user: self.redact(&prompt.user, redaction),
+        }
+    }
+}
+
+/// 流式输出的占位符还原
+///
+/// 占位符可能被拆到相邻的两段增量中，末尾未闭合的部分留到下一段再还原
+struct StreamRestorer {
+    redaction: Redaction,
+    pending: String,
+    max_placeholder_len: usize,
+}
+
+impl StreamRestorer {
+    fn push(&mut self, delta: &str) -> String {
+        self.pending.push_str(delta);
+        let keep_from = match self.pending.rfind('[') {
+            Some(i) if !self.pending[i..].contains(']') && self.pending.len() - i < self.max_placeholder_len => i,
+            _ => self.pending.len(),
+        };
+        let ready: String = self.pending.drain(..keep_from).collect();
+        self.redaction.restore(&ready)
+    }
+
+    fn flush(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        self.redaction.restore(&rest)
+    }
+
+    fn handle(&mut self, event: Result<LLMStreamEvent>) -> Vec<Result<LLMStreamEvent>> {
+        let delta = |text: String| (!text.is_empty()).then_some(Ok(LLMStreamEvent::Delta(text)));
+        match event {
+            Ok(LLMStreamEvent::Delta(text)) => delta(self.push(&text)).into_iter().collect(),
+            Ok(LLMStreamEvent::Done { model, confidence, reasoning, token_usage }) => {
+                let done = Ok(LLMStreamEvent::Done {
+                    model,
+                    confidence,
+                    reasoning: self.redaction.restore(&reasoning),
+                    token_usage,
+                });
+                delta(self.flush()).into_iter().chain([done]).collect()
+            }
+            Err(e) => vec![Err(e)],
+        }
+    }
+}
+
+/// 发往外部服务前脱敏的Embedding服务
+pub struct RedactingEmbeddingService {
+    inner: Arc<dyn EmbeddingService + Send + Sync>,
+    redactor: Arc<Redactor>,
+}
+
+impl RedactingEmbeddingService {
+    pub fn new(inner: Arc<dyn EmbeddingService + Send + Sync>, redactor: Arc<Redactor>) -> Self {
+        Self { inner, redactor }
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for RedactingEmbeddingService {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let text = self.redactor.redact(text, &mut Redaction::new());
+        self.inner.embed(&text).await
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let texts: Vec<String> = texts.iter()
+            .map(|text| self.redactor.redact(text, &mut Redaction::new()))
+            .collect();
+        self.inner.embed_batch(&texts).await
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+}
+
+/// 发往外部服务前脱敏的重排序服务，返回的文档还原为原文
+pub struct RedactingRerankService {
+    inner: Arc<dyn RerankService + Send + Sync>,
+    redactor: Arc<Redactor>,
+}
+
+impl RedactingRerankService {
+    pub fn new(inner: Arc<dyn RerankService + Send + Sync>, redactor: Arc<Redactor>) -> Self {
+        Self { inner, redactor }
+    }
+
+    fn redact_all(&self, query: &str, documents: &[String], redaction: &mut Redaction) -> (String, Vec<String>) {
+        let query = self.redactor.redact(query, redaction);
+        let documents = documents.iter().map(|doc| self.redactor.redact(doc, redaction)).collect();
+        (query, documents)
+    }
+}
+
+fn restore_results(results: Vec<RerankResult>, redaction: &Redaction) -> Vec<RerankResult> {
+    results.into_iter()
+        .map(|result| RerankResult { document: redaction.restore(&result.document), ..result })
+        .collect()
+}
+
+#[async_trait]
+impl RerankService for RedactingRerankService {
+    async fn rerank(&self, query: &str, documents: &[String]) -> Result<Vec<RerankResult>> {
+        let mut redaction = Redaction::new();
+        let (query, documents) = self.redact_all(query, documents, &mut redaction);
+        let results = self.inner.rerank(&query, &documents).await?;
+        Ok(restore_results(results, &redaction))
+    }
+
+    async fn rerank_batch(
+        &self,
+        queries: &[String],
+        documents: &[Vec<String>],
+    ) -> Result<Vec<Vec<RerankResult>>> {
+        let mut redactions = Vec::with_capacity(queries.len());
+        let (queries, documents): (Vec<String>, Vec<Vec<String>>) = queries.iter()
+            .zip(documents)
+            .map(|(query, docs)| {
+                let mut redaction = Redaction::new();
+                let redacted = self.redact_all(query, docs, &mut redaction);
+                redactions.push(redaction);
+                redacted
+            })
+            .unzip();
+
+        let results = self.inner.rerank_batch(&queries, &documents).await?;
+        Ok(results.into_iter()
+            .zip(&redactions)
+            .map(|(results, redaction)| restore_results(results, redaction))
+            .collect())
+    }
+
+    fn max_documents(&self) -> usize {
+        self.inner.max_documents()
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+}
+
+/// 发往外部服务前脱敏的LLM服务，生成内容中的占位符还原为原文
+pub struct RedactingLLMService {
+    inner: Arc<dyn LLMService + Send + Sync>,
+    redactor: Arc<Redactor>,
+}
+
+impl RedactingLLMService {
+    pub fn new(inner: Arc<dyn LLMService + Send + Sync>, redactor: Arc<Redactor>) -> Self {
+        Self { inner, redactor }
+    }
+
+    /// 还原生成结果；要求JSON输出时按JSON字符串转义原文
+    fn restore(response: LLMResponse, redaction: &Redaction, options: &GenerationOptions) -> LLMResponse {
+        let content = match options.response_format {
+            Some(ResponseFormat::JsonObject) => redaction.restore_json(&response.content),
+            _ => redaction.restore(&response.content),
+        };
+        LLMResponse {
+            content,
+            reasoning: redaction.restore(&response.reasoning),
+            ..response
+        }
+    }
+}
+
+#[async_trait]
+impl LLMService for RedactingLLMService {
+    async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let mut redaction = Redaction::new();
+        let prompt = self.redactor.redact_prompt(prompt, &mut redaction);
+        if !redaction.is_empty() {
+            debug!("Prompt中已脱敏 {} 处敏感信息", redaction.len());
+        }
+        let response = self.inner.generate(&prompt, options).await?;
+        Ok(Self::restore(response, &redaction, options))
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        let mut redaction = Redaction::new();
+        let prompt = self.redactor.redact_prompt(prompt, &mut redaction);
+        let events = self.inner.generate_stream(&prompt, options).await?;
+
+        let mut restorer = StreamRestorer {
+            redaction,
+            pending: String::new(),
+            max_placeholder_len: self.redactor.max_placeholder_len,
+        };
+        Ok(Box::pin(events.flat_map(move |event| stream::iter(restorer.handle(event)))))
+    }
+
+    async fn generate_solutions_batch(
+        &self,
+        requests: &[(Ticket, Vec<RerankResult>)],
+        options: &GenerationOptions,
+    ) -> Result<Vec<LLMResponse>> {
+        let mut redactions = Vec::with_capacity(requests.len());
+        let requests: Vec<(Ticket, Vec<RerankResult>)> = requests.iter()
+            .map(|(ticket, cases)| {
+                let mut redaction = Redaction::new();
+                let mut ticket = ticket.clone();
+                ticket.title = self.redactor.redact(&ticket.title, &mut redaction);
+                ticket.description = self.redactor.redact(&ticket.description, &mut redaction);
+                let cases = cases.iter()
+                    .map(|case| RerankResult {
+                        document: self.redactor.redact(&case.document, &mut redaction),
+                        ..case.clone()
+                    })
+                    .collect();
+                redactions.push(redaction);
+                (ticket, cases)
+            })
+            .collect();
+
+        let responses = self.inner.generate_solutions_batch(&requests, options).await?;
+        Ok(responses.into_iter()
+            .zip(&redactions)
+            .map(|(response, redaction)| Self::restore(response, redaction, options))
+            .collect())
+    }
+
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse> {
+        let mut redaction = Redaction::new();
+        let prompt = self.redactor.redact(prompt, &mut redaction);
+        let response = self.inner.chat(&prompt, options).await?;
+        Ok(Self::restore(response, &redaction, options))
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    fn token_estimator(&self) -> TokenEstimator {
+        self.inner.token_estimator()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor() -> Redactor {
+        Redactor::new(&PrivacyConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn id_card_check_code() {
+        let cases = [
+            ("11010519491231002X", true),
+            ("11010519491231002x", true),
+            ("440304199001011233", true),
+            ("110105194912310021", false),
+            ("440304199001011234", false),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(valid_id_card(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn bank_card_luhn() {
+        let cases = [
+            ("4111111111111111", true),
+            ("4111 1111 1111 1111", true),
+            ("6222021234567890128", true),
+            ("4111111111111112", false),
+            ("6222021234567890123", false),
+            ("411111111111111", false), // 15位
+        ];
+        for (value, expected) in cases {
+            assert_eq!(valid_bank_card(value), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn detects_only_checked_numbers() {
+        let redactor = redactor();
+        let labels = |text: &str| redactor.detect(text).into_iter().map(|m| m.label).collect::<Vec<_>>();
+        assert_eq!(labels("身份证 11010519491231002X"), vec!["ID_CARD"]);
+        assert!(labels("单号 110105194912310021").is_empty(), "校验码错误不应视为身份证号");
+        assert_eq!(labels("卡号 4111-1111-1111-1111"), vec!["BANK_CARD"]);
+        assert!(labels("全角 １３８００１３８０００").is_empty(), "只匹配ASCII数字");
+    }
+
+    #[test]
+    fn ipv6_requires_hex_digits() {
+        let redactor = redactor();
+        assert!(redactor.detect("调用 std::io 失败，分隔符 :: 不是地址").is_empty());
+        let detected = redactor.detect("来源 fe80::1 与 2001:db8::8a2e:370:7334");
+        assert_eq!(detected.iter().map(|m| m.value.as_str()).collect::<Vec<_>>(), vec!["fe80::1", "2001:db8::8a2e:370:7334"]);
+    }
+
+    #[test]
+    fn restore_json_escapes_originals() {
+        let mut redaction = Redaction::new();
+        let placeholder = redaction.placeholder("SECRET", r#"a"b\c"#);
+        let json = format!(r#"{{"solution": "使用 {} 登录"}}"#, placeholder);
+        let restored: serde_json::Value = serde_json::from_str(&redaction.restore_json(&json)).unwrap();
+        assert_eq!(restored["solution"], r#"使用 a"b\c 登录"#);
+        assert_eq!(redaction.restore(&placeholder), r#"a"b\c"#);
+    }
+}
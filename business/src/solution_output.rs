@@ -0,0 +1,210 @@
+//! # 解决方案输出解析模块
+//!
+//! 约定模型输出的JSON结构，负责校验与格式修复
+
+use rag_deps::*;
+use rag_core::{
+    models::{Citation, SimilarTicket, StructuredSolution},
+    prompt::RenderedPrompt,
+};
+use std::collections::HashSet;
+
+/// 追加到系统提示词中的输出格式要求
+pub const OUTPUT_CONTRACT_INSTRUCTION: &str = r#"
+请只输出一个JSON对象，不要输出任何其他内容，格式如下：
+{
+  "steps": ["处理步骤1", "处理步骤2"],
+  "cited_ticket_ids": ["参考的历史工单ID"],
+  "confidence": 0.8,
+  "reasoning": "给出该方案的依据"
+}
+其中 steps 至少包含一个步骤；cited_ticket_ids 只能填写上下文中出现的工单ID，未参考任何历史工单时为空数组；
+confidence 为 0 到 1 之间的数字，表示你对方案有效性的把握。"#;
+
+/// 模型原始输出结构
+#[derive(Debug, Deserialize)]
+struct RawSolution {
+    steps: Vec<String>,
+    #[serde(default)]
+    cited_ticket_ids: Vec<String>,
+    confidence: f32,
+    reasoning: String,
+}
+
+/// 在系统提示词后追加输出格式要求
+pub fn with_output_contract(prompt: &RenderedPrompt) -> RenderedPrompt {
+    RenderedPrompt {
+        template_id: prompt.template_id.clone(),
+        system: format!("{}\n{}", prompt.system.trim_end(), OUTPUT_CONTRACT_INSTRUCTION),
+        user: prompt.user.clone(),
+    }
+}
+
+/// 构建格式错误后的重新请求Prompt
+pub fn reprompt_for_format(prompt: &RenderedPrompt, previous_output: &str, error: &str) -> RenderedPrompt {
+    RenderedPrompt {
+        template_id: prompt.template_id.clone(),
+        system: prompt.system.clone(),
+        user: format!(
+            "{}\n\n## 上一次输出\n{}\n\n上一次输出不符合要求的JSON格式（{}）。请严格按照要求只输出JSON对象。",
+            prompt.user, previous_output, error,
+        ),
+    }
+}
+
+/// 按约定结构解析并校验模型输出
+pub fn parse_structured_solution(text: &str) -> std::result::Result<StructuredSolution, String> {
+    let raw: RawSolution = serde_json::from_str(text.trim())
+        .map_err(|e| format!("JSON解析失败: {}", e))?;
+
+    let steps: Vec<String> = raw.steps.into_iter()
+        .map(|step| step.trim().to_string())
+        .filter(|step| !step.is_empty())
+        .collect();
+    if steps.is_empty() {
+        return Err("steps 不能为空".to_string());
+    }
+    if !raw.confidence.is_finite() || !(0.0..=1.0).contains(&raw.confidence) {
+        return Err(format!("confidence 超出范围: {}", raw.confidence));
+    }
+    Ok(StructuredSolution {
+        steps,
+        cited_ticket_ids: raw.cited_ticket_ids.into_iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect(),
+        confidence: raw.confidence,
+        reasoning: raw.reasoning.trim().to_string(),
+    })
+}
+
+/// 修复常见的JSON格式问题：代码块包裹、前后多余文本、尾随逗号
+pub fn repair_json(text: &str) -> String {
+    let unfenced = strip_code_fences(text);
+    let body = match (unfenced.find('{'), unfenced.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &unfenced[start..=end],
+        _ => unfenced,
+    };
+    remove_trailing_commas(body)
+}
+
+/// 去掉Markdown代码块标记，只保留第一个代码块中的内容
+fn strip_code_fences(text: &str) -> &str {
+    let Some(open) = text.find("```") else { return text.trim() };
+    let after_open = &text[open + 3..];
+    // 跳过代码块语言标记（如 ```json）
+    let content_start = after_open.find('\n').map(|i| i + 1).unwrap_or(0);
+    let content = &after_open[content_start..];
+    match content.find("```") {
+        Some(close) => content[..close].trim(),
+        None => content.trim(),
+    }
+}
+
+/// 删除对象或数组结尾处的多余逗号，字符串内部的内容保持不变
+fn remove_trailing_commas(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if in_string {
+            output.push(ch);
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => {
+                in_string = true;
+                output.push(ch);
+            }
+            ',' => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if !matches!(next, Some('}') | Some(']')) {
+                    output.push(ch);
+                }
+            }
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+/// 按提供给模型的上下文核对引用
+/// 
+/// 返回有效引用与无效引用（格式错误或不在上下文中），重复引用只保留一次
+pub fn resolve_citations(
+    cited_ticket_ids: &[String],
+    similar_tickets: &[SimilarTicket],
+) -> (Vec<Citation>, Vec<String>) {
+    let mut seen = HashSet::new();
+    let mut citations = Vec::new();
+    let mut invalid = Vec::new();
+
+    for raw in cited_ticket_ids {
+        let Ok(id) = Uuid::parse_str(raw) else {
+            invalid.push(raw.clone());
+            continue;
+        };
+        if !seen.insert(id) {
+            continue;
+        }
+        match similar_tickets.iter().find(|t| t.ticket_id == id) {
+            Some(ticket) => citations.push(Citation {
+                ticket_id: ticket.ticket_id,
+                title: ticket.title.clone(),
+                solution_id: ticket.solution_id,
+                solution: ticket.solution.clone(),
+                score: ticket.scores.fused,
+            }),
+            None => invalid.push(raw.clone()),
+        }
+    }
+    (citations, invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{"steps": ["重启服务"], "cited_ticket_ids": [], "confidence": 0.8, "reasoning": "日志显示内存泄漏"}"#;
+
+    #[test]
+    fn repair_json_cases() {
+        // (说明, 模型输出, 修复后能否按约定解析)
+        let cases = [
+            ("合法JSON", VALID.to_string(), true),
+            ("json代码块", format!("```json\n{}\n```", VALID), true),
+            ("无语言标记的代码块", format!("```\n{}\n```", VALID), true),
+            ("未闭合的代码块", format!("```json\n{}", VALID), true),
+            ("前后多余文本", format!("方案如下：\n{}\n以上。", VALID), true),
+            ("对象尾随逗号", r#"{"steps": ["重启服务"], "confidence": 0.8, "reasoning": "r",}"#.to_string(), true),
+            ("数组尾随逗号", r#"{"steps": ["重启服务", "检查日志",], "confidence": 0.8, "reasoning": "r"}"#.to_string(), true),
+            ("字符串中的逗号保留", r#"{"steps": ["执行 a,}"], "confidence": 0.8, "reasoning": "r"}"#.to_string(), true),
+            ("截断的对象", r#"{"steps": ["重启服务"], "confidence": 0.8, "reas"#.to_string(), false),
+            ("代码块中截断在数组里", format!("```json\n{}", r#"{"steps": ["重启服务", "检"#), false),
+            ("非JSON文本", "建议先重启服务，再观察内存。".to_string(), false),
+            ("空输出", String::new(), false),
+        ];
+        for (name, input, parses) in cases {
+            let repaired = repair_json(&input);
+            assert_eq!(parse_structured_solution(&repaired).is_ok(), parses, "{}: {}", name, repaired);
+        }
+    }
+
+    #[test]
+    fn repair_json_keeps_string_contents() {
+        let repaired = repair_json(r#"```json
+{"steps": ["执行 a,}", "转义 \"b\",]"], "confidence": 0.5, "reasoning": "r",}
+```"#);
+        let solution = parse_structured_solution(&repaired).unwrap();
+        assert_eq!(solution.steps, vec!["执行 a,}", r#"转义 "b",]"#]);
+    }
+}
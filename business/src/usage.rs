@@ -0,0 +1,592 @@
+//! # 用量统计模块
+//!
+//! 记录embedding、重排序和LLM调用的token、耗时与费用，并执行每日预算
+
+use rag_deps::*;
+use rag_core::{
+    config::UsageConfig,
+    models::{Ticket, UsageKind, UsageQuery, UsageRecord, UsageReport, usage_day},
+    prompt::{RenderedPrompt, TokenEstimator},
+    traits::*,
+    traits::embedding::ModelInfo,
+    traits::reranking::RerankResult,
+    traits::llm::{GenerationOptions, LLMResponse, LLMStream, LLMStreamEvent, TokenUsage},
+    errors::{AppError, AppResult},
+};
+use futures::StreamExt;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+
+/// 内存用量存储，重启后失效
+#[derive(Default)]
+pub struct InMemoryUsageStore {
+    records: RwLock<Vec<UsageRecord>>,
+}
+
+impl InMemoryUsageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageRepository for InMemoryUsageStore {
+    async fn insert_usage_record(&self, record: &UsageRecord) -> Result<()> {
+        self.records.write().unwrap().push(record.clone());
+        Ok(())
+    }
+
+    async fn list_usage_records(&self, query: &UsageQuery) -> Result<Vec<UsageRecord>> {
+        Ok(self.records.read().unwrap()
+            .iter()
+            .filter(|record| query.matches(record))
+            .cloned()
+            .collect())
+    }
+}
+
+/// 用量归属的工单
+#[derive(Debug, Clone, Default)]
+pub struct UsageScope {
+    pub ticket_id: Option<Uuid>,
+    pub category: Option<String>,
+}
+
+impl UsageScope {
+    pub fn for_ticket(ticket: &Ticket) -> Self {
+        Self {
+            ticket_id: Some(ticket.id),
+            category: Some(ticket.category.clone()),
+        }
+    }
+}
+
+tokio::task_local! {
+    static USAGE_SCOPE: UsageScope;
+}
+
+/// 在指定归属下执行，期间发起的模型调用都记到该工单
+pub async fn with_scope<F: Future>(scope: UsageScope, future: F) -> F::Output {
+    USAGE_SCOPE.scope(scope, future).await
+}
+
+fn current_scope() -> UsageScope {
+    USAGE_SCOPE.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// 当日预算状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub date: String,
+    pub spent: f64,
+    pub limit: Option<f64>,
+    pub exceeded: bool,
+}
+
+/// 预算检查结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetDecision {
+    /// 正常处理
+    Proceed,
+    /// 改用指定的模型路由后端
+    Downgrade(String),
+}
+
+/// 超出预算时的处理方式
+#[derive(Debug, Clone)]
+enum OverBudgetAction {
+    Downgrade(String),
+    Refuse,
+}
+
+/// 当日累计费用
+///
+/// 本进程的记录在 [`UsageTracker::record`] 中同步累加，不等待后台写入；
+/// 存储中的其他记录首次检查预算时加载一次，按ID排除本进程已累加的记录
+#[derive(Debug, Default)]
+struct DailySpend {
+    day: String,
+    recorded: HashSet<Uuid>, // 本进程当日已累加的记录
+    local: f64,
+    stored: Option<f64>, // 存储中的其他记录，未加载时为空
+}
+
+impl DailySpend {
+    /// 切换到 `day`，日期变化时清空累计
+    fn roll_to(&mut self, day: &str) {
+        if self.day.as_str() < day {
+            *self = Self { day: day.to_string(), ..Default::default() };
+        }
+    }
+}
+
+/// 用量追踪器
+///
+/// 职责：
+/// - 按模型单价计算每次调用的费用并异步保存记录
+/// - 同步维护当日累计费用，超出预算时降级或拒绝非紧急工单
+/// - 按条件汇总用量报表
+pub struct UsageTracker {
+    store: Arc<dyn UsageRepository>,
+    config: UsageConfig,
+    action: OverBudgetAction,
+    daily: Mutex<DailySpend>,
+}
+
+impl UsageTracker {
+    pub fn new(store: Arc<dyn UsageRepository>, config: UsageConfig) -> AppResult<Self> {
+        let action = match (config.over_budget.as_str(), &config.fallback_backend) {
+            ("refuse", _) => OverBudgetAction::Refuse,
+            ("downgrade", Some(backend)) => OverBudgetAction::Downgrade(backend.clone()),
+            ("downgrade", None) => return Err(AppError::Configuration {
+                message: "预算降级需要配置 usage.fallback_backend".to_string(),
+            }),
+            (other, _) => return Err(AppError::Configuration {
+                message: format!("不支持的超预算处理方式: {}", other),
+            }),
+        };
+        Ok(Self {
+            store,
+            config,
+            action,
+            daily: Mutex::new(DailySpend::default()),
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// 超出预算时降级使用的模型路由后端
+    pub fn fallback_backend(&self) -> Option<&str> {
+        match &self.action {
+            OverBudgetAction::Downgrade(backend) => Some(backend),
+            OverBudgetAction::Refuse => None,
+        }
+    }
+
+    /// 按配置的单价计算费用，未配置单价的模型使用服务的单次调用费用
+    pub fn cost(&self, info: &ModelInfo, model: &str, prompt_tokens: u32, completion_tokens: u32) -> f64 {
+        match self.config.pricing.get(model) {
+            Some(pricing) => {
+                pricing.per_call
+                    + prompt_tokens as f64 / 1000.0 * pricing.prompt_per_1k
+                    + completion_tokens as f64 / 1000.0 * pricing.completion_per_1k
+            }
+            None => info.cost_per_call.unwrap_or(0.0) as f64,
+        }
+    }
+
+    /// 记录一次调用，当日累计立即更新，存储在后台完成，失败只记录日志
+    pub fn record(&self, record: UsageRecord) {
+        if !self.config.enabled {
+            return;
+        }
+        {
+            let mut daily = self.daily.lock().unwrap();
+            let day = usage_day(record.created_at);
+            daily.roll_to(&day);
+            if daily.day == day && daily.recorded.insert(record.id) {
+                daily.local += record.cost;
+            }
+        }
+
+        let store = self.store.clone();
+        tokio::spawn(async move {
+            if let Err(e) = store.insert_usage_record(&record).await {
+                warn!("保存用量记录失败: {}", e);
+            }
+        });
+    }
+
+    /// 当日预算状态
+    pub async fn budget_status(&self) -> AppResult<BudgetStatus> {
+        let now = Utc::now();
+        let today = usage_day(now);
+        let cached = {
+            let mut daily = self.daily.lock().unwrap();
+            daily.roll_to(&today);
+            daily.stored.map(|stored| stored + daily.local)
+        };
+
+        let spent = match cached {
+            Some(spent) => spent,
+            None => {
+                let start_of_day = now.date_naive().and_hms_opt(0, 0, 0).map(|t| t.and_utc());
+                let query = UsageQuery { from: start_of_day, ..Default::default() };
+                let records = self.store.list_usage_records(&query).await?;
+
+                // 加载期间新增的记录已同步累加，写入存储的部分按ID排除
+                let mut daily = self.daily.lock().unwrap();
+                daily.roll_to(&today);
+                if daily.stored.is_none() && daily.day == today {
+                    let stored = records.iter()
+                        .filter(|r| !daily.recorded.contains(&r.id))
+                        .map(|r| r.cost)
+                        .sum();
+                    daily.stored = Some(stored);
+                }
+                daily.stored.unwrap_or_default() + daily.local
+            }
+        };
+
+        Ok(BudgetStatus {
+            date: today,
+            spent,
+            limit: self.config.daily_budget,
+            exceeded: self.config.daily_budget.is_some_and(|limit| spent >= limit),
+        })
+    }
+
+    /// 处理工单前检查预算，紧急工单不受限制
+    pub async fn check_budget(&self, ticket: &Ticket) -> AppResult<BudgetDecision> {
+        let Some(limit) = self.config.daily_budget.filter(|_| self.config.enabled) else {
+            return Ok(BudgetDecision::Proceed);
+        };
+        if ticket.priority >= self.config.urgent_priority {
+            return Ok(BudgetDecision::Proceed);
+        }
+        let status = self.budget_status().await?;
+        if !status.exceeded {
+            return Ok(BudgetDecision::Proceed);
+        }
+
+        match &self.action {
+            OverBudgetAction::Downgrade(backend) => {
+                warn!("今日费用 {:.4} 已超出预算 {:.4}，工单 {} 改用模型 {}", status.spent, limit, ticket.id, backend);
+                Ok(BudgetDecision::Downgrade(backend.clone()))
+            }
+            OverBudgetAction::Refuse => Err(AppError::BudgetExceeded {
+                message: format!("今日费用 {:.4} 已超出预算 {:.4}，仅处理紧急工单", status.spent, limit),
+            }),
+        }
+    }
+
+    /// 按条件汇总用量
+    pub async fn report(&self, query: &UsageQuery) -> AppResult<UsageReport> {
+        let records = self.store.list_usage_records(query).await?;
+        Ok(UsageReport::from_records(&records))
+    }
+}
+
+/// 一次进行中的模型调用
+struct MeteredCall {
+    tracker: Arc<UsageTracker>,
+    kind: UsageKind,
+    info: ModelInfo,
+    scope: UsageScope,
+    started: Instant,
+    prompt_tokens: usize, // 估算的输入token数，服务未返回用量时使用
+    estimator: TokenEstimator,
+}
+
+impl MeteredCall {
+    /// 调用成功，`token_usage` 为空时按估算值记录
+    fn succeed(self, model: Option<&str>, token_usage: Option<&TokenUsage>, output: &str) {
+        let (prompt_tokens, completion_tokens, estimated) = match token_usage {
+            Some(usage) => (usage.prompt_tokens, usage.completion_tokens, false),
+            None => (self.prompt_tokens as u32, self.estimator.estimate(output) as u32, true),
+        };
+        let model = model.filter(|m| !m.is_empty()).unwrap_or(&self.info.name).to_string();
+        let mut record = self.record(model, true);
+        record.prompt_tokens = prompt_tokens;
+        record.completion_tokens = completion_tokens;
+        record.total_tokens = prompt_tokens + completion_tokens;
+        record.estimated = estimated;
+        record.cost = self.tracker.cost(&self.info, &record.model, prompt_tokens, completion_tokens);
+        self.tracker.record(record);
+    }
+
+    /// 调用失败，不计token和费用
+    fn fail(self) {
+        let record = self.record(self.info.name.clone(), false);
+        self.tracker.record(record);
+    }
+
+    fn record(&self, model: String, success: bool) -> UsageRecord {
+        UsageRecord {
+            id: Uuid::new_v4(),
+            ticket_id: self.scope.ticket_id,
+            category: self.scope.category.clone(),
+            kind: self.kind,
+            provider: self.info.provider.clone(),
+            model,
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+            estimated: false,
+            latency_ms: self.started.elapsed().as_millis() as u64,
+            cost: 0.0,
+            success,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 估算多段文本的token数
+fn estimate_texts<'a>(estimator: &TokenEstimator, texts: impl IntoIterator<Item = &'a str>) -> usize {
+    texts.into_iter().map(|text| estimator.estimate(text)).sum()
+}
+
+/// 记录用量的Embedding服务
+pub struct MeteredEmbeddingService {
+    inner: Arc<dyn EmbeddingService + Send + Sync>,
+    tracker: Arc<UsageTracker>,
+}
+
+impl MeteredEmbeddingService {
+    pub fn new(inner: Arc<dyn EmbeddingService + Send + Sync>, tracker: Arc<UsageTracker>) -> Self {
+        Self { inner, tracker }
+    }
+
+    fn begin<'a>(&self, texts: impl IntoIterator<Item = &'a str>) -> MeteredCall {
+        let estimator = TokenEstimator::default();
+        MeteredCall {
+            tracker: self.tracker.clone(),
+            kind: UsageKind::Embedding,
+            info: self.inner.model_info(),
+            scope: current_scope(),
+            started: Instant::now(),
+            prompt_tokens: estimate_texts(&estimator, texts),
+            estimator,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for MeteredEmbeddingService {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let call = self.begin([text]);
+        let result = self.inner.embed(text).await;
+        match &result {
+            Ok(_) => call.succeed(None, None, ""),
+            Err(_) => call.fail(),
+        }
+        result
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let call = self.begin(texts.iter().map(String::as_str));
+        let result = self.inner.embed_batch(texts).await;
+        match &result {
+            Ok(_) => call.succeed(None, None, ""),
+            Err(_) => call.fail(),
+        }
+        result
+    }
+
+    fn dimension(&self) -> usize {
+        self.inner.dimension()
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+}
+
+/// 记录用量的重排序服务
+pub struct MeteredRerankService {
+    inner: Arc<dyn RerankService + Send + Sync>,
+    tracker: Arc<UsageTracker>,
+}
+
+impl MeteredRerankService {
+    pub fn new(inner: Arc<dyn RerankService + Send + Sync>, tracker: Arc<UsageTracker>) -> Self {
+        Self { inner, tracker }
+    }
+
+    fn begin<'a>(&self, texts: impl IntoIterator<Item = &'a str>) -> MeteredCall {
+        let estimator = TokenEstimator::default();
+        MeteredCall {
+            tracker: self.tracker.clone(),
+            kind: UsageKind::Rerank,
+            info: self.inner.model_info(),
+            scope: current_scope(),
+            started: Instant::now(),
+            prompt_tokens: estimate_texts(&estimator, texts),
+            estimator,
+        }
+    }
+}
+
+#[async_trait]
+impl RerankService for MeteredRerankService {
+    async fn rerank(&self, query: &str, documents: &[String]) -> Result<Vec<RerankResult>> {
+        let call = self.begin(std::iter::once(query).chain(documents.iter().map(String::as_str)));
+        let result = self.inner.rerank(query, documents).await;
+        match &result {
+            Ok(_) => call.succeed(None, None, ""),
+            Err(_) => call.fail(),
+        }
+        result
+    }
+
+    async fn rerank_batch(
+        &self,
+        queries: &[String],
+        documents: &[Vec<String>],
+    ) -> Result<Vec<Vec<RerankResult>>> {
+        let texts = queries.iter().chain(documents.iter().flatten()).map(String::as_str);
+        let call = self.begin(texts);
+        let result = self.inner.rerank_batch(queries, documents).await;
+        match &result {
+            Ok(_) => call.succeed(None, None, ""),
+            Err(_) => call.fail(),
+        }
+        result
+    }
+
+    fn max_documents(&self) -> usize {
+        self.inner.max_documents()
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+}
+
+/// 记录用量的LLM服务
+///
+/// 用量归属优先取生成参数中的路由依据，其次取当前任务的归属
+pub struct MeteredLLMService {
+    inner: Arc<dyn LLMService + Send + Sync>,
+    tracker: Arc<UsageTracker>,
+}
+
+impl MeteredLLMService {
+    pub fn new(inner: Arc<dyn LLMService + Send + Sync>, tracker: Arc<UsageTracker>) -> Self {
+        Self { inner, tracker }
+    }
+
+    fn begin<'a>(&self, options: &GenerationOptions, texts: impl IntoIterator<Item = &'a str>) -> MeteredCall {
+        let estimator = self.inner.token_estimator();
+        let scope = match &options.route {
+            Some(route) => UsageScope {
+                ticket_id: route.ticket_id,
+                category: route.category.clone(),
+            },
+            None => current_scope(),
+        };
+        MeteredCall {
+            tracker: self.tracker.clone(),
+            kind: UsageKind::Llm,
+            info: self.inner.model_info(),
+            scope,
+            started: Instant::now(),
+            prompt_tokens: estimate_texts(&estimator, texts),
+            estimator,
+        }
+    }
+
+    fn track(call: MeteredCall, result: &Result<LLMResponse>) {
+        match result {
+            Ok(response) => call.succeed(Some(&response.model), response.token_usage.as_ref(), &response.content),
+            Err(_) => call.fail(),
+        }
+    }
+}
+
+#[async_trait]
+impl LLMService for MeteredLLMService {
+    async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let call = self.begin(options, [prompt.system.as_str(), prompt.user.as_str()]);
+        let result = self.inner.generate(prompt, options).await;
+        Self::track(call, &result);
+        result
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        let call = self.begin(options, [prompt.system.as_str(), prompt.user.as_str()]);
+        let stream = match self.inner.generate_stream(prompt, options).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                call.fail();
+                return Err(e);
+            }
+        };
+
+        // 结束事件到达时记录，期间累积输出文本用于估算
+        let mut call = Some(call);
+        let mut output = String::new();
+        Ok(Box::pin(stream.map(move |event| {
+            match &event {
+                Ok(LLMStreamEvent::Delta(delta)) => output.push_str(delta),
+                Ok(LLMStreamEvent::Done { model, token_usage, .. }) => {
+                    if let Some(call) = call.take() {
+                        call.succeed(Some(model), token_usage.as_ref(), &output);
+                    }
+                }
+                Err(_) => {
+                    if let Some(call) = call.take() {
+                        call.fail();
+                    }
+                }
+            }
+            event
+        })))
+    }
+
+    async fn generate_solutions_batch(
+        &self,
+        requests: &[(Ticket, Vec<RerankResult>)],
+        options: &GenerationOptions,
+    ) -> Result<Vec<LLMResponse>> {
+        let calls: Vec<MeteredCall> = requests.iter()
+            .map(|(ticket, cases)| {
+                let texts = [ticket.title.as_str(), ticket.description.as_str()].into_iter()
+                    .chain(cases.iter().map(|case| case.document.as_str()));
+                let mut call = self.begin(options, texts);
+                call.scope = UsageScope::for_ticket(ticket);
+                call
+            })
+            .collect();
+
+        let result = self.inner.generate_solutions_batch(requests, options).await;
+        match &result {
+            Ok(responses) => {
+                for (call, response) in calls.into_iter().zip(responses) {
+                    call.succeed(Some(&response.model), response.token_usage.as_ref(), &response.content);
+                }
+            }
+            Err(_) => calls.into_iter().for_each(MeteredCall::fail),
+        }
+        result
+    }
+
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse> {
+        let call = self.begin(options, [prompt]);
+        let result = self.inner.chat(prompt, options).await;
+        Self::track(call, &result);
+        result
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        self.inner.model_info()
+    }
+
+    fn token_estimator(&self) -> TokenEstimator {
+        self.inner.token_estimator()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        self.inner.health_check().await
+    }
+}
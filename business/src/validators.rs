@@ -58,9 +58,46 @@ impl TicketValidator {
     }
     
     /// 验证工单更新
+    /// 
+    /// 状态变化需符合状态流转表，不符合时返回 `status` 字段的 `Validation`；
+    /// 更新后的字段需满足与新建工单相同的规则
     pub fn validate_ticket_update(ticket: &Ticket, updates: &TicketUpdate) -> Result<(), AppError> {
-        // TODO: 实现更新验证逻辑
-        todo!("实现工单更新验证")
+        if let Some(status) = &updates.status {
+            ticket.status.check_transition(status)?;
+        }
+        if updates.resolution_note.as_ref().is_some_and(|note| note.len() > 5000) {
+            return Err(AppError::validation("resolution_note", "解决说明不能超过5000字符"));
+        }
+        
+        let mut updated = ticket.clone();
+        updates.apply_to(&mut updated);
+        Self::validate_new_ticket(&NewTicket {
+            title: updated.title,
+            description: updated.description,
+            category: updated.category,
+            priority: updated.priority,
+            tags: updated.tags,
+        })
+    }
+    
+    /// 验证工单可以标记为已解决：需要已采纳的解决方案或非空的解决说明
+    pub fn validate_resolution(ticket: &Ticket, has_accepted_solution: bool) -> Result<(), AppError> {
+        let has_note = ticket.resolution_note.as_ref().is_some_and(|note| !note.trim().is_empty());
+        if !has_accepted_solution && !has_note {
+            return Err(AppError::validation("resolution_note", "没有已采纳的解决方案时，解决工单需填写解决说明"));
+        }
+        Ok(())
+    }
+    
+    /// 验证评论正文
+    pub fn validate_comment_body(body: &str) -> Result<(), AppError> {
+        if body.trim().is_empty() {
+            return Err(AppError::validation("body", "评论内容不能为空"));
+        }
+        if body.len() > 5000 {
+            return Err(AppError::validation("body", "评论内容不能超过5000字符"));
+        }
+        Ok(())
     }
 }
 
@@ -132,13 +169,82 @@ impl ConfigValidator {
 }
 
 /// 工单更新请求
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TicketUpdate {
     pub title: Option<String>,
     pub description: Option<String>,
+    pub category: Option<String>,
     pub status: Option<TicketStatus>,
     pub priority: Option<i32>,
     pub tags: Option<Vec<String>>,
+    pub resolution_note: Option<String>,
+}
+
+impl TicketUpdate {
+    /// 将指定的字段写入工单，不修改 `updated_at`
+    pub fn apply_to(&self, ticket: &mut Ticket) {
+        if let Some(title) = &self.title {
+            ticket.title = title.clone();
+        }
+        if let Some(description) = &self.description {
+            ticket.description = description.clone();
+        }
+        if let Some(category) = &self.category {
+            ticket.category = category.clone();
+        }
+        if let Some(status) = &self.status {
+            ticket.status = *status;
+        }
+        if let Some(priority) = self.priority {
+            ticket.priority = priority;
+        }
+        if let Some(tags) = &self.tags {
+            ticket.tags = tags.clone();
+        }
+        if let Some(note) = &self.resolution_note {
+            ticket.resolution_note = Some(note.clone());
+        }
+    }
+}
+
+/// 解决方案反馈更新，未指定的字段保持原值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeedbackUpdate {
+    pub is_accepted: Option<bool>,
+    pub score: Option<i32>,
+    pub comment: Option<String>,
+}
+
+impl FeedbackUpdate {
+    /// 合并到方案当前的反馈上
+    pub fn merge(&self, solution: &TicketSolution) -> Feedback {
+        Feedback {
+            solution_id: solution.id,
+            is_accepted: self.is_accepted.unwrap_or(solution.is_accepted),
+            score: self.score.or(solution.feedback_score),
+            comment: self.comment.clone().or_else(|| solution.feedback_comment.clone()),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 评论更新请求，未指定的字段保持原值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommentUpdate {
+    pub body: Option<String>,
+    pub internal: Option<bool>,
+}
+
+impl CommentUpdate {
+    /// 将指定的字段写入评论，不修改 `updated_at`
+    pub fn apply_to(&self, comment: &mut TicketComment) {
+        if let Some(body) = &self.body {
+            comment.body = body.clone();
+        }
+        if let Some(internal) = self.internal {
+            comment.internal = internal;
+        }
+    }
 }
 
 // 重新导出配置类型 - 临时解决方案
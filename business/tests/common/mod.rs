@@ -0,0 +1,353 @@
+//! # 业务层测试共用组件
+//!
+//! 以确定性的桩服务代替外部模型和向量库，组装完整的工单处理器
+
+#![allow(dead_code)]
+
+use rag_deps::*;
+use rag_core::{
+    config::*,
+    models::{NewTicket, Ticket},
+    prompt::RenderedPrompt,
+    traits::*,
+    traits::embedding::ModelInfo,
+    traits::llm::{GenerationOptions, LLMResponse},
+    traits::reranking::RerankResult,
+    traits::vector_db::{DatabaseInfo, DatabaseStats, SearchResult, VectorFilter, VectorMetadata, VectorRecord},
+};
+use rag_business::{
+    attachments::AttachmentService,
+    confidence::ConfidenceCalibrator,
+    context::ContextBuilder,
+    guardrails::Guardrails,
+    indexing::TicketIndexer,
+    injection::{InjectionDetector, ScreeningVectorDatabase},
+    lifecycle::TicketLifecycle,
+    processors::{GenerationComponents, TicketProcessor},
+    prompts::{InMemoryPromptStore, PromptManager},
+    usage::UsageTracker,
+};
+use rag_services::{attachment_storage::LocalFileStorage, memory_database::InMemoryDatabase, virus_scan::NoopVirusScanner};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// 桩向量维度
+pub const DIMENSION: usize = 32;
+
+fn model_info(name: &str) -> ModelInfo {
+    ModelInfo {
+        name: name.to_string(),
+        version: "test".to_string(),
+        provider: "stub".to_string(),
+        max_tokens: 32_000,
+        cost_per_call: None,
+    }
+}
+
+/// 按字符散列的词袋向量，字符重合越多越相似
+pub struct StubEmbedding;
+
+#[async_trait]
+impl EmbeddingService for StubEmbedding {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vector = vec![0.0; DIMENSION];
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            vector[c as usize % DIMENSION] += 1.0;
+        }
+        Ok(vector)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::new();
+        for text in texts {
+            vectors.push(self.embed(text).await?);
+        }
+        Ok(vectors)
+    }
+
+    fn dimension(&self) -> usize {
+        DIMENSION
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        model_info("stub-embedding")
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+/// 保持候选原有顺序的重排序
+pub struct StubRerank;
+
+#[async_trait]
+impl RerankService for StubRerank {
+    async fn rerank(&self, _query: &str, documents: &[String]) -> Result<Vec<RerankResult>> {
+        Ok(documents.iter().enumerate()
+            .map(|(index, document)| RerankResult {
+                index,
+                score: 1.0 / (index as f32 + 1.0),
+                document: document.clone(),
+            })
+            .collect())
+    }
+
+    async fn rerank_batch(&self, queries: &[String], documents: &[Vec<String>]) -> Result<Vec<Vec<RerankResult>>> {
+        let mut results = Vec::new();
+        for (query, documents) in queries.iter().zip(documents) {
+            results.push(self.rerank(query, documents).await?);
+        }
+        Ok(results)
+    }
+
+    fn max_documents(&self) -> usize {
+        1000
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        model_info("stub-rerank")
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+/// 内存向量库，按余弦相似度检索
+#[derive(Default)]
+pub struct StubVectorDB {
+    records: RwLock<HashMap<Uuid, VectorRecord>>,
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 { 0.0 } else { dot / norm }
+}
+
+#[async_trait]
+impl VectorDatabase for StubVectorDB {
+    async fn insert(&self, id: Uuid, vector: &[f32], metadata: VectorMetadata) -> Result<()> {
+        self.records.write().unwrap().insert(id, VectorRecord { id, vector: vector.to_vec(), metadata });
+        Ok(())
+    }
+
+    async fn insert_batch(&self, records: &[VectorRecord]) -> Result<()> {
+        let mut stored = self.records.write().unwrap();
+        for record in records {
+            stored.insert(record.id, record.clone());
+        }
+        Ok(())
+    }
+
+    async fn search(&self, query_vector: &[f32], limit: usize, filter: Option<VectorFilter>) -> Result<Vec<SearchResult>> {
+        let mut results: Vec<SearchResult> = self.records.read().unwrap().values()
+            .filter(|record| filter.as_ref().is_none_or(|filter| filter.matches(&record.metadata)))
+            .map(|record| SearchResult {
+                id: record.id,
+                score: cosine(query_vector, &record.vector),
+                keyword_score: None,
+                metadata: record.metadata.clone(),
+                vector: None,
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.id.cmp(&b.id)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    async fn hybrid_search(
+        &self,
+        query_vector: &[f32],
+        _keywords: &[String],
+        limit: usize,
+        filter: Option<VectorFilter>,
+    ) -> Result<Vec<SearchResult>> {
+        self.search(query_vector, limit, filter).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<()> {
+        self.records.write().unwrap().remove(&id);
+        Ok(())
+    }
+
+    async fn update(&self, id: Uuid, vector: &[f32], metadata: Option<VectorMetadata>) -> Result<()> {
+        let mut records = self.records.write().unwrap();
+        let Some(record) = records.get_mut(&id) else {
+            return Err(AnyhowError::msg(format!("向量 {} 不存在", id)));
+        };
+        record.vector = vector.to_vec();
+        if let Some(metadata) = metadata {
+            record.metadata = metadata;
+        }
+        Ok(())
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<VectorRecord>> {
+        Ok(self.records.read().unwrap().get(&id).cloned())
+    }
+
+    async fn scroll(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Uuid>> {
+        let mut ids: Vec<Uuid> = self.records.read().unwrap().keys()
+            .filter(|id| after.is_none_or(|after| **id > after))
+            .copied()
+            .collect();
+        ids.sort();
+        ids.truncate(limit);
+        Ok(ids)
+    }
+
+    async fn stats(&self) -> Result<DatabaseStats> {
+        Ok(DatabaseStats {
+            total_vectors: self.records.read().unwrap().len() as u64,
+            dimension: DIMENSION,
+            storage_size: 0,
+            index_type: "flat".to_string(),
+        })
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+
+    fn database_info(&self) -> DatabaseInfo {
+        DatabaseInfo {
+            name: "stub".to_string(),
+            version: "test".to_string(),
+            supports_hybrid_search: true,
+            supports_filtering: true,
+            max_dimension: DIMENSION,
+            recommended_batch_size: 100,
+        }
+    }
+}
+
+/// 返回固定结构化方案的LLM，记录每次调用的模型与Prompt
+pub struct StubLLM {
+    pub name: String,
+    pub calls: Mutex<Vec<(Option<String>, RenderedPrompt)>>,
+}
+
+impl StubLLM {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), calls: Mutex::new(Vec::new()) }
+    }
+
+    /// 各次调用指定的模型后端
+    pub fn requested_models(&self) -> Vec<Option<String>> {
+        self.calls.lock().unwrap().iter().map(|(model, _)| model.clone()).collect()
+    }
+}
+
+#[async_trait]
+impl LLMService for StubLLM {
+    async fn generate(&self, prompt: &RenderedPrompt, options: &GenerationOptions) -> Result<LLMResponse> {
+        self.calls.lock().unwrap().push((options.model.clone(), prompt.clone()));
+        let content = serde_json::json!({
+            "steps": ["重启服务", "检查连接池配置"],
+            "cited_ticket_ids": [],
+            "confidence": 0.8,
+            "reasoning": "参考相似案例",
+        });
+        Ok(LLMResponse {
+            content: content.to_string(),
+            model: options.model.clone().unwrap_or_else(|| self.name.clone()),
+            confidence: 0.8,
+            reasoning: String::new(),
+            token_usage: None,
+            avg_logprob: None,
+        })
+    }
+
+    async fn generate_solutions_batch(
+        &self,
+        _requests: &[(Ticket, Vec<RerankResult>)],
+        _options: &GenerationOptions,
+    ) -> Result<Vec<LLMResponse>> {
+        Ok(Vec::new())
+    }
+
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse> {
+        let prompt = RenderedPrompt {
+            template_id: "chat".to_string(),
+            system: String::new(),
+            user: prompt.to_string(),
+        };
+        self.generate(&prompt, options).await
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        model_info(&self.name)
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+/// 组装好的处理器及其依赖，便于测试直接检查各组件
+pub struct Harness {
+    pub processor: Arc<TicketProcessor>,
+    pub database: Arc<InMemoryDatabase>,
+    pub vector_db: Arc<StubVectorDB>,
+    pub llm: Arc<StubLLM>,
+    pub usage: Arc<UsageTracker>,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::with_usage(UsageConfig::default())
+    }
+
+    pub fn with_usage(usage: UsageConfig) -> Self {
+        let database = Arc::new(InMemoryDatabase::new());
+        let vector_db = Arc::new(StubVectorDB::default());
+        let llm = Arc::new(StubLLM::new("primary"));
+        let embedding: Arc<dyn EmbeddingService + Send + Sync> = Arc::new(StubEmbedding);
+        // 与工厂一致，入库和检索都经过提示注入检测
+        let detector = Arc::new(InjectionDetector::new(&InjectionConfig::default()).unwrap());
+        let screened: Arc<dyn VectorDatabase + Send + Sync> =
+            Arc::new(ScreeningVectorDatabase::new(vector_db.clone(), detector));
+        let usage = Arc::new(UsageTracker::new(database.clone(), usage).unwrap());
+        let generation = GenerationComponents {
+            prompts: Arc::new(PromptManager::new(Arc::new(InMemoryPromptStore::new()), "简体中文".to_string())),
+            confidence: Arc::new(ConfidenceCalibrator::new(ConfidenceConfig::default()).unwrap()),
+            context: ContextBuilder::new(ContextConfig::default(), 1024),
+            usage: usage.clone(),
+            guardrails: Arc::new(Guardrails::new(GuardrailConfig::default(), &PrivacyConfig::default(), llm.clone()).unwrap()),
+            indexer: Arc::new(TicketIndexer::new(embedding.clone(), screened.clone(), database.clone(), IndexingConfig::default())),
+            lifecycle: Arc::new(TicketLifecycle::new(Vec::new())),
+            attachments: Arc::new(AttachmentService::new(
+                Arc::new(LocalFileStorage::new(std::env::temp_dir().join("rag-business-tests"))),
+                Arc::new(NoopVirusScanner),
+                database.clone(),
+                AttachmentConfig::default(),
+            )),
+        };
+        let processor = Arc::new(TicketProcessor::new(
+            embedding,
+            Arc::new(StubRerank),
+            screened,
+            llm.clone(),
+            database.clone(),
+            generation,
+            RetrievalConfig::default(),
+        ));
+        Self { processor, database, vector_db, llm, usage }
+    }
+
+    /// 创建并索引工单
+    pub async fn create(&self, title: &str, description: &str) -> Ticket {
+        self.processor
+            .create_ticket(NewTicket {
+                title: title.to_string(),
+                description: description.to_string(),
+                category: "数据库".to_string(),
+                priority: 2,
+                tags: Vec::new(),
+            }, "tester")
+            .await
+            .unwrap()
+    }
+}
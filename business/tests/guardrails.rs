@@ -0,0 +1,84 @@
+//! # 生成方案安全检查测试
+//!
+//! 覆盖规则拦截、数据泄露隐藏和流式输出中跨分片的命中
+
+mod common;
+
+use rag_deps::*;
+use rag_core::{
+    config::{GuardrailConfig, PrivacyConfig},
+    models::{GuardrailAction, NewTicket, Ticket},
+};
+use rag_business::guardrails::{Guardrails, StreamGuard};
+use common::StubLLM;
+use std::sync::Arc;
+
+fn guardrails() -> Arc<Guardrails> {
+    let llm = Arc::new(StubLLM::new("judge"));
+    Arc::new(Guardrails::new(GuardrailConfig::default(), &PrivacyConfig::default(), llm).unwrap())
+}
+
+fn ticket() -> Ticket {
+    Ticket::new(NewTicket {
+        title: "日志目录占满磁盘".to_string(),
+        description: "用户 ops@example.com 反馈 /data/logs 占满磁盘".to_string(),
+        category: "存储".to_string(),
+        priority: 2,
+        tags: Vec::new(),
+    })
+}
+
+#[tokio::test]
+async fn destructive_command_blocks_the_solution() {
+    let guardrails = guardrails();
+    let guarded = guardrails
+        .guard(&ticket(), "1. 执行 rm -rf /data/logs 清理日志".to_string(), "释放空间".to_string(), None, 0.9)
+        .await;
+
+    assert_eq!(guarded.report.action, GuardrailAction::Block);
+    assert!(guarded.report.findings.iter().any(|f| f.rule == "rm_recursive_force"));
+    assert_eq!(guarded.content, GuardrailConfig::default().blocked_message);
+    assert!(!guarded.content.contains("rm -rf"));
+    assert_eq!(guarded.confidence, 0.0);
+    assert_eq!(guarded.report.original_confidence, Some(0.9));
+}
+
+#[tokio::test]
+async fn leaked_data_is_redacted_but_own_data_is_kept() {
+    let guardrails = guardrails();
+    let text = "参考 alice@other-corp.com 的处理记录，通知 ops@example.com";
+    let report = guardrails.review(&ticket(), text).await;
+
+    assert_eq!(report.action, GuardrailAction::Redact);
+    assert_eq!(report.findings.len(), 1, "{:?}", report.findings);
+    assert_eq!(report.findings[0].category, "data_leak");
+    let enforced = guardrails.enforce(&report, text);
+    assert!(!enforced.contains("alice@other-corp.com"));
+    assert!(enforced.contains("ops@example.com"), "当前工单自身的信息不算泄露");
+}
+
+#[test]
+fn stream_guard_redacts_a_leak_split_across_chunks() {
+    let mut guard = StreamGuard::new(guardrails(), ticket());
+
+    // 未换行的内容暂存，泄露片段不会被拆开放行
+    assert_eq!(guard.push("1. 联系 alice@oth"), None);
+    let released = guard.push("er-corp.com 获取日志\n2. 清理").unwrap();
+    assert!(!released.contains("alice@other-corp.com"), "{}", released);
+    assert!(!released.contains("清理"), "未完成的行不应放行");
+    assert!(released.contains("[已隐藏]"));
+
+    assert_eq!(guard.finish().as_deref(), Some("2. 清理"));
+    assert!(guard.text().contains("alice@other-corp.com"), "原文保留用于结束后的完整检查");
+}
+
+#[test]
+fn stream_guard_stops_after_a_blocked_line() {
+    let mut guard = StreamGuard::new(guardrails(), ticket());
+
+    assert_eq!(guard.push("1. 执行 rm -").unwrap_or_default(), "");
+    let released = guard.push("rf /data/logs\n").unwrap();
+    assert_eq!(released, GuardrailConfig::default().blocked_message);
+    assert_eq!(guard.push("2. 重启服务\n"), None);
+    assert_eq!(guard.finish(), None);
+}
@@ -0,0 +1,31 @@
+//! # 关键词索引测试
+
+mod common;
+
+use rag_deps::*;
+use rag_core::{keyword::extract_keywords, traits::{EmbeddingService, VectorDatabase}};
+use rag_business::keyword_index::KeywordIndexedVectorDatabase;
+use common::{Harness, StubEmbedding};
+
+#[tokio::test]
+async fn warm_up_rebuilds_the_index_from_tickets() {
+    let harness = Harness::new();
+    let timeout = harness.create("数据库连接超时", "应用访问数据库时连接池耗尽").await;
+    let slow = harness.create("数据库查询变慢", "应用访问数据库时查询耗时升高").await;
+    let printer = harness.create("打印机卡纸", "办公室打印机报错E42").await;
+
+    // 模拟重启：新的索引只能从工单表重建
+    let indexed = KeywordIndexedVectorDatabase::new(harness.vector_db.clone());
+    assert_eq!(indexed.warm_up(harness.database.as_ref()).await.unwrap(), 3);
+
+    let query = "数据库连接超时";
+    let vector = StubEmbedding.embed(query).await.unwrap();
+    let dense: Vec<Uuid> = indexed.search(&vector, 2, None).await.unwrap().iter().map(|r| r.id).collect();
+    assert!(!dense.contains(&printer.id));
+
+    let keywords = extract_keywords("打印机 E42", &[], 8);
+    let results = indexed.hybrid_search(&vector, &keywords, 2, None).await.unwrap();
+    let printer_hit = results.iter().find(|r| r.id == printer.id).expect("关键词召回的工单应进入结果");
+    assert!(printer_hit.keyword_score.is_some());
+    assert!(results.iter().any(|r| r.id == timeout.id || r.id == slow.id));
+}
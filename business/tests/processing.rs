@@ -0,0 +1,43 @@
+//! # 工单处理流程测试
+//!
+//! 使用桩服务运行完整的检索与生成流程
+
+mod common;
+
+use rag_deps::*;
+use rag_core::errors::AppError;
+use rag_business::processors::ProcessOptions;
+use common::Harness;
+
+#[tokio::test]
+async fn retrieval_excludes_the_ticket_itself() {
+    let harness = Harness::new();
+    let similar = harness.create("数据库连接超时", "应用访问数据库时连接池耗尽导致超时").await;
+    let ticket = harness.create("数据库连接超时", "应用访问数据库时连接池耗尽导致超时，重启后恢复").await;
+
+    let result = harness.processor.process(&ticket).await.unwrap();
+    let ids: Vec<Uuid> = result.similar_tickets.iter().map(|t| t.ticket_id).collect();
+    assert!(!ids.contains(&ticket.id), "工单不应作为自己的相似案例: {:?}", ids);
+    assert_eq!(ids, vec![similar.id]);
+    assert!(result.citations.iter().all(|c| c.ticket_id != ticket.id));
+}
+
+#[tokio::test]
+async fn fusion_strategy_can_be_chosen_per_request() {
+    let harness = Harness::new();
+    harness.create("数据库连接超时", "应用访问数据库时连接池耗尽").await;
+    harness.create("数据库查询变慢", "应用访问数据库时查询耗时升高").await;
+    let ticket = harness.create("数据库连接失败", "应用无法连接数据库").await;
+
+    let options = ProcessOptions {
+        fusion: Some(harness.processor.fusion_strategy("rerank_only").unwrap()),
+        ..Default::default()
+    };
+    let result = harness.processor.process_with_options(&ticket, &options).await.unwrap();
+    assert_eq!(result.similar_tickets.len(), 2);
+    // 仅重排序时融合分数只来自重排序，第一名的重排序分数最高
+    assert_eq!(result.similar_tickets[0].scores.rerank, Some(1.0));
+
+    let unknown = harness.processor.fusion_strategy("borda").unwrap_err();
+    assert!(matches!(unknown, AppError::Validation { ref field, .. } if field == "fusion_strategy"));
+}
@@ -0,0 +1,98 @@
+//! # 用量与预算测试
+//!
+//! 覆盖当日累计费用的同步更新，以及超出预算后的拒绝和降级
+
+mod common;
+
+use rag_deps::*;
+use rag_core::{
+    config::UsageConfig,
+    errors::AppError,
+    models::{UsageKind, UsageRecord},
+    traits::UsageRepository,
+};
+use rag_business::usage::{InMemoryUsageStore, UsageTracker};
+use common::Harness;
+use std::sync::Arc;
+
+fn usage_record(cost: f64) -> UsageRecord {
+    UsageRecord {
+        id: Uuid::new_v4(),
+        ticket_id: None,
+        category: None,
+        kind: UsageKind::Llm,
+        provider: "stub".to_string(),
+        model: "primary".to_string(),
+        prompt_tokens: 100,
+        completion_tokens: 50,
+        total_tokens: 150,
+        estimated: false,
+        latency_ms: 10,
+        cost,
+        success: true,
+        created_at: Utc::now(),
+    }
+}
+
+fn budget(over_budget: &str, fallback_backend: Option<&str>) -> UsageConfig {
+    UsageConfig {
+        daily_budget: Some(1.0),
+        over_budget: over_budget.to_string(),
+        fallback_backend: fallback_backend.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn daily_spend_counts_records_before_they_are_saved() {
+    let store = Arc::new(InMemoryUsageStore::new());
+    store.insert_usage_record(&usage_record(1.0)).await.unwrap();
+    let tracker = UsageTracker::new(store.clone(), budget("refuse", None)).unwrap();
+
+    // 后台写入尚未执行，累计费用已包含本次记录
+    tracker.record(usage_record(2.0));
+    assert_eq!(tracker.budget_status().await.unwrap().spent, 3.0);
+
+    // 写入完成后不重复计算
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    tracker.record(usage_record(0.5));
+    let status = tracker.budget_status().await.unwrap();
+    assert_eq!(status.spent, 3.5);
+    assert!(status.exceeded);
+
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    let restarted = UsageTracker::new(store, budget("refuse", None)).unwrap();
+    assert_eq!(restarted.budget_status().await.unwrap().spent, 3.5, "重启后从存储加载全部记录");
+}
+
+#[tokio::test]
+async fn refuses_non_urgent_tickets_over_budget() {
+    let harness = Harness::with_usage(budget("refuse", None));
+    harness.create("数据库连接超时", "应用访问数据库时连接池耗尽").await;
+    let mut ticket = harness.create("数据库连接失败", "应用无法连接数据库").await;
+
+    harness.usage.record(usage_record(1.5));
+    let calls = harness.llm.requested_models().len();
+    let error = harness.processor.process(&ticket).await.unwrap_err();
+    assert!(matches!(error, AppError::BudgetExceeded { .. }), "{:?}", error);
+    assert_eq!(harness.llm.requested_models().len(), calls, "拒绝时不应调用LLM");
+
+    ticket.priority = UsageConfig::default().urgent_priority;
+    harness.processor.process(&ticket).await.unwrap();
+    assert_eq!(harness.llm.requested_models().last(), Some(&None), "紧急工单按原模型处理");
+}
+
+#[tokio::test]
+async fn downgrades_to_the_fallback_backend_over_budget() {
+    let harness = Harness::with_usage(budget("downgrade", Some("cheap")));
+    harness.create("数据库连接超时", "应用访问数据库时连接池耗尽").await;
+    let ticket = harness.create("数据库连接失败", "应用无法连接数据库").await;
+
+    harness.processor.process(&ticket).await.unwrap();
+    assert_eq!(harness.llm.requested_models().last(), Some(&None), "预算内使用默认模型");
+
+    harness.usage.record(usage_record(1.5));
+    let result = harness.processor.process(&ticket).await.unwrap();
+    assert_eq!(harness.llm.requested_models().last(), Some(&Some("cheap".to_string())));
+    assert_eq!(result.model, "cheap");
+}
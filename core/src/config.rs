@@ -3,6 +3,7 @@
 //! 定义系统配置结构和加载逻辑
 
 use rag_deps::*;
+use std::collections::HashMap;
 
 /// 应用程序配置
 /// 
@@ -19,6 +20,30 @@ pub struct AppConfig {
     pub reranking: RerankingConfig,
     pub llm: LLMConfig,
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub retrieval: RetrievalConfig,
+    #[serde(default)]
+    pub prompts: PromptConfig,
+    #[serde(default)]
+    pub confidence: ConfidenceConfig,
+    #[serde(default)]
+    pub context: ContextConfig,
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    #[serde(default)]
+    pub usage: UsageConfig,
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub guardrails: GuardrailConfig,
+    #[serde(default)]
+    pub injection: InjectionConfig,
+    #[serde(default)]
+    pub indexing: IndexingConfig,
+    #[serde(default)]
+    pub lifecycle: LifecycleConfig,
+    #[serde(default)]
+    pub attachments: AttachmentConfig,
 }
 
 /// 服务器配置
@@ -81,6 +106,291 @@ pub struct LLMConfig {
     pub max_tokens: usize,
     pub temperature: f32,
     pub timeout: u64, // seconds
+    #[serde(default)]
+    pub max_concurrency: Option<usize>, // 批量生成的最大并发数
+    #[serde(default)]
+    pub context_window: Option<usize>, // 模型上下文窗口，未配置时使用服务默认值
+}
+
+/// 主模型（`[llm]` 配置）在模型路由中的后端名称
+pub const PRIMARY_LLM_BACKEND: &str = "primary";
+
+/// 模型路由配置
+/// 
+/// 职责：
+/// - 声明 `[llm]` 主模型之外的其他模型后端
+/// - 按规则、显式指定和A/B分流为每次生成选择后端
+/// 
+/// 未配置其他后端时不启用路由
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    pub default_backend: String,
+    pub backends: HashMap<String, LLMConfig>,
+    pub rules: Vec<RoutingRule>, // 按顺序匹配，第一条命中的规则生效
+    pub ab_test: Option<AbTestConfig>,
+}
+
+/// 模型路由规则，所有已设置的条件同时满足时命中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub backend: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    pub min_priority: Option<i32>,
+    pub max_priority: Option<i32>,
+    pub min_ticket_chars: Option<usize>,
+    pub max_ticket_chars: Option<usize>,
+    pub min_retrieval_confidence: Option<f32>,
+    pub max_retrieval_confidence: Option<f32>,
+}
+
+/// A/B分流配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbTestConfig {
+    pub backend: String,
+    pub percent: u8, // 未命中规则的工单中分流到该后端的百分比
+}
+
+/// 用量与费用配置
+/// 
+/// 职责：
+/// - 指定用量记录的存储（database、memory）
+/// - 提供各模型的单价，未配置的模型使用服务自带的单次调用费用
+/// - 设置每日预算及超出后的处理方式（downgrade 切换到便宜模型、refuse 拒绝非紧急工单）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    pub enabled: bool,
+    pub store: String,
+    pub pricing: HashMap<String, ModelPricing>, // 模型名 -> 单价
+    pub daily_budget: Option<f64>,
+    pub over_budget: String,
+    pub fallback_backend: Option<String>, // downgrade 时使用的模型路由后端
+    pub urgent_priority: i32, // 优先级不低于该值的工单不受预算限制
+}
+
+/// 模型单价
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelPricing {
+    pub prompt_per_1k: f64,
+    pub completion_per_1k: f64,
+    pub per_call: f64,
+}
+
+/// 敏感信息脱敏配置
+/// 
+/// 职责：
+/// - 指定需要脱敏的服务提供商，发往这些提供商的文本先替换敏感信息
+/// - 选择启用的内置检测器（mobile、id_card、bank_card、email、ipv4、ipv6、hostname）
+/// - 提供内部主机名后缀和自定义正则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    pub enabled: bool,
+    pub providers: Vec<String>,
+    pub detectors: Vec<String>,
+    pub hostname_suffixes: Vec<String>, // 如 ".corp.example.com"
+    pub custom_patterns: Vec<CustomPattern>,
+}
+
+/// 自定义脱敏规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomPattern {
+    pub name: String, // 占位符前缀，如 EMPLOYEE_ID
+    pub pattern: String,
+}
+
+/// 生成方案安全检查配置
+/// 
+/// 职责：
+/// - 为内置规则（破坏性命令、数据泄露、提示注入）指定处理方式：off、flag、redact、block
+/// - 提供自定义正则规则
+/// - 可选地由LLM复核方案安全性
+/// - 指定命中规则后的置信度系数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuardrailConfig {
+    pub enabled: bool,
+    pub destructive_commands: String,
+    pub data_leak: String, // 方案中出现当前工单以外的敏感信息，检测器沿用 privacy 配置
+    pub prompt_injection: String,
+    pub custom_rules: Vec<GuardrailRule>,
+    pub llm_judge: bool,
+    pub judge_backend: Option<String>, // 复核使用的模型后端，默认与生成相同
+    pub judge_action: String, // off / flag / block，复核不定位片段，不支持 redact
+    pub flag_confidence_factor: f32,
+    pub redact_confidence_factor: f32,
+    pub blocked_message: String,
+}
+
+/// 历史工单提示注入检测配置
+/// 
+/// 职责：
+/// - 入库时检测工单中类似指令的文本，命中规则数达到阈值时隔离
+/// - 提供自定义检测正则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InjectionConfig {
+    pub enabled: bool,
+    pub quarantine_threshold: usize, // 命中的不同规则数达到该值时隔离
+    pub custom_patterns: Vec<String>,
+}
+
+/// 工单向量索引配置
+/// 
+/// 职责：
+/// - 控制索引事件（outbox）的轮询与失败重试
+/// - 控制工单表与向量库的定期对账
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IndexingConfig {
+    pub max_retries: u32, // 超过后事件标记为失败，由对账修复
+    pub retry_initial_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+    pub poll_interval_ms: u64,
+    pub batch_size: usize,
+    pub reconcile_interval_secs: u64, // 0 表示不定期对账
+    pub reconcile_batch_size: usize,
+}
+
+/// 工单状态流转配置
+/// 
+/// 职责：
+/// - 开关状态流转后执行的内置钩子
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleConfig {
+    pub auto_process_on_new: bool,   // 新建工单后自动生成解决方案
+    pub finetune_on_resolved: bool,  // 工单解决后生成微调数据
+}
+
+/// 工单附件配置
+/// 
+/// 职责：
+/// - 选择附件存储后端：local（本地目录）或 s3（S3兼容对象存储）
+/// - 限制单个附件大小和每个工单的附件数量
+/// - 控制文本提取和进入向量索引的错误行数量
+/// - 配置病毒扫描：none 或 clamd
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentConfig {
+    pub storage: String,
+    pub local_path: String,
+    pub s3: Option<S3StorageConfig>,
+    pub max_size_bytes: usize,
+    pub max_per_ticket: usize,
+    pub max_extracted_chars: usize, // 提取文本保存的最大字符数
+    pub max_error_lines: usize,     // 每个附件进入向量索引的错误行数
+    pub virus_scan: String,
+    pub clamd_address: Option<String>, // 如 127.0.0.1:3310
+}
+
+/// S3兼容对象存储配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3StorageConfig {
+    pub endpoint: String, // 如 https://s3.us-east-1.amazonaws.com 或 http://minio:9000
+    pub bucket: String,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default = "default_path_style")]
+    pub path_style: bool, // 路径形式访问存储桶，MinIO等自建服务通常需要
+    #[serde(default)]
+    pub prefix: String,   // 对象键前缀
+}
+
+fn default_path_style() -> bool {
+    true
+}
+
+/// 自定义安全检查规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardrailRule {
+    pub name: String,
+    pub pattern: String,
+    pub action: String, // flag, redact, block
+}
+
+/// 检索与融合配置
+/// 
+/// 职责：
+/// - 指定多路排序（向量、关键词、重排序）的融合策略
+/// - 提供各路分数的权重与候选数量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetrievalConfig {
+    pub fusion_strategy: String, // rrf, weighted, rerank_only
+    pub rrf_k: f32,
+    pub dense_weight: f32,
+    pub keyword_weight: f32,
+    pub rerank_weight: f32,
+    pub candidate_limit: usize,
+    pub top_k: usize,
+    pub max_keywords: usize, // 混合检索提取的关键词上限，0表示仅向量检索
+    pub boost: BoostConfig,
+}
+
+/// 检索结果加权配置
+/// 
+/// 职责：
+/// - 在重排序和融合之后按工单时效衰减分数
+/// - 根据历史解决方案的采纳情况加权或降权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoostConfig {
+    pub enabled: bool,
+    pub time_decay_half_life_days: f32, // <= 0 表示不做时间衰减
+    pub time_decay_floor: f32,          // 时间衰减系数下限
+    pub accepted_boost: f32,            // 已采纳方案的加权比例
+    pub rejected_penalty: f32,          // 被拒绝或低分方案的降权比例
+    pub low_score_threshold: i32,       // 反馈评分不高于该值视为低质量
+}
+
+/// Prompt模板配置
+/// 
+/// 职责：
+/// - 指定模板文件目录，未配置时仅使用内置模板
+/// - 指定默认输出语言
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PromptConfig {
+    pub directory: Option<String>,
+    pub output_language: String,
+}
+
+/// 置信度配置
+/// 
+/// 职责：
+/// - 指定各置信度信号的权重
+/// - 指定基于历史反馈的校准方法和样本数量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfidenceConfig {
+    pub rerank_weight: f32,
+    pub agreement_weight: f32,
+    pub self_rating_weight: f32,
+    pub token_prob_weight: f32,
+    pub request_logprobs: bool,   // 生成时请求token对数概率
+    pub calibration: String,      // isotonic, platt, none
+    pub min_samples: usize,       // 样本少于该值时不做校准
+    pub max_samples: usize,       // 拟合时最多使用的最近样本数
+    pub refit_interval_secs: u64, // 定期重新拟合的间隔，0 表示只在启动时拟合
+}
+
+/// Prompt上下文配置
+/// 
+/// 职责：
+/// - 指定为模型输出预留的token数
+/// - 限制单个相似案例的描述和历史方案长度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    pub reserved_output_tokens: Option<usize>, // 未配置时使用 llm.max_tokens
+    pub max_description_tokens: usize,
+    pub max_solution_tokens: usize,
+    pub min_case_tokens: usize, // 剩余预算低于该值时不再截断放入，直接丢弃
 }
 
 /// 日志配置
@@ -126,6 +436,178 @@ impl Default for ServerConfig {
     }
 }
 
+impl Default for RetrievalConfig {
+    fn default() -> Self {
+        Self {
+            fusion_strategy: "rrf".to_string(),
+            rrf_k: 60.0,
+            dense_weight: 1.0,
+            keyword_weight: 1.0,
+            rerank_weight: 1.0,
+            candidate_limit: 100,
+            top_k: 10,
+            max_keywords: 16,
+            boost: BoostConfig::default(),
+        }
+    }
+}
+
+impl Default for BoostConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            time_decay_half_life_days: 365.0,
+            time_decay_floor: 0.2,
+            accepted_boost: 0.2,
+            rejected_penalty: 0.5,
+            low_score_threshold: 2,
+        }
+    }
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            output_language: "简体中文".to_string(),
+        }
+    }
+}
+
+impl Default for ConfidenceConfig {
+    fn default() -> Self {
+        Self {
+            rerank_weight: 1.0,
+            agreement_weight: 1.0,
+            self_rating_weight: 1.0,
+            token_prob_weight: 1.0,
+            request_logprobs: true,
+            calibration: "isotonic".to_string(),
+            min_samples: 50,
+            max_samples: 5000,
+            refit_interval_secs: 3600,
+        }
+    }
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            reserved_output_tokens: None,
+            max_description_tokens: 400,
+            max_solution_tokens: 300,
+            min_case_tokens: 80,
+        }
+    }
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            providers: vec!["qwen".to_string(), "openai".to_string()],
+            detectors: ["mobile", "id_card", "bank_card", "email", "ipv4", "ipv6", "hostname"]
+                .iter()
+                .map(|d| d.to_string())
+                .collect(),
+            hostname_suffixes: Vec::new(),
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+impl Default for GuardrailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            destructive_commands: "block".to_string(),
+            data_leak: "redact".to_string(),
+            prompt_injection: "flag".to_string(),
+            custom_rules: Vec::new(),
+            llm_judge: false,
+            judge_backend: None,
+            judge_action: "flag".to_string(),
+            flag_confidence_factor: 0.7,
+            redact_confidence_factor: 0.5,
+            blocked_message: "生成的方案未通过安全检查，已拦截，请人工处理。".to_string(),
+        }
+    }
+}
+
+impl Default for InjectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quarantine_threshold: 1,
+            custom_patterns: Vec::new(),
+        }
+    }
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        Self {
+            auto_process_on_new: false,
+            finetune_on_resolved: true,
+        }
+    }
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            storage: "local".to_string(),
+            local_path: "./data/attachments".to_string(),
+            s3: None,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_per_ticket: 20,
+            max_extracted_chars: 100_000,
+            max_error_lines: 50,
+            virus_scan: "none".to_string(),
+            clamd_address: None,
+        }
+    }
+}
+
+impl Default for IndexingConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            retry_initial_delay_ms: 1000,
+            retry_max_delay_ms: 60_000,
+            poll_interval_ms: 1000,
+            batch_size: 100,
+            reconcile_interval_secs: 3600,
+            reconcile_batch_size: 500,
+        }
+    }
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            store: "database".to_string(),
+            pricing: HashMap::new(),
+            daily_budget: None,
+            over_budget: "refuse".to_string(),
+            fallback_backend: None,
+            urgent_priority: 4,
+        }
+    }
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            default_backend: PRIMARY_LLM_BACKEND.to_string(),
+            backends: HashMap::new(),
+            rules: Vec::new(),
+            ab_test: None,
+        }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
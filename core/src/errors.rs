@@ -27,18 +27,27 @@ pub enum AppError {
     #[error("LLM服务错误: {message}")]
     LLMService { message: String },
     
+    #[error("附件存储错误: {message}")]
+    Storage { message: String },
+    
     #[error("配置错误: {message}")]
     Configuration { message: String },
     
     #[error("验证错误: {field}: {message}")]
     Validation { field: String, message: String },
     
+    #[error("版本冲突: 期望版本 {expected}，当前版本 {actual}")]
+    VersionConflict { expected: i64, actual: i64 },
+    
     #[error("资源未找到: {resource}: {id}")]
     NotFound { resource: String, id: String },
     
     #[error("权限不足: {action}")]
     Permission { action: String },
     
+    #[error("超出预算: {message}")]
+    BudgetExceeded { message: String },
+    
     #[error("网络请求失败: {message}")]
     Network { message: String },
     
@@ -100,9 +109,13 @@ impl From<serde_json::Error> for AppError {
 }
 
 impl From<anyhow::Error> for AppError {
+    /// 包装的是AppError时原样取出，保留错误类别
     fn from(err: anyhow::Error) -> Self {
-        Self::Internal {
-            message: err.to_string(),
+        match err.downcast::<AppError>() {
+            Ok(err) => err,
+            Err(err) => Self::Internal {
+                message: err.to_string(),
+            },
         }
     }
 } 
\ No newline at end of file
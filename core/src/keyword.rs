@@ -0,0 +1,174 @@
+//! # 关键词检索模块
+//!
+//! 提供中英文混合分词、可增量维护的倒排索引和BM25打分，供不支持原生混合检索的向量数据库使用
+
+use rag_deps::*;
+use std::collections::{HashMap, HashSet};
+
+/// BM25参数
+const BM25_K1: f32 = 1.2;
+const BM25_B: f32 = 0.75;
+
+/// 常见停用词
+const STOP_WORDS: &[&str] = &[
+    "the", "a", "an", "and", "or", "of", "to", "in", "on", "for", "is", "are", "was", "be",
+    "with", "not", "it", "this", "that", "at", "by", "from",
+    "的", "了", "是", "在", "和", "与", "或", "也", "就", "都", "我", "你", "他", "她", "它",
+];
+
+/// 分词
+///
+/// 英文和数字按连续字符切分并转小写，中日韩文字按二元组（bigram）切分，
+/// 单个汉字组成的片段保留为单字词
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+
+    for ch in text.chars() {
+        if is_cjk(ch) {
+            flush_word(&mut word, &mut tokens);
+            cjk_run.push(ch);
+        } else if ch.is_alphanumeric() || ch == '_' {
+            flush_cjk(&mut cjk_run, &mut tokens);
+            word.extend(ch.to_lowercase());
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk_run, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk_run, &mut tokens);
+
+    tokens.retain(|t| !STOP_WORDS.contains(&t.as_str()));
+    tokens
+}
+
+/// 从工单文本和标签中提取检索关键词（去重，保持出现顺序）
+pub fn extract_keywords(text: &str, tags: &[String], max_keywords: usize) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .chain(tokenize(text).into_iter().filter(|t| t.chars().count() > 1))
+        .filter(|t| seen.insert(t.clone()))
+        .take(max_keywords)
+        .collect()
+}
+
+/// 是否为中日韩文字
+pub fn is_cjk(ch: char) -> bool {
+    matches!(ch as u32,
+        0x4E00..=0x9FFF     // CJK统一汉字
+        | 0x3400..=0x4DBF   // 扩展A
+        | 0x3040..=0x30FF   // 日文假名
+        | 0xAC00..=0xD7AF   // 韩文
+        | 0xF900..=0xFAFF)  // 兼容汉字
+}
+
+fn flush_word(word: &mut String, tokens: &mut Vec<String>) {
+    if !word.is_empty() {
+        tokens.push(std::mem::take(word));
+    }
+}
+
+fn flush_cjk(run: &mut Vec<char>, tokens: &mut Vec<String>) {
+    match run.len() {
+        0 => {}
+        1 => tokens.push(run[0].to_string()),
+        _ => tokens.extend(run.windows(2).map(|w| w.iter().collect::<String>())),
+    }
+    run.clear();
+}
+
+/// 倒排索引
+///
+/// 职责：
+/// - 按词项记录文档及词频，支持按ID覆盖和删除文档
+/// - 使用BM25对查询词打分
+#[derive(Debug, Default)]
+pub struct InvertedIndex {
+    postings: HashMap<String, HashMap<Uuid, u32>>,
+    documents: HashMap<Uuid, Vec<String>>, // 文档的全部词项（含重复），长度即文档长度
+    total_length: usize,
+}
+
+impl InvertedIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加文档，ID已存在时覆盖原文档
+    pub fn add_document(&mut self, id: Uuid, text: &str) {
+        self.remove_document(id);
+        let tokens = tokenize(text);
+        for token in &tokens {
+            *self.postings.entry(token.clone()).or_default().entry(id).or_default() += 1;
+        }
+        self.total_length += tokens.len();
+        self.documents.insert(id, tokens);
+    }
+
+    /// 删除文档，返回文档是否存在
+    pub fn remove_document(&mut self, id: Uuid) -> bool {
+        let Some(tokens) = self.documents.remove(&id) else {
+            return false;
+        };
+        for token in &tokens {
+            if let Some(docs) = self.postings.get_mut(token) {
+                docs.remove(&id);
+                if docs.is_empty() {
+                    self.postings.remove(token);
+                }
+            }
+        }
+        self.total_length -= tokens.len();
+        true
+    }
+
+    /// 是否包含文档
+    pub fn contains(&self, id: Uuid) -> bool {
+        self.documents.contains_key(&id)
+    }
+
+    /// 文档数量
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// BM25检索，按分数降序返回命中的文档，分数相同时按ID排序
+    pub fn search(&self, keywords: &[String], limit: usize) -> Vec<(Uuid, f32)> {
+        if self.is_empty() {
+            return vec![];
+        }
+
+        let doc_count = self.documents.len() as f32;
+        let avg_len = self.total_length as f32 / doc_count;
+        let query_terms: HashSet<String> = keywords.iter()
+            .flat_map(|k| tokenize(k))
+            .collect();
+
+        let mut scores: HashMap<Uuid, f32> = HashMap::new();
+        for term in &query_terms {
+            let Some(postings) = self.postings.get(term) else { continue };
+            let df = postings.len() as f32;
+            let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+            for (id, tf) in postings {
+                let tf = *tf as f32;
+                let len_norm = 1.0 - BM25_B + BM25_B * self.documents[id].len() as f32 / avg_len.max(1.0);
+                *scores.entry(*id).or_default() += idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * len_norm);
+            }
+        }
+
+        let mut results: Vec<(Uuid, f32)> = scores.into_iter().collect();
+        results.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        results.truncate(limit);
+        results
+    }
+}
@@ -10,4 +10,6 @@
 pub mod traits;
 pub mod models;
 pub mod errors;
-pub mod config; 
\ No newline at end of file
+pub mod config;
+pub mod keyword;
+pub mod prompt;
@@ -0,0 +1,40 @@
+//! # 工单附件模型
+//!
+//! 附件内容保存在附件存储中，关系库只保存元数据和提取的文本
+
+use rag_deps::*;
+
+/// 工单附件
+///
+/// 职责：
+/// - 记录文件名、按内容识别的类型、大小和存储位置
+/// - 保存从文本类附件提取的内容及其中的错误行，错误行随工单进入向量索引
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub filename: String,
+    pub content_type: String, // 按文件内容识别，不采信客户端声明的类型
+    pub size_bytes: i64,
+    pub storage_key: String,
+    pub extracted_text: Option<String>, // 仅文本类附件，超出上限时截断
+    pub error_lines: Vec<String>,
+    pub uploaded_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 病毒扫描结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected { signature: String },
+}
+
+/// 格式化附件中的错误行（用于向量化），按附件顺序每行一条
+pub fn format_error_lines(attachments: &[Attachment]) -> String {
+    attachments.iter()
+        .flat_map(|attachment| attachment.error_lines.iter())
+        .map(|line| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,80 @@
+//! # 审计日志模型
+//!
+//! 工单及其解决方案的变更记录，只追加不修改
+
+use rag_deps::*;
+
+/// 系统自动操作使用的操作者
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// 审计动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Created,           // 工单创建
+    FieldChanged,      // 字段变更，`field` 为字段名
+    StatusChanged,     // 状态流转
+    SolutionAccepted,  // 采纳解决方案
+    SolutionRejected,  // 拒绝解决方案
+    FeedbackSubmitted, // 提交评分或评论
+    CommentAdded,      // 新增工单评论
+    CommentEdited,     // 修改工单评论
+    CommentDeleted,    // 删除工单评论
+    AttachmentAdded,   // 上传附件
+    AttachmentDeleted, // 删除附件
+    Deleted,           // 工单删除
+}
+
+/// 审计记录
+///
+/// 职责：
+/// - 记录操作者、时间和动作
+/// - 记录变更前后的值，未涉及时为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub solution_id: Option<Uuid>,
+    pub actor: String,
+    pub action: AuditAction,
+    pub field: Option<String>,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditEntry {
+    /// 创建不含变更值的记录
+    pub fn new(ticket_id: Uuid, actor: &str, action: AuditAction) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            ticket_id,
+            solution_id: None,
+            actor: actor.to_string(),
+            action,
+            field: None,
+            old_value: None,
+            new_value: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// 记录字段变更前后的值
+    pub fn with_change(
+        mut self,
+        field: &str,
+        old_value: Option<serde_json::Value>,
+        new_value: Option<serde_json::Value>,
+    ) -> Self {
+        self.field = Some(field.to_string());
+        self.old_value = old_value;
+        self.new_value = new_value;
+        self
+    }
+
+    /// 关联解决方案
+    pub fn with_solution(mut self, solution_id: Uuid) -> Self {
+        self.solution_id = Some(solution_id);
+        self
+    }
+}
@@ -0,0 +1,79 @@
+//! # 工单评论模型
+//!
+//! 提单人与处理人围绕工单的往来沟通记录
+
+use rag_deps::*;
+
+/// 评论者角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentRole {
+    Requester, // 提单人
+    Agent,     // 处理人员
+    System,    // 系统自动生成
+}
+
+impl CommentRole {
+    /// 沟通记录中的角色名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Requester => "提单人",
+            Self::Agent => "处理人员",
+            Self::System => "系统",
+        }
+    }
+}
+
+/// 工单评论
+///
+/// 职责：
+/// - 记录评论者、角色、正文和时间
+/// - 区分内部备注（`internal`，仅处理人员可见）与公开回复
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketComment {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub author: String,
+    pub role: CommentRole,
+    pub body: String,
+    pub internal: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 新建评论请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewComment {
+    pub role: CommentRole,
+    pub body: String,
+    #[serde(default)]
+    pub internal: bool,
+}
+
+impl TicketComment {
+    /// 创建新评论
+    pub fn new(ticket_id: Uuid, author: &str, request: NewComment) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            ticket_id,
+            author: author.to_string(),
+            role: request.role,
+            body: request.body,
+            internal: request.internal,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// 格式化沟通记录，每条评论一行，按给定顺序排列
+///
+/// `include_internal` 为false时跳过内部备注，用于可能展示给提单人或发往外部服务商的场景
+pub fn format_conversation(comments: &[TicketComment], include_internal: bool) -> String {
+    comments.iter()
+        .filter(|comment| include_internal || !comment.internal)
+        .map(|comment| format!("{}: {}", comment.role.label(), comment.body.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -3,6 +3,7 @@
 //! 定义系统中的通用数据结构
 
 use rag_deps::*;
+use super::ticket::Ticket;
 
 /// 微调数据记录
 /// 
@@ -20,7 +21,7 @@ pub struct FinetuneData {
 }
 
 /// 数据来源枚举
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataSource {
     UserFeedback,    // 用户反馈
     ExpertLabeling,  // 专家标注
@@ -56,6 +57,22 @@ pub struct QueryFilter {
     pub keywords: Option<String>,
 }
 
+impl QueryFilter {
+    /// 工单是否满足过滤条件，与数据库查询的语义一致
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        let text = format!("{}\n{}", ticket.title, ticket.description).to_lowercase();
+        self.category.as_ref().is_none_or(|c| &ticket.category == c)
+            && self.status.as_ref().is_none_or(|s| ticket.status.as_str().eq_ignore_ascii_case(s))
+            && self.priority_min.is_none_or(|min| ticket.priority >= min)
+            && self.priority_max.is_none_or(|max| ticket.priority <= max)
+            && self.date_from.is_none_or(|from| ticket.created_at >= from)
+            && self.date_to.is_none_or(|to| ticket.created_at < to)
+            && self.keywords.iter()
+                .flat_map(|k| k.split_whitespace())
+                .all(|keyword| text.contains(&keyword.to_lowercase()))
+    }
+}
+
 impl<T> PagedResult<T> {
     pub fn new(data: Vec<T>, total: u64, page: u32, page_size: u32) -> Self {
         let total_pages = (total as f64 / page_size as f64).ceil() as u32;
@@ -5,8 +5,18 @@
 pub mod ticket;
 pub mod solution;
 pub mod common;
+pub mod usage;
+pub mod outbox;
+pub mod audit;
+pub mod comment;
+pub mod attachment;
 
 // 重新导出主要模型
 pub use ticket::*;
 pub use solution::*;
-pub use common::*; 
\ No newline at end of file
+pub use common::*;
+pub use usage::*;
+pub use outbox::*;
+pub use audit::*;
+pub use comment::*;
+pub use attachment::*; 
\ No newline at end of file
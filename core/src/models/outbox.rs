@@ -0,0 +1,52 @@
+//! # 索引变更事件模型
+//!
+//! 工单变更时在同一事务中写入的待同步事件（transactional outbox），
+//! 由后台任务应用到向量库
+
+use rag_deps::*;
+
+/// 索引变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexEventKind {
+    Upsert, // 工单新建或更新
+    Delete, // 工单删除
+}
+
+/// 事件状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexEventStatus {
+    Pending, // 等待处理或重试
+    Failed,  // 重试次数用尽，等待对账修复
+}
+
+/// 索引变更事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEvent {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub kind: IndexEventKind,
+    pub status: IndexEventStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IndexEvent {
+    /// 创建立即待处理的事件
+    pub fn new(ticket_id: Uuid, kind: IndexEventKind) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            ticket_id,
+            kind,
+            status: IndexEventStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: now,
+            created_at: now,
+        }
+    }
+}
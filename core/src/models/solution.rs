@@ -20,6 +20,14 @@ pub struct TicketSolution {
     pub created_at: DateTime<Utc>,
     pub feedback_score: Option<i32>, // 1-5分评分
     pub feedback_comment: Option<String>,
+    #[serde(default)]
+    pub prompt_version: Option<String>, // 生成该方案的Prompt模板版本
+    #[serde(default)]
+    pub citations: Vec<Citation>,
+    #[serde(default)]
+    pub raw_confidence: Option<f32>, // 校准前的置信度，用于重新拟合校准模型
+    #[serde(default)]
+    pub guardrail: Option<GuardrailReport>, // 生成后安全检查结果
 }
 
 /// 处理结果
@@ -34,9 +42,118 @@ pub struct ProcessResult {
     pub suggested_solution: String,
     pub confidence: f32,
     pub reasoning: String,
+    pub raw_confidence: f32,
+    pub confidence_signals: ConfidenceSignals,
+    pub structured: Option<StructuredSolution>,
+    pub output_status: OutputStatus,
+    pub citations: Vec<Citation>,
+    pub invalid_citations: Vec<String>, // 模型引用了但不在上下文中的工单ID
+    pub context: ContextReport,
+    pub model: String, // 实际生成所用的模型
+    pub guardrail: GuardrailReport,
+    pub prompt_version: String,
     pub processing_time_ms: u64,
 }
 
+/// 结构化解决方案
+/// 
+/// 职责：
+/// - 约定模型输出的JSON结构
+/// - 记录处理步骤及引用的相似工单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StructuredSolution {
+    pub steps: Vec<String>,
+    pub cited_ticket_ids: Vec<String>, // 模型给出的原始引用，需按上下文核对
+    pub confidence: f32, // 0-1
+    pub reasoning: String,
+}
+
+/// Prompt上下文组装报告
+/// 
+/// 职责：
+/// - 记录token预算及实际使用量
+/// - 记录被截断和被丢弃的相似案例
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextReport {
+    pub budget_tokens: usize,
+    pub used_tokens: usize,
+    pub included: Vec<Uuid>,
+    pub truncated: Vec<Uuid>,
+    pub dropped: Vec<DroppedCase>,
+}
+
+/// 未放入Prompt的相似案例
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedCase {
+    pub ticket_id: Uuid,
+    pub estimated_tokens: usize,
+    pub reason: String,
+}
+
+/// 安全检查的处理方式，按严重程度递增
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailAction {
+    #[default]
+    Pass,   // 未命中任何规则
+    Flag,   // 保留原文，提示人工复核
+    Redact, // 隐藏命中的片段
+    Block,  // 整个方案不予展示
+}
+
+/// 安全检查命中的规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardrailFinding {
+    pub rule: String,
+    pub category: String, // destructive_command, data_leak, prompt_injection, custom, llm_judge
+    pub action: GuardrailAction,
+    pub excerpt: String,  // 命中的片段
+}
+
+/// 生成方案的安全检查报告
+/// 
+/// 职责：
+/// - 记录命中的规则及最终处理方式
+/// - 记录检查前后的置信度
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GuardrailReport {
+    pub action: GuardrailAction,
+    pub findings: Vec<GuardrailFinding>,
+    pub original_confidence: Option<f32>, // 命中规则被下调前的置信度
+}
+
+/// 置信度信号
+/// 
+/// 各信号取值0-1，无法获得时为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfidenceSignals {
+    pub top_rerank: Option<f32>,  // 最相关工单的重排序分数
+    pub agreement: Option<f32>,   // 相似工单已采纳方案之间的一致程度
+    pub self_rating: Option<f32>, // 模型自评
+    pub token_prob: Option<f32>,  // 平均token概率
+}
+
+/// 模型输出的解析结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStatus {
+    Parsed,     // 直接解析成功
+    Repaired,   // 修复格式后解析成功
+    Reprompted, // 重新请求后解析成功
+    Fallback,   // 无法解析，按原始文本处理
+}
+
+impl StructuredSolution {
+    /// 将处理步骤格式化为编号列表
+    pub fn steps_text(&self) -> String {
+        self.steps.iter()
+            .enumerate()
+            .map(|(i, step)| format!("{}. {}", i + 1, step))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
 /// 相似工单
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimilarTicket {
@@ -45,7 +162,40 @@ pub struct SimilarTicket {
     pub description: String,
     pub similarity_score: f32,
     pub rerank_score: f32,
+    pub scores: ScoreComponents,
+    pub solution_id: Option<Uuid>,
     pub solution: Option<String>,
+    #[serde(default)]
+    pub quarantined: bool, // 疑似包含提示注入，不放入LLM上下文
+}
+
+/// 方案引用
+/// 
+/// 职责：
+/// - 记录生成方案实际参考的历史工单
+/// - 记录随该工单提供给模型的历史方案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citation {
+    pub ticket_id: Uuid,
+    pub title: String,
+    pub solution_id: Option<Uuid>,
+    pub solution: Option<String>,
+    pub score: f32, // 该工单的融合分数
+}
+
+/// 排序分数分解
+/// 
+/// 职责：
+/// - 记录每一路排序对最终结果的原始分数
+/// - 解释相似案例的最终排名来源
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreComponents {
+    pub dense: Option<f32>,   // 向量相似度
+    pub keyword: Option<f32>, // 关键词/BM25
+    pub rerank: Option<f32>,  // 重排序模型
+    pub recency: Option<f32>, // 时效衰减系数
+    pub boost: Option<f32>,   // 时效衰减与方案质量的加权系数
+    pub fused: f32,           // 融合（含加权）后分数
 }
 
 /// 用户反馈
@@ -65,6 +215,7 @@ impl TicketSolution {
         solution: String,
         confidence: f32,
         reasoning: String,
+        prompt_version: Option<String>,
     ) -> Self {
         Self {
             id: Uuid::new_v4(),
@@ -76,6 +227,10 @@ impl TicketSolution {
             created_at: Utc::now(),
             feedback_score: None,
             feedback_comment: None,
+            prompt_version,
+            citations: Vec::new(),
+            raw_confidence: None,
+            guardrail: None,
         }
     }
     
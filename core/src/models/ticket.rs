@@ -3,6 +3,9 @@
 //! 定义工单相关的数据结构
 
 use rag_deps::*;
+use crate::errors::{AppError, AppResult};
+use super::comment::{TicketComment, format_conversation};
+use super::attachment::{Attachment, format_error_lines};
 
 /// 工单实体
 /// 
@@ -21,6 +24,10 @@ pub struct Ticket {
     pub updated_at: DateTime<Utc>,
     pub embedding: Option<Vec<f32>>,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub resolution_note: Option<String>, // 解决说明，没有已采纳方案时解决工单必须填写
+    #[serde(default)]
+    pub version: i64, // 乐观锁版本号，新建为1，每次更新加一
 }
 
 /// 新建工单请求
@@ -34,7 +41,13 @@ pub struct NewTicket {
 }
 
 /// 工单状态枚举
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 
+/// 允许的流转：
+/// - New → Processing / Resolved / Closed
+/// - Processing → Resolved / Closed
+/// - Resolved → Processing（重新打开）/ Closed
+/// - Closed → Processing（重新打开）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TicketStatus {
     New,        // 新建
     Processing, // 处理中
@@ -42,6 +55,45 @@ pub enum TicketStatus {
     Closed,     // 已关闭
 }
 
+impl TicketStatus {
+    /// 状态名，与序列化结果一致
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::New => "New",
+            Self::Processing => "Processing",
+            Self::Resolved => "Resolved",
+            Self::Closed => "Closed",
+        }
+    }
+    
+    /// 当前状态可以流转到的状态
+    pub fn allowed_transitions(&self) -> &'static [TicketStatus] {
+        match self {
+            Self::New => &[Self::Processing, Self::Resolved, Self::Closed],
+            Self::Processing => &[Self::Resolved, Self::Closed],
+            Self::Resolved => &[Self::Processing, Self::Closed],
+            Self::Closed => &[Self::Processing],
+        }
+    }
+    
+    /// 是否允许流转到 `to`，状态不变时视为允许
+    pub fn can_transition_to(&self, to: &TicketStatus) -> bool {
+        self == to || self.allowed_transitions().contains(to)
+    }
+    
+    /// 检查流转是否合法，不合法时返回 `status` 字段的 `Validation`
+    pub fn check_transition(&self, to: &TicketStatus) -> AppResult<()> {
+        if self.can_transition_to(to) {
+            Ok(())
+        } else {
+            Err(AppError::validation(
+                "status",
+                format!("不允许的状态流转: {} -> {}", self.as_str(), to.as_str()),
+            ))
+        }
+    }
+}
+
 impl Ticket {
     /// 创建新工单
     pub fn new(request: NewTicket) -> Self {
@@ -57,6 +109,8 @@ impl Ticket {
             updated_at: now,
             embedding: None,
             tags: request.tags,
+            resolution_note: None,
+            version: 1,
         }
     }
     
@@ -65,10 +119,37 @@ impl Ticket {
         format!("{} {} {}", self.title, self.description, self.tags.join(" "))
     }
     
-    /// 更新工单状态
-    pub fn update_status(&mut self, status: TicketStatus) {
+    /// 工单完整文本附加公开的沟通记录和附件错误行（用于向量化）
+    ///
+    /// 内部备注不发往嵌入服务商，也不参与检索
+    pub fn get_full_text_with(&self, comments: &[TicketComment], attachments: &[Attachment]) -> String {
+        [self.get_full_text(), format_conversation(comments, false), format_error_lines(attachments)]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    
+    /// 描述后附加公开的沟通记录和附件错误行，用于检索查询和生成方案的Prompt
+    pub fn with_details(&self, comments: &[TicketComment], attachments: &[Attachment]) -> Ticket {
+        let mut description = self.description.clone();
+        let conversation = format_conversation(comments, false);
+        if !conversation.is_empty() {
+            description = format!("{}\n\n沟通记录:\n{}", description, conversation);
+        }
+        let error_lines = format_error_lines(attachments);
+        if !error_lines.is_empty() {
+            description = format!("{}\n\n附件中的错误信息:\n{}", description, error_lines);
+        }
+        Ticket { description, ..self.clone() }
+    }
+    
+    /// 更新工单状态，按状态流转表检查
+    pub fn update_status(&mut self, status: TicketStatus) -> AppResult<()> {
+        self.status.check_transition(&status)?;
         self.status = status;
         self.updated_at = Utc::now();
+        Ok(())
     }
     
     /// 设置向量化结果
@@ -0,0 +1,128 @@
+//! # 用量统计模型
+//!
+//! 记录每次模型调用的token、耗时与费用，并按工单、分类、日期等维度汇总
+
+use rag_deps::*;
+use std::collections::BTreeMap;
+
+/// 调用类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    Embedding,
+    Rerank,
+    Llm,
+}
+
+/// 单次模型调用的用量记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub id: Uuid,
+    pub ticket_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub kind: UsageKind,
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+    pub estimated: bool, // 服务未返回用量，token数为估算值
+    pub latency_ms: u64,
+    pub cost: f64,
+    pub success: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 用量查询条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub ticket_id: Option<Uuid>,
+    pub category: Option<String>,
+    pub kind: Option<UsageKind>,
+    pub model: Option<String>,
+}
+
+impl UsageQuery {
+    /// 记录是否满足查询条件
+    pub fn matches(&self, record: &UsageRecord) -> bool {
+        self.from.is_none_or(|from| record.created_at >= from)
+            && self.to.is_none_or(|to| record.created_at < to)
+            && self.ticket_id.is_none_or(|id| record.ticket_id == Some(id))
+            && self.category.as_ref().is_none_or(|c| record.category.as_ref() == Some(c))
+            && self.kind.is_none_or(|kind| record.kind == kind)
+            && self.model.as_ref().is_none_or(|m| &record.model == m)
+    }
+}
+
+/// 用量汇总
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageSummary {
+    pub calls: u64,
+    pub failed_calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+    pub avg_latency_ms: f64,
+}
+
+impl UsageSummary {
+    /// 累加一条记录
+    pub fn add(&mut self, record: &UsageRecord) {
+        let total_latency = self.avg_latency_ms * self.calls as f64 + record.latency_ms as f64;
+        self.calls += 1;
+        if !record.success {
+            self.failed_calls += 1;
+        }
+        self.prompt_tokens += record.prompt_tokens as u64;
+        self.completion_tokens += record.completion_tokens as u64;
+        self.total_tokens += record.total_tokens as u64;
+        self.cost += record.cost;
+        self.avg_latency_ms = total_latency / self.calls as f64;
+    }
+}
+
+/// 用量报表
+///
+/// 职责：
+/// - 汇总总用量
+/// - 按调用类型、模型、工单、分类和日期（UTC）分组
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub total: UsageSummary,
+    pub by_kind: BTreeMap<String, UsageSummary>,
+    pub by_model: BTreeMap<String, UsageSummary>,
+    pub by_ticket: BTreeMap<Uuid, UsageSummary>,
+    pub by_category: BTreeMap<String, UsageSummary>,
+    pub by_day: BTreeMap<String, UsageSummary>,
+}
+
+impl UsageReport {
+    pub fn from_records<'a>(records: impl IntoIterator<Item = &'a UsageRecord>) -> Self {
+        let mut report = Self::default();
+        for record in records {
+            report.total.add(record);
+            let kind = serde_json::to_value(record.kind)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+            report.by_kind.entry(kind).or_default().add(record);
+            report.by_model.entry(record.model.clone()).or_default().add(record);
+            if let Some(ticket_id) = record.ticket_id {
+                report.by_ticket.entry(ticket_id).or_default().add(record);
+            }
+            if let Some(category) = &record.category {
+                report.by_category.entry(category.clone()).or_default().add(record);
+            }
+            report.by_day.entry(usage_day(record.created_at)).or_default().add(record);
+        }
+        report
+    }
+}
+
+/// 用量统计日期（UTC），格式 `YYYY-MM-DD`
+pub fn usage_day(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d").to_string()
+}
@@ -0,0 +1,38 @@
+//! # 附件存储与扫描抽象接口
+//!
+//! 定义附件内容的存储接口和病毒扫描钩子，具体后端（本地目录、S3兼容对象存储、clamd）由配置选择
+
+use rag_deps::*;
+use crate::models::ScanVerdict;
+
+/// 附件内容存储
+///
+/// 职责：
+/// - 按对象键保存、读取和删除附件内容
+/// - 同一键重复写入时覆盖
+#[async_trait]
+pub trait AttachmentStorage: Send + Sync {
+    /// 保存附件内容
+    async fn put(&self, key: &str, data: &[u8], content_type: &str) -> Result<()>;
+
+    /// 读取附件内容，不存在时返回 `NotFound`
+    async fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// 删除附件内容，不存在时忽略
+    async fn delete(&self, key: &str) -> Result<()>;
+
+    /// 后端名称，如 local、s3
+    fn backend(&self) -> &'static str;
+}
+
+/// 病毒扫描钩子
+///
+/// 附件写入存储前调用，扫描失败时拒绝上传
+#[async_trait]
+pub trait VirusScanner: Send + Sync {
+    /// 扫描附件内容
+    async fn scan(&self, filename: &str, data: &[u8]) -> Result<ScanVerdict>;
+
+    /// 扫描器名称，用于日志
+    fn name(&self) -> &'static str;
+}
@@ -6,24 +6,85 @@ use rag_deps::*;
 use super::embedding::ModelInfo;
 use super::reranking::RerankResult;
 use crate::models::Ticket;
+use crate::prompt::{PromptTemplate, RenderedPrompt, TokenEstimator, DEFAULT_OUTPUT_LANGUAGE};
+use futures::stream::{self, Stream};
+use std::pin::Pin;
 
 /// LLM推理结果
 #[derive(Debug, Clone)]
 pub struct LLMResponse {
     pub content: String,
+    pub model: String, // 实际生成所用的模型
     pub confidence: f32,
     pub reasoning: String,
     pub token_usage: Option<TokenUsage>,
+    pub avg_logprob: Option<f32>, // 平均token对数概率，服务不支持时为空
 }
 
 /// Token使用统计
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
 }
 
+/// 流式生成事件
+#[derive(Debug, Clone)]
+pub enum LLMStreamEvent {
+    /// 增量文本
+    Delta(String),
+    /// 生成结束，附带置信度、推理说明和最终token统计
+    Done {
+        model: String,
+        confidence: f32,
+        reasoning: String,
+        token_usage: Option<TokenUsage>,
+    },
+}
+
+/// 流式生成结果
+pub type LLMStream = Pin<Box<dyn Stream<Item = Result<LLMStreamEvent>> + Send>>;
+
+/// 单次生成参数
+/// 
+/// 未设置的字段使用服务配置中的默认值，
+/// 便于摘要、分类、方案生成等不同调用方使用各自的参数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GenerationOptions {
+    pub max_tokens: Option<usize>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub stop: Vec<String>,
+    pub seed: Option<u64>,
+    pub response_format: Option<ResponseFormat>,
+    #[serde(default)]
+    pub logprobs: bool, // 请求返回token对数概率（服务支持时）
+    #[serde(default)]
+    pub model: Option<String>, // 指定模型后端，仅在启用模型路由时生效
+    #[serde(default)]
+    pub route: Option<RouteHints>, // 模型路由依据
+}
+
+/// 模型路由依据
+/// 
+/// 由调用方根据工单和检索结果填写，单一模型时忽略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteHints {
+    pub ticket_id: Option<Uuid>, // A/B分流按工单ID分桶，同一工单始终落在同一组
+    pub category: Option<String>,
+    pub priority: Option<i32>,
+    pub ticket_chars: usize, // 工单标题与描述的字符数
+    pub retrieval_confidence: Option<f32>,
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResponseFormat {
+    Text,
+    JsonObject,
+}
+
 /// LLM服务trait
 /// 
 /// 职责：
@@ -32,25 +93,85 @@ pub struct TokenUsage {
 /// - 管理token使用
 #[async_trait]
 pub trait LLMService: Send + Sync {
+    /// 根据渲染后的Prompt生成
+    async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse>;
+    
+    /// 根据渲染后的Prompt流式生成
+    /// 
+    /// 默认实现等待完整结果后一次性输出，支持流式接口的服务应覆盖该方法
+    async fn generate_stream(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        let response = self.generate(prompt, options).await?;
+        let events = vec![
+            Ok(LLMStreamEvent::Delta(response.content)),
+            Ok(LLMStreamEvent::Done {
+                model: response.model,
+                confidence: response.confidence,
+                reasoning: response.reasoning,
+                token_usage: response.token_usage,
+            }),
+        ];
+        Ok(Box::pin(stream::iter(events)))
+    }
+    
     /// 生成工单处理建议
+    /// 
+    /// 默认使用内置模板渲染Prompt，需要指定模板版本时调用 [`LLMService::generate`]
     async fn generate_solution(
         &self, 
         ticket: &Ticket, 
-        similar_cases: &[RerankResult]
-    ) -> Result<LLMResponse>;
+        similar_cases: &[RerankResult],
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let prompt = PromptTemplate::builtin_solution()
+            .render_solution(ticket, similar_cases, &[], DEFAULT_OUTPUT_LANGUAGE);
+        let mut response = self.generate(&prompt, options).await?;
+        response.reasoning = solution_reasoning(similar_cases.len());
+        Ok(response)
+    }
+    
+    /// 流式生成工单处理建议
+    async fn generate_solution_stream(
+        &self,
+        ticket: &Ticket,
+        similar_cases: &[RerankResult],
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        let prompt = PromptTemplate::builtin_solution()
+            .render_solution(ticket, similar_cases, &[], DEFAULT_OUTPUT_LANGUAGE);
+        self.generate_stream(&prompt, options).await
+    }
     
     /// 批量生成建议
     async fn generate_solutions_batch(
         &self,
-        requests: &[(Ticket, Vec<RerankResult>)]
+        requests: &[(Ticket, Vec<RerankResult>)],
+        options: &GenerationOptions,
     ) -> Result<Vec<LLMResponse>>;
     
     /// 自定义prompt推理
-    async fn chat(&self, prompt: &str) -> Result<LLMResponse>;
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse>;
     
-    /// 获取模型信息
+    /// 获取模型信息，`max_tokens` 为模型上下文窗口大小
     fn model_info(&self) -> ModelInfo;
     
+    /// 该模型的token估算器
+    fn token_estimator(&self) -> TokenEstimator {
+        TokenEstimator::default()
+    }
+    
     /// 健康检查
     async fn health_check(&self) -> Result<bool>;
-} 
\ No newline at end of file
+}
+
+/// 解决方案生成的推理说明
+pub fn solution_reasoning(case_count: usize) -> String {
+    format!("参考{}个历史相似案例生成", case_count)
+}
@@ -6,9 +6,16 @@ pub mod embedding;
 pub mod reranking;
 pub mod vector_db;
 pub mod llm;
+pub mod repository;
+pub mod attachment;
 
 // 重新导出主要trait
 pub use embedding::EmbeddingService;
 pub use reranking::RerankService;
 pub use vector_db::VectorDatabase;
-pub use llm::LLMService; 
\ No newline at end of file
+pub use llm::LLMService;
+pub use attachment::{AttachmentStorage, VirusScanner};
+pub use repository::{
+    Repository, TicketRepository, CommentRepository, SolutionRepository, FeedbackRepository, FinetuneRepository, UsageRepository,
+    OutboxRepository, AuditRepository, AttachmentRepository,
+}; 
\ No newline at end of file
@@ -0,0 +1,242 @@
+//! # 数据存储抽象接口
+//!
+//! 定义工单、评论、附件、解决方案、反馈、微调数据、用量记录和审计日志的存储接口，
+//! 业务层只依赖这些trait，具体后端（PostgreSQL、SQLite、内存）由配置选择
+
+use rag_deps::*;
+use crate::models::*;
+use std::collections::HashMap;
+
+/// 分页查询单页最大记录数
+pub const MAX_PAGE_SIZE: u32 = 200;
+
+/// 工单存储
+///
+/// 新建、更新和删除工单时，在同一事务中写入对应的 [`IndexEvent`]；
+/// 写入类方法的 `audit` 为本次变更的审计记录，与变更一同提交或回滚
+#[async_trait]
+pub trait TicketRepository: Send + Sync {
+    /// 插入新工单
+    async fn insert_ticket(&self, ticket: &Ticket, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 查询工单
+    async fn get_ticket(&self, id: Uuid) -> Result<Option<Ticket>>;
+
+    /// 更新工单，仅当存储中的版本等于 `ticket.version` 时写入，写入后版本加一
+    ///
+    /// 工单不存在时返回 `NotFound`，版本不一致时返回 `VersionConflict`
+    async fn update_ticket(&self, ticket: &Ticket, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 删除工单及其解决方案，工单不存在时返回 `NotFound`
+    async fn delete_ticket(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 回写工单向量，不改变 `updated_at`，也不产生索引变更事件；工单不存在时忽略
+    async fn set_ticket_embedding(&self, id: Uuid, embedding: Option<&[f32]>) -> Result<()>;
+
+    /// 按ID升序分页遍历工单，`after` 为上一页最后一个ID
+    async fn scroll_tickets(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Ticket>>;
+
+    /// 查询工单列表，按创建时间倒序
+    ///
+    /// `keywords` 按空白拆分，每个词都需出现在标题或描述中（不区分大小写）；
+    /// `status` 不区分大小写
+    async fn list_tickets(
+        &self,
+        filter: &QueryFilter,
+        pagination: &Pagination
+    ) -> Result<PagedResult<Ticket>>;
+}
+
+/// 工单评论存储
+///
+/// 评论参与工单的向量化文本，新增、修改和删除评论时在同一事务中写入工单的 [`IndexEvent`]
+/// 和本次变更的审计记录 `audit`
+#[async_trait]
+pub trait CommentRepository: Send + Sync {
+    /// 插入评论，工单不存在时返回 `NotFound`
+    async fn insert_comment(&self, comment: &TicketComment, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 查询评论
+    async fn get_comment(&self, id: Uuid) -> Result<Option<TicketComment>>;
+
+    /// 更新评论正文和可见范围，评论不存在时返回 `NotFound`
+    async fn update_comment(&self, comment: &TicketComment, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 删除评论，评论不存在时返回 `NotFound`
+    async fn delete_comment(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 查询工单的评论，按创建时间正序
+    async fn list_comments(&self, ticket_id: Uuid) -> Result<Vec<TicketComment>>;
+}
+
+/// 工单附件元数据存储，附件内容由 [`AttachmentStorage`](crate::traits::AttachmentStorage) 保存
+///
+/// 附件中的错误行参与工单的向量化文本，新增和删除附件时在同一事务中写入工单的 [`IndexEvent`]
+/// 和本次变更的审计记录 `audit`
+#[async_trait]
+pub trait AttachmentRepository: Send + Sync {
+    /// 插入附件，工单不存在时返回 `NotFound`
+    async fn insert_attachment(&self, attachment: &Attachment, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 查询附件
+    async fn get_attachment(&self, id: Uuid) -> Result<Option<Attachment>>;
+
+    /// 删除附件，附件不存在时返回 `NotFound`
+    async fn delete_attachment(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()>;
+
+    /// 查询工单的附件，按上传时间正序
+    async fn list_attachments(&self, ticket_id: Uuid) -> Result<Vec<Attachment>>;
+}
+
+/// 解决方案存储
+#[async_trait]
+pub trait SolutionRepository: Send + Sync {
+    /// 插入解决方案
+    async fn insert_solution(&self, solution: &TicketSolution) -> Result<()>;
+
+    /// 查询解决方案
+    async fn get_solution(&self, id: Uuid) -> Result<Option<TicketSolution>>;
+
+    /// 查询工单的解决方案，按创建时间倒序
+    async fn get_solutions_by_ticket(&self, ticket_id: Uuid) -> Result<Vec<TicketSolution>>;
+
+    /// 按工单ID批量查询解决方案，没有方案的工单对应空列表
+    ///
+    /// 默认实现逐个查询，支持批量查询的后端应覆盖该方法
+    async fn solutions_for_tickets(
+        &self,
+        ticket_ids: &[Uuid]
+    ) -> Result<HashMap<Uuid, Vec<TicketSolution>>> {
+        let mut solutions = HashMap::new();
+        for id in ticket_ids {
+            solutions.insert(*id, self.get_solutions_by_ticket(*id).await?);
+        }
+        Ok(solutions)
+    }
+
+    /// 查询最近有反馈的解决方案（已采纳或已评分），按创建时间倒序
+    async fn list_reviewed_solutions(&self, limit: usize) -> Result<Vec<TicketSolution>>;
+}
+
+/// 反馈存储
+#[async_trait]
+pub trait FeedbackRepository: Send + Sync {
+    /// 更新解决方案的采纳状态和评分，同时保留一条反馈记录；方案不存在时返回 `NotFound`
+    ///
+    /// `audit` 在同一事务中写入
+    async fn update_solution_feedback(
+        &self,
+        solution_id: Uuid,
+        feedback: &Feedback,
+        audit: &[AuditEntry],
+    ) -> Result<()>;
+
+    /// 查询解决方案的反馈记录，按时间排序
+    async fn list_feedback(&self, solution_id: Uuid) -> Result<Vec<Feedback>>;
+}
+
+/// 微调数据存储
+#[async_trait]
+pub trait FinetuneRepository: Send + Sync {
+    /// 插入微调数据
+    async fn insert_finetune_data(&self, data: &FinetuneData) -> Result<()>;
+
+    /// 导出微调数据，按创建时间排序
+    async fn export_finetune_data(
+        &self,
+        filter: &FinetuneDataFilter
+    ) -> Result<Vec<FinetuneData>>;
+}
+
+/// 模型调用用量存储
+#[async_trait]
+pub trait UsageRepository: Send + Sync {
+    /// 插入用量记录
+    async fn insert_usage_record(&self, record: &UsageRecord) -> Result<()>;
+
+    /// 按条件查询用量记录，按时间排序
+    async fn list_usage_records(&self, query: &UsageQuery) -> Result<Vec<UsageRecord>>;
+}
+
+/// 索引变更事件存储
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    /// 写入事件，用于对账修复等不伴随工单变更的场景
+    async fn enqueue_index_event(&self, event: &IndexEvent) -> Result<()>;
+
+    /// 查询到期的待处理事件，按创建时间排序
+    async fn due_index_events(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<IndexEvent>>;
+
+    /// 保存事件的重试状态
+    async fn update_index_event(&self, event: &IndexEvent) -> Result<()>;
+
+    /// 事件处理完成后删除
+    async fn complete_index_event(&self, id: Uuid) -> Result<()>;
+}
+
+/// 审计日志存储，只追加不修改
+///
+/// 工单删除后仍保留其审计记录；伴随数据变更的审计记录由对应的写入方法在同一事务中写入
+#[async_trait]
+pub trait AuditRepository: Send + Sync {
+    /// 追加不伴随数据变更的审计记录
+    async fn append_audit_entries(&self, entries: &[AuditEntry]) -> Result<()>;
+
+    /// 查询工单的审计记录，按时间正序
+    async fn list_audit_entries(&self, ticket_id: Uuid) -> Result<Vec<AuditEntry>>;
+}
+
+/// 完整的数据存储
+///
+/// 职责：
+/// - 组合各类存储接口，由同一后端实现
+/// - 提供跨表的统计信息
+#[async_trait]
+pub trait Repository:
+    TicketRepository
+    + CommentRepository
+    + AttachmentRepository
+    + SolutionRepository
+    + FeedbackRepository
+    + FinetuneRepository
+    + UsageRepository
+    + OutboxRepository
+    + AuditRepository
+{
+    /// 获取统计信息
+    async fn get_statistics(&self) -> Result<DatabaseStatistics>;
+
+    /// 后端名称，如 postgres、sqlite、memory
+    fn backend(&self) -> &'static str;
+}
+
+/// 微调数据过滤器
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FinetuneDataFilter {
+    pub quality_score_min: Option<f32>,
+    pub data_source: Option<DataSource>,
+    pub date_from: Option<DateTime<Utc>>,
+    pub date_to: Option<DateTime<Utc>>,
+}
+
+impl FinetuneDataFilter {
+    /// 记录是否满足过滤条件
+    pub fn matches(&self, data: &FinetuneData) -> bool {
+        self.quality_score_min.is_none_or(|min| data.quality_score >= min)
+            && self.data_source.as_ref().is_none_or(|source| source == &data.data_source)
+            && self.date_from.is_none_or(|from| data.created_at >= from)
+            && self.date_to.is_none_or(|to| data.created_at < to)
+    }
+}
+
+/// 数据库统计信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStatistics {
+    pub total_tickets: u64,
+    pub total_solutions: u64,
+    pub accepted_solutions: u64,
+    pub average_confidence: f32,
+    pub finetune_data_count: u64,
+    pub pending_index_events: u64,
+    pub failed_index_events: u64,
+}
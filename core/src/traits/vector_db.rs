@@ -3,6 +3,7 @@
 //! 定义向量数据库的统一接口，支持多种向量数据库实现
 
 use rag_deps::*;
+use crate::models::Ticket;
 
 /// 向量记录
 #[derive(Debug, Clone)]
@@ -21,13 +22,52 @@ pub struct VectorMetadata {
     pub priority: i32,
     pub created_at: DateTime<Utc>,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub quarantined: bool, // 疑似包含提示注入，不放入LLM上下文
+    #[serde(default)]
+    pub content_hash: Option<String>, // 向量化文本的指纹，用于判断向量是否过期
+}
+
+impl VectorMetadata {
+    /// 由工单及其向量化文本生成检索元数据
+    ///
+    /// 传入 [`Ticket::with_details`] 的结果，使描述带上公开的沟通记录和附件错误行
+    pub fn from_ticket(ticket: &Ticket, indexed_text: &str) -> Self {
+        Self {
+            title: ticket.title.clone(),
+            description: ticket.description.clone(),
+            category: ticket.category.clone(),
+            priority: ticket.priority,
+            created_at: ticket.created_at,
+            tags: ticket.tags.clone(),
+            quarantined: false,
+            content_hash: Some(content_hash(indexed_text)),
+        }
+    }
+
+    /// 参与关键词检索的文本：标题、描述和标签
+    pub fn keyword_text(&self) -> String {
+        format!("{} {} {}", self.title, self.description, self.tags.join(" "))
+    }
+}
+
+/// 文本指纹（64位FNV-1a，十六进制），跨进程和版本稳定
+pub fn content_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
 }
 
 /// 搜索结果
+/// 
+/// `score` 始终是向量相似度；混合检索时 `keyword_score` 记录关键词得分，
+/// 结果顺序反映融合后的排名
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub id: Uuid,
     pub score: f32,
+    pub keyword_score: Option<f32>,
     pub metadata: VectorMetadata,
     pub vector: Option<Vec<f32>>,
 }
@@ -41,6 +81,16 @@ pub struct VectorFilter {
     pub tags: Option<Vec<String>>,
 }
 
+impl VectorFilter {
+    /// 元数据是否满足过滤条件；范围两端都包含，标签需全部出现
+    pub fn matches(&self, metadata: &VectorMetadata) -> bool {
+        self.category.as_ref().is_none_or(|category| *category == metadata.category)
+            && self.priority_range.is_none_or(|(min, max)| (min..=max).contains(&metadata.priority))
+            && self.date_range.is_none_or(|(from, to)| (from..=to).contains(&metadata.created_at))
+            && self.tags.as_ref().is_none_or(|tags| tags.iter().all(|tag| metadata.tags.contains(tag)))
+    }
+}
+
 /// 数据库统计信息
 #[derive(Debug)]
 pub struct DatabaseStats {
@@ -70,7 +120,7 @@ pub struct DatabaseInfo {
 /// - 提供批量操作能力
 #[async_trait]
 pub trait VectorDatabase: Send + Sync {
-    /// 插入单个向量
+    /// 插入单个向量，ID已存在时覆盖原记录
     async fn insert(&self, id: Uuid, vector: &[f32], metadata: VectorMetadata) -> Result<()>;
     
     /// 批量插入向量
@@ -85,15 +135,24 @@ pub trait VectorDatabase: Send + Sync {
     ) -> Result<Vec<SearchResult>>;
     
     /// 混合搜索（向量 + 关键词）
+    /// 
+    /// 默认实现只做向量检索，丢弃关键词并记录警告。具备原生混合检索能力的后端应覆盖该方法；
+    /// 不具备的后端由业务层套上内存倒排索引（`KeywordIndexedVectorDatabase`）补足关键词召回，
+    /// 走到默认实现说明装配遗漏了该索引
     async fn hybrid_search(
         &self, 
         query_vector: &[f32], 
         keywords: &[String], 
         limit: usize, 
         filter: Option<VectorFilter>
-    ) -> Result<Vec<SearchResult>>;
+    ) -> Result<Vec<SearchResult>> {
+        if !keywords.is_empty() {
+            warn!("{} 不支持混合检索，已丢弃关键词 {:?}，只做向量检索", self.database_info().name, keywords);
+        }
+        self.search(query_vector, limit, filter).await
+    }
     
-    /// 删除向量
+    /// 删除向量，ID不存在时忽略
     async fn delete(&self, id: Uuid) -> Result<()>;
     
     /// 更新向量
@@ -102,6 +161,9 @@ pub trait VectorDatabase: Send + Sync {
     /// 获取向量
     async fn get(&self, id: Uuid) -> Result<Option<VectorRecord>>;
     
+    /// 按ID升序分页列出向量ID，`after` 为上一页最后一个ID
+    async fn scroll(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Uuid>>;
+    
     /// 统计信息
     async fn stats(&self) -> Result<DatabaseStats>;
     
@@ -110,4 +172,4 @@ pub trait VectorDatabase: Send + Sync {
     
     /// 获取数据库信息
     fn database_info(&self) -> DatabaseInfo;
-} 
\ No newline at end of file
+}
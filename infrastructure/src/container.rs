@@ -3,9 +3,15 @@
 //! 管理所有服务实例的依赖注入容器
 
 use rag_deps::*;
-use rag_core::traits::*;
-use rag_business::processors::TicketProcessor;
-use rag_services::database::PostgresDatabase;
+use rag_core::{traits::*, config::RetrievalConfig};
+use rag_business::{
+    processors::{TicketProcessor, GenerationComponents},
+    prompts::PromptManager,
+    confidence::ConfidenceCalibrator,
+    usage::UsageTracker,
+    indexing::TicketIndexer,
+    attachments::AttachmentService,
+};
 use std::sync::Arc;
 
 /// 服务容器
@@ -20,7 +26,12 @@ pub struct ServiceContainer {
     pub rerank_service: Arc<dyn RerankService + Send + Sync>,
     pub vector_db: Arc<dyn VectorDatabase + Send + Sync>,
     pub llm_service: Arc<dyn LLMService + Send + Sync>,
-    pub database: Arc<PostgresDatabase>,
+    pub database: Arc<dyn Repository>,
+    pub prompt_manager: Arc<PromptManager>,
+    pub confidence_calibrator: Arc<ConfidenceCalibrator>,
+    pub usage_tracker: Arc<UsageTracker>,
+    pub ticket_indexer: Arc<TicketIndexer>,
+    pub attachments: Arc<AttachmentService>,
     pub ticket_processor: Arc<TicketProcessor>,
 }
 
@@ -31,7 +42,9 @@ impl ServiceContainer {
         rerank_service: Arc<dyn RerankService + Send + Sync>,
         vector_db: Arc<dyn VectorDatabase + Send + Sync>,
         llm_service: Arc<dyn LLMService + Send + Sync>,
-        database: Arc<PostgresDatabase>,
+        database: Arc<dyn Repository>,
+        generation: GenerationComponents,
+        retrieval: RetrievalConfig,
     ) -> Self {
         // 创建工单处理器，注入所需依赖
         let ticket_processor = Arc::new(TicketProcessor::new(
@@ -39,6 +52,9 @@ impl ServiceContainer {
             rerank_service.clone(),
             vector_db.clone(),
             llm_service.clone(),
+            database.clone(),
+            generation.clone(),
+            retrieval,
         ));
         
         Self {
@@ -47,6 +63,11 @@ impl ServiceContainer {
             vector_db,
             llm_service,
             database,
+            prompt_manager: generation.prompts,
+            confidence_calibrator: generation.confidence,
+            usage_tracker: generation.usage,
+            ticket_indexer: generation.indexer,
+            attachments: generation.attachments,
             ticket_processor,
         }
     }
@@ -8,10 +8,34 @@ use rag_services::{
     embedding::{QwenEmbeddingService},
     reranking::{QwenRerankService},
     vector_db::{SqliteVectorDB, QdrantVectorDB, PostgresVectorDB},
-    llm::{QwenLLMService},
+    llm::{QwenLLMService, OpenAILLMService},
+    llm_router::RoutingLLMService,
     database::PostgresDatabase,
+    sqlite_database::SqliteDatabase,
+    memory_database::InMemoryDatabase,
+    attachment_storage::{LocalFileStorage, S3Storage},
+    virus_scan::{NoopVirusScanner, ClamdScanner},
+};
+use rag_business::{
+    fusion::FusionStrategy,
+    confidence::ConfidenceCalibrator,
+    context::ContextBuilder,
+    guardrails::Guardrails,
+    indexing::TicketIndexer,
+    lifecycle::TicketLifecycle,
+    attachments::AttachmentService,
+    injection::{InjectionDetector, ScreeningVectorDatabase},
+    keyword_index::KeywordIndexedVectorDatabase,
+    processors::GenerationComponents,
+    prompts::{PromptManager, PromptStore, FilePromptStore, InMemoryPromptStore},
+    usage::{
+        UsageTracker, InMemoryUsageStore,
+        MeteredEmbeddingService, MeteredRerankService, MeteredLLMService,
+    },
+    redaction::{Redactor, RedactingEmbeddingService, RedactingRerankService, RedactingLLMService},
 };
 use crate::container::ServiceContainer;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// 服务工厂
@@ -27,12 +51,51 @@ impl ServiceFactory {
     pub async fn create_service_container(config: &AppConfig) -> Result<ServiceContainer> {
         info!("开始创建服务容器...");
         
-        // 创建各个服务
-        let embedding_service = Self::create_embedding_service(&config.embedding).await?;
-        let rerank_service = Self::create_rerank_service(&config.reranking).await?;
-        let vector_db = Self::create_vector_database(&config.vector_db).await?;
-        let llm_service = Self::create_llm_service(&config.llm).await?;
+        // 提前校验检索融合配置
+        FusionStrategy::from_config(&config.retrieval)?;
+        
+        // 创建各个服务，发往外部服务商的文本先脱敏，模型调用经过用量记录
         let database = Self::create_database(&config.database).await?;
+        let usage = Self::create_usage_tracker(config, &database)?;
+        let redactor = Self::create_redactor(&config.privacy)?;
+        let mut embedding_service = Self::create_embedding_service(&config.embedding).await?;
+        let mut rerank_service = Self::create_rerank_service(&config.reranking).await?;
+        if let Some(redactor) = redactor.as_ref().filter(|r| r.applies_to(&config.embedding.provider)) {
+            embedding_service = Arc::new(RedactingEmbeddingService::new(embedding_service, redactor.clone()));
+        }
+        if let Some(redactor) = redactor.as_ref().filter(|r| r.applies_to(&config.reranking.provider)) {
+            rerank_service = Arc::new(RedactingRerankService::new(rerank_service, redactor.clone()));
+        }
+        if usage.enabled() {
+            embedding_service = Arc::new(MeteredEmbeddingService::new(embedding_service, usage.clone()));
+            rerank_service = Arc::new(MeteredRerankService::new(rerank_service, usage.clone()));
+        }
+        let mut vector_db = Self::create_vector_database(&config.vector_db, &database).await?;
+        if config.injection.enabled {
+            let detector = Arc::new(InjectionDetector::new(&config.injection)?);
+            vector_db = Arc::new(ScreeningVectorDatabase::new(vector_db, detector));
+        }
+        let llm_service = Self::create_routed_llm_service(&config.llm, &config.routing, &usage, redactor.as_ref()).await?;
+        let generation = GenerationComponents {
+            prompts: Self::create_prompt_manager(&config.prompts),
+            confidence: Arc::new(ConfidenceCalibrator::new(config.confidence.clone())?),
+            context: ContextBuilder::new(config.context.clone(), config.llm.max_tokens),
+            usage,
+            guardrails: Self::create_guardrails(config, &llm_service)?,
+            indexer: Arc::new(TicketIndexer::new(
+                embedding_service.clone(),
+                vector_db.clone(),
+                database.clone(),
+                config.indexing.clone(),
+            )),
+            lifecycle: Arc::new(TicketLifecycle::from_config(&config.lifecycle, database.clone())),
+            attachments: Arc::new(AttachmentService::new(
+                Self::create_attachment_storage(&config.attachments)?,
+                Self::create_virus_scanner(&config.attachments)?,
+                database.clone(),
+                config.attachments.clone(),
+            )),
+        };
         
         // 创建服务容器
         let container = ServiceContainer::new(
@@ -41,12 +104,33 @@ impl ServiceFactory {
             vector_db,
             llm_service,
             database,
+            generation,
+            config.retrieval.clone(),
         );
         
+        container.ticket_indexer.spawn_outbox_worker();
+        container.ticket_indexer.spawn_reconciler();
+        container.confidence_calibrator.spawn_recalibrator(container.database.clone());
+        
         info!("服务容器创建完成");
         Ok(container)
     }
     
+    /// 创建Prompt模板管理器
+    pub fn create_prompt_manager(config: &PromptConfig) -> Arc<PromptManager> {
+        let store: Arc<dyn PromptStore> = match &config.directory {
+            Some(directory) => {
+                info!("使用Prompt模板目录: {}", directory);
+                Arc::new(FilePromptStore::new(directory))
+            }
+            None => {
+                info!("未配置Prompt模板目录，使用内存模板存储");
+                Arc::new(InMemoryPromptStore::new())
+            }
+        };
+        Arc::new(PromptManager::new(store, config.output_language.clone()))
+    }
+    
     /// 创建嵌入服务
     pub async fn create_embedding_service(
         config: &EmbeddingConfig,
@@ -91,19 +175,20 @@ impl ServiceFactory {
         }
     }
     
-    /// 创建向量数据库
+    /// 创建向量数据库，不支持原生混合检索的后端套上关键词倒排索引，索引由工单表重建
     pub async fn create_vector_database(
         config: &VectorDbConfig,
+        database: &Arc<dyn Repository>,
     ) -> Result<Arc<dyn VectorDatabase + Send + Sync>> {
         info!("创建向量数据库: {}", config.provider);
         
-        match config.provider.as_str() {
+        let db: Arc<dyn VectorDatabase + Send + Sync> = match config.provider.as_str() {
             "sqlite" => {
                 let db = SqliteVectorDB::new(
                     &config.connection_string,
                     config.dimension,
                 ).await?;
-                Ok(Arc::new(db))
+                Arc::new(db)
             }
             "qdrant" => {
                 let db = QdrantVectorDB::new(
@@ -111,7 +196,7 @@ impl ServiceFactory {
                     "tickets".to_string(), // 默认集合名
                     config.dimension,
                 ).await?;
-                Ok(Arc::new(db))
+                Arc::new(db)
             }
             "postgres" => {
                 let db = PostgresVectorDB::new(
@@ -119,12 +204,108 @@ impl ServiceFactory {
                     "ticket_vectors".to_string(), // 默认表名
                     config.dimension,
                 ).await?;
-                Ok(Arc::new(db))
+                Arc::new(db)
             }
-            _ => Err(AppError::Configuration {
+            _ => return Err(AppError::Configuration {
                 message: format!("不支持的向量数据库提供商: {}", config.provider),
             }.into()),
+        };
+        if db.database_info().supports_hybrid_search {
+            return Ok(db);
+        }
+        
+        // 不支持原生混合检索的后端由内存倒排索引补足关键词召回，索引由工单表重建
+        let indexed = KeywordIndexedVectorDatabase::new(db);
+        let count = indexed.warm_up(database.as_ref()).await?;
+        info!("关键词倒排索引已建立: {} 条", count);
+        Ok(Arc::new(indexed))
+    }
+    
+    /// 创建用量追踪器，预算降级使用的后端必须在模型路由中配置
+    pub fn create_usage_tracker(
+        config: &AppConfig,
+        database: &Arc<dyn Repository>,
+    ) -> Result<Arc<UsageTracker>> {
+        let store: Arc<dyn UsageRepository> = match config.usage.store.as_str() {
+            "database" => database.clone(),
+            "memory" => {
+                warn!("用量记录保存在内存中，重启后丢失");
+                Arc::new(InMemoryUsageStore::new())
+            }
+            other => return Err(AppError::Configuration {
+                message: format!("不支持的用量存储: {}", other),
+            }.into()),
+        };
+        let tracker = UsageTracker::new(store, config.usage.clone())?;
+        
+        if let Some(backend) = tracker.fallback_backend() {
+            if backend != PRIMARY_LLM_BACKEND && !config.routing.backends.contains_key(backend) {
+                return Err(AppError::Configuration {
+                    message: format!("预算降级后端未在模型路由中配置: {}", backend),
+                }.into());
+            }
+        }
+        Ok(Arc::new(tracker))
+    }
+    
+    /// 创建生成方案安全检查，LLM复核指定的后端必须在模型路由中配置
+    pub fn create_guardrails(
+        config: &AppConfig,
+        llm_service: &Arc<dyn LLMService + Send + Sync>,
+    ) -> Result<Arc<Guardrails>> {
+        let guardrails = &config.guardrails;
+        if let (true, Some(backend)) = (guardrails.llm_judge, &guardrails.judge_backend) {
+            if backend != PRIMARY_LLM_BACKEND && !config.routing.backends.contains_key(backend) {
+                return Err(AppError::Configuration {
+                    message: format!("安全复核后端未在模型路由中配置: {}", backend),
+                }.into());
+            }
+        }
+        if !guardrails.enabled {
+            warn!("生成方案安全检查未启用");
+        }
+        Ok(Arc::new(Guardrails::new(guardrails.clone(), &config.privacy, llm_service.clone())?))
+    }
+    
+    /// 创建敏感信息脱敏器，未启用时返回 `None`
+    pub fn create_redactor(config: &PrivacyConfig) -> Result<Option<Arc<Redactor>>> {
+        if !config.enabled {
+            warn!("敏感信息脱敏未启用，工单原文将直接发送给外部服务商");
+            return Ok(None);
         }
+        info!("启用敏感信息脱敏: 服务商 {:?}", config.providers);
+        Ok(Some(Arc::new(Redactor::new(config)?)))
+    }
+    
+    /// 创建大语言模型服务，配置了其他模型后端时在主模型和各后端之间路由
+    /// 
+    /// 脱敏和用量记录包在每个后端上，以便按实际调用的服务商执行
+    pub async fn create_routed_llm_service(
+        primary: &LLMConfig,
+        routing: &RoutingConfig,
+        usage: &Arc<UsageTracker>,
+        redactor: Option<&Arc<Redactor>>,
+    ) -> Result<Arc<dyn LLMService + Send + Sync>> {
+        let decorate = |config: &LLMConfig, mut service: Arc<dyn LLMService + Send + Sync>| -> Arc<dyn LLMService + Send + Sync> {
+            if let Some(redactor) = redactor.filter(|r| r.applies_to(&config.provider)) {
+                service = Arc::new(RedactingLLMService::new(service, redactor.clone()));
+            }
+            if usage.enabled() {
+                service = Arc::new(MeteredLLMService::new(service, usage.clone()));
+            }
+            service
+        };
+        if routing.backends.is_empty() {
+            return Ok(decorate(primary, Self::create_llm_service(primary).await?));
+        }
+        
+        let mut backends = HashMap::new();
+        backends.insert(PRIMARY_LLM_BACKEND.to_string(), decorate(primary, Self::create_llm_service(primary).await?));
+        for (name, config) in &routing.backends {
+            info!("注册模型后端: {} ({})", name, config.model);
+            backends.insert(name.clone(), decorate(config, Self::create_llm_service(config).await?));
+        }
+        Ok(Arc::new(RoutingLLMService::new(backends, routing)?))
     }
     
     /// 创建大语言模型服务
@@ -135,13 +316,33 @@ impl ServiceFactory {
         
         match config.provider.as_str() {
             "qwen" => {
-                let service = QwenLLMService::new(
+                let mut service = QwenLLMService::new(
                     config.api_key.clone(),
                     config.model.clone(),
                     config.endpoint.clone(),
-                    4096, // 默认最大token
-                    0.7, // 默认温度
-                );
+                    config.max_tokens,
+                    config.temperature,
+                    config.timeout,
+                    config.max_concurrency,
+                )?;
+                if let Some(context_window) = config.context_window {
+                    service = service.with_context_window(context_window);
+                }
+                Ok(Arc::new(service))
+            }
+            "openai" | "ollama" => {
+                let mut service = OpenAILLMService::new(
+                    config.api_key.clone(),
+                    config.model.clone(),
+                    config.endpoint.clone(),
+                    config.max_tokens,
+                    config.temperature,
+                    config.timeout,
+                    config.max_concurrency,
+                )?;
+                if let Some(context_window) = config.context_window {
+                    service = service.with_context_window(context_window);
+                }
                 Ok(Arc::new(service))
             }
             _ => Err(AppError::Configuration {
@@ -150,13 +351,65 @@ impl ServiceFactory {
         }
     }
     
-    /// 创建数据库服务
+    /// 创建数据库服务，按连接地址的协议选择后端
+    /// 
+    /// - `postgres://`、`postgresql://`：PostgreSQL
+    /// - `sqlite:`：SQLite，如 `sqlite://data/rag.db`、`sqlite::memory:`
+    /// - `memory://`：进程内存储，重启后丢失
     pub async fn create_database(
         config: &DatabaseConfig,
-    ) -> Result<Arc<PostgresDatabase>> {
-        info!("创建数据库连接: {}", config.url);
+    ) -> Result<Arc<dyn Repository>> {
+        let scheme = config.url.split(':').next().unwrap_or_default().to_lowercase();
+        let database: Arc<dyn Repository> = match scheme.as_str() {
+            "postgres" | "postgresql" => Arc::new(PostgresDatabase::new(config).await?),
+            "sqlite" => Arc::new(SqliteDatabase::new(config).await?),
+            "memory" => {
+                warn!("业务数据保存在内存中，重启后丢失");
+                Arc::new(InMemoryDatabase::new())
+            }
+            _ => return Err(AppError::Configuration {
+                message: format!("不支持的数据库类型: {}", scheme),
+            }.into()),
+        };
+        info!("数据库后端: {}", database.backend());
+        Ok(database)
+    }
+    
+    /// 创建附件存储
+    pub fn create_attachment_storage(config: &AttachmentConfig) -> Result<Arc<dyn AttachmentStorage>> {
+        info!("创建附件存储: {}", config.storage);
         
-        let database = PostgresDatabase::new(&config.url).await?;
-        Ok(Arc::new(database))
+        match config.storage.as_str() {
+            "local" => Ok(Arc::new(LocalFileStorage::new(&config.local_path))),
+            "s3" => {
+                let s3 = config.s3.clone().ok_or_else(|| AppError::Configuration {
+                    message: "附件存储为s3时必须配置 attachments.s3".to_string(),
+                })?;
+                Ok(Arc::new(S3Storage::new(s3)?))
+            }
+            _ => Err(AppError::Configuration {
+                message: format!("不支持的附件存储: {}", config.storage),
+            }.into()),
+        }
+    }
+    
+    /// 创建附件病毒扫描器
+    pub fn create_virus_scanner(config: &AttachmentConfig) -> Result<Arc<dyn VirusScanner>> {
+        match config.virus_scan.as_str() {
+            "none" => {
+                warn!("附件病毒扫描未启用");
+                Ok(Arc::new(NoopVirusScanner))
+            }
+            "clamd" => {
+                let address = config.clamd_address.clone().ok_or_else(|| AppError::Configuration {
+                    message: "病毒扫描为clamd时必须配置 attachments.clamd_address".to_string(),
+                })?;
+                info!("附件病毒扫描: clamd ({})", address);
+                Ok(Arc::new(ClamdScanner::new(address)))
+            }
+            _ => Err(AppError::Configuration {
+                message: format!("不支持的病毒扫描方式: {}", config.virus_scan),
+            }.into()),
+        }
     }
 } 
\ No newline at end of file
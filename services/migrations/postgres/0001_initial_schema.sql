@@ -0,0 +1,59 @@
+-- 工单、解决方案、反馈与微调数据
+
+CREATE TABLE IF NOT EXISTS tickets (
+    id          UUID PRIMARY KEY,
+    title       TEXT NOT NULL,
+    description TEXT NOT NULL,
+    category    TEXT NOT NULL,
+    priority    INTEGER NOT NULL CHECK (priority BETWEEN 1 AND 5),
+    status      TEXT NOT NULL,
+    tags        TEXT[] NOT NULL DEFAULT '{}',
+    embedding   REAL[],
+    created_at  TIMESTAMPTZ NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_tickets_category ON tickets (category);
+CREATE INDEX IF NOT EXISTS idx_tickets_status ON tickets (status);
+CREATE INDEX IF NOT EXISTS idx_tickets_created_at ON tickets (created_at DESC);
+
+CREATE TABLE IF NOT EXISTS solutions (
+    id               UUID PRIMARY KEY,
+    ticket_id        UUID NOT NULL REFERENCES tickets (id) ON DELETE CASCADE,
+    solution         TEXT NOT NULL,
+    confidence       REAL NOT NULL,
+    reasoning        TEXT NOT NULL,
+    is_accepted      BOOLEAN NOT NULL DEFAULT FALSE,
+    feedback_score   INTEGER CHECK (feedback_score BETWEEN 1 AND 5),
+    feedback_comment TEXT,
+    prompt_version   TEXT,
+    citations        JSONB NOT NULL DEFAULT '[]',
+    raw_confidence   REAL,
+    guardrail        JSONB,
+    created_at       TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_solutions_ticket_id ON solutions (ticket_id);
+CREATE INDEX IF NOT EXISTS idx_solutions_created_at ON solutions (created_at DESC);
+
+CREATE TABLE IF NOT EXISTS feedback (
+    id          UUID PRIMARY KEY,
+    solution_id UUID NOT NULL REFERENCES solutions (id) ON DELETE CASCADE,
+    is_accepted BOOLEAN NOT NULL,
+    score       INTEGER CHECK (score BETWEEN 1 AND 5),
+    comment     TEXT,
+    created_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_feedback_solution_id ON feedback (solution_id);
+
+CREATE TABLE IF NOT EXISTS finetune_data (
+    id            UUID PRIMARY KEY,
+    input_text    TEXT NOT NULL,
+    target_output TEXT NOT NULL,
+    data_source   TEXT NOT NULL,
+    quality_score REAL NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_finetune_data_created_at ON finetune_data (created_at);
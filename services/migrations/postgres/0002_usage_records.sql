@@ -0,0 +1,21 @@
+-- 模型调用用量记录
+
+CREATE TABLE IF NOT EXISTS usage_records (
+    id                UUID PRIMARY KEY,
+    ticket_id         UUID,
+    category          TEXT,
+    kind              TEXT NOT NULL,
+    provider          TEXT NOT NULL,
+    model             TEXT NOT NULL,
+    prompt_tokens     INTEGER NOT NULL,
+    completion_tokens INTEGER NOT NULL,
+    total_tokens      INTEGER NOT NULL,
+    estimated         BOOLEAN NOT NULL,
+    latency_ms        BIGINT NOT NULL,
+    cost              DOUBLE PRECISION NOT NULL,
+    success           BOOLEAN NOT NULL,
+    created_at        TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_records_created_at ON usage_records (created_at);
+CREATE INDEX IF NOT EXISTS idx_usage_records_ticket_id ON usage_records (ticket_id);
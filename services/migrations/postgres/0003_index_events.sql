@@ -0,0 +1,20 @@
+-- 工单索引变更事件（transactional outbox）
+
+CREATE TABLE IF NOT EXISTS index_events (
+    id              UUID PRIMARY KEY,
+    ticket_id       UUID NOT NULL,
+    kind            TEXT NOT NULL,
+    status          TEXT NOT NULL,
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    next_attempt_at TIMESTAMPTZ NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_index_events_due ON index_events (status, next_attempt_at);
+
+-- 尚未写入向量索引的已有工单补一条同步事件
+INSERT INTO index_events (id, ticket_id, kind, status, attempts, next_attempt_at, created_at)
+SELECT gen_random_uuid(), id, 'upsert', 'pending', 0, NOW(), NOW()
+FROM tickets
+WHERE embedding IS NULL;
@@ -0,0 +1,14 @@
+-- 工单评论，随工单级联删除
+
+CREATE TABLE IF NOT EXISTS ticket_comments (
+    id         UUID PRIMARY KEY,
+    ticket_id  UUID NOT NULL REFERENCES tickets (id) ON DELETE CASCADE,
+    author     TEXT NOT NULL,
+    role       TEXT NOT NULL,
+    body       TEXT NOT NULL,
+    internal   BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_comments_ticket ON ticket_comments (ticket_id, created_at);
@@ -0,0 +1,21 @@
+-- 工单索引变更事件（transactional outbox）
+
+CREATE TABLE IF NOT EXISTS index_events (
+    id              BLOB PRIMARY KEY,
+    ticket_id       BLOB NOT NULL,
+    kind            TEXT NOT NULL,
+    status          TEXT NOT NULL,
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    last_error      TEXT,
+    next_attempt_at TEXT NOT NULL,
+    created_at      TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_index_events_due ON index_events (status, next_attempt_at);
+
+-- 尚未写入向量索引的已有工单补一条同步事件
+INSERT INTO index_events (id, ticket_id, kind, status, attempts, next_attempt_at, created_at)
+SELECT randomblob(16), id, 'upsert', 'pending', 0,
+       strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now'), strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+FROM tickets
+WHERE embedding IS NULL;
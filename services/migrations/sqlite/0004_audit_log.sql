@@ -0,0 +1,16 @@
+-- 工单审计日志，只追加不修改；不设外键，工单删除后仍保留记录
+
+CREATE TABLE IF NOT EXISTS audit_log (
+    seq         INTEGER PRIMARY KEY AUTOINCREMENT, -- 追加顺序
+    id          BLOB NOT NULL UNIQUE,
+    ticket_id   BLOB NOT NULL,
+    solution_id BLOB,
+    actor       TEXT NOT NULL,
+    action      TEXT NOT NULL,
+    field       TEXT,
+    old_value   TEXT,
+    new_value   TEXT,
+    created_at  TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_ticket ON audit_log (ticket_id, seq);
@@ -0,0 +1,3 @@
+-- 工单解决说明，没有已采纳方案时解决工单必须填写
+
+ALTER TABLE tickets ADD COLUMN resolution_note TEXT;
@@ -0,0 +1,16 @@
+-- 工单附件元数据，内容保存在附件存储中；随工单级联删除
+
+CREATE TABLE IF NOT EXISTS ticket_attachments (
+    id             BLOB PRIMARY KEY,
+    ticket_id      BLOB NOT NULL REFERENCES tickets (id) ON DELETE CASCADE,
+    filename       TEXT NOT NULL,
+    content_type   TEXT NOT NULL,
+    size_bytes     INTEGER NOT NULL,
+    storage_key    TEXT NOT NULL,
+    extracted_text TEXT,
+    error_lines    TEXT NOT NULL DEFAULT '[]',
+    uploaded_by    TEXT NOT NULL,
+    created_at     TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_ticket_attachments_ticket ON ticket_attachments (ticket_id, created_at);
@@ -0,0 +1,3 @@
+-- 工单乐观锁版本号，按版本条件更新，每次更新加一
+
+ALTER TABLE tickets ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,261 @@
+//! # 附件存储实现模块
+//!
+//! 提供本地目录和S3兼容对象存储两种附件存储后端
+
+use rag_deps::*;
+use rag_core::{config::S3StorageConfig, errors::AppError, traits::AttachmentStorage};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// S3请求超时时间
+const S3_TIMEOUT_SECS: u64 = 60;
+
+/// 本地目录附件存储
+///
+/// 职责：
+/// - 以对象键作为相对路径，把附件保存在根目录下
+/// - 先写临时文件再重命名，避免读到写了一半的内容
+pub struct LocalFileStorage {
+    root: PathBuf,
+}
+
+impl LocalFileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 对象键对应的文件路径，拒绝绝对路径和 `..`
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let relative = Path::new(key);
+        let valid = !key.is_empty() && relative.components().all(|c| matches!(c, Component::Normal(_)));
+        if !valid {
+            return Err(AppError::Storage {
+                message: format!("非法的附件对象键: {}", key),
+            }.into());
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl AttachmentStorage for LocalFileStorage {
+    async fn put(&self, key: &str, data: &[u8], _content_type: &str) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(storage_error)?;
+        }
+        let temp = path.with_extension(format!("tmp-{}", Uuid::new_v4()));
+        tokio::fs::write(&temp, data).await.map_err(storage_error)?;
+        if let Err(e) = tokio::fs::rename(&temp, &path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(storage_error(e).into());
+        }
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(AppError::not_found("attachment", key).into()),
+            Err(e) => Err(storage_error(e).into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage_error(e).into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+}
+
+/// S3兼容对象存储
+///
+/// 职责：
+/// - 通过PUT/GET/DELETE Object接口读写附件
+/// - 使用AWS Signature Version 4签名请求，兼容AWS S3、MinIO等服务
+/// - 支持路径形式和虚拟主机形式访问存储桶
+pub struct S3Storage {
+    client: reqwest::Client,
+    config: S3StorageConfig,
+}
+
+impl S3Storage {
+    pub fn new(config: S3StorageConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(S3_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self { client, config })
+    }
+
+    /// 对象URL，键中的每段路径分别编码
+    fn object_url(&self, key: &str) -> Result<reqwest::Url> {
+        let endpoint = self.config.endpoint.trim_end_matches('/');
+        let key = format!("{}{}", self.config.prefix, key);
+        let path = key.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+        let url = if self.config.path_style {
+            format!("{}/{}/{}", endpoint, uri_encode(&self.config.bucket), path)
+        } else {
+            let (scheme, host) = endpoint.split_once("://").unwrap_or(("https", endpoint));
+            format!("{}://{}.{}/{}", scheme, self.config.bucket, host, path)
+        };
+        reqwest::Url::parse(&url).map_err(|e| {
+            AppError::Configuration {
+                message: format!("S3地址无效: {}: {}", url, e),
+            }.into()
+        })
+    }
+
+    /// 发送签名后的请求
+    async fn send(&self, method: reqwest::Method, key: &str, body: Vec<u8>, content_type: Option<&str>) -> Result<reqwest::Response> {
+        let url = self.object_url(key)?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(AppError::Configuration { message: format!("S3地址缺少主机名: {}", url) }.into()),
+        };
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(content_type) = content_type {
+            headers.push(("content-type", content_type.to_string()));
+        }
+        let authorization = sigv4_authorization(&SigningRequest {
+            method: method.as_str(),
+            path: url.path(),
+            headers: &headers,
+            payload_hash: &payload_hash,
+            amz_date: &amz_date,
+            region: &self.config.region,
+            access_key_id: &self.config.access_key_id,
+            secret_access_key: &self.config.secret_access_key,
+        });
+
+        let mut request = self.client.request(method, url).header("authorization", authorization);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, value);
+        }
+        Ok(request.body(body).send().await.map_err(AppError::from)?)
+    }
+}
+
+#[async_trait]
+impl AttachmentStorage for S3Storage {
+    async fn put(&self, key: &str, data: &[u8], content_type: &str) -> Result<()> {
+        let response = self.send(reqwest::Method::PUT, key, data.to_vec(), Some(content_type)).await?;
+        ensure_success(response, "PutObject").await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let response = self.send(reqwest::Method::GET, key, Vec::new(), None).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::not_found("attachment", key).into());
+        }
+        let response = ensure_success(response, "GetObject").await?;
+        Ok(response.bytes().await.map_err(AppError::from)?.to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self.send(reqwest::Method::DELETE, key, Vec::new(), None).await?;
+        // S3删除不存在的对象也返回204
+        if response.status() != reqwest::StatusCode::NOT_FOUND {
+            ensure_success(response, "DeleteObject").await?;
+        }
+        Ok(())
+    }
+
+    fn backend(&self) -> &'static str {
+        "s3"
+    }
+}
+
+/// 待签名的请求，`headers` 的名称需为小写
+struct SigningRequest<'a> {
+    method: &'a str,
+    path: &'a str,
+    headers: &'a [(&'a str, String)],
+    payload_hash: &'a str,
+    amz_date: &'a str, // 如 20130524T000000Z
+    region: &'a str,
+    access_key_id: &'a str,
+    secret_access_key: &'a str,
+}
+
+/// 按AWS Signature Version 4生成 `Authorization` 请求头（服务名为 s3，不含查询参数）
+fn sigv4_authorization(request: &SigningRequest) -> String {
+    let mut headers: Vec<(&str, &str)> = request.headers.iter()
+        .map(|(name, value)| (*name, value.trim()))
+        .collect();
+    headers.sort_by_key(|(name, _)| *name);
+    let canonical_headers: String = headers.iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        request.method, request.path, canonical_headers, signed_headers, request.payload_hash,
+    );
+
+    let date = &request.amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, request.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        request.amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes())),
+    );
+    let key = [date, request.region, "s3", "aws4_request"].iter().fold(
+        format!("AWS4{}", request.secret_access_key).into_bytes(),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    );
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        request.access_key_id, scope, signed_headers, signature,
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC接受任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 按SigV4规则编码路径段：只保留非保留字符
+fn uri_encode(segment: &str) -> String {
+    segment.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// 检查HTTP响应状态，失败时带上响应体
+async fn ensure_success(response: reqwest::Response, api: &str) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let detail = response.text().await.unwrap_or_default();
+    Err(AppError::Storage {
+        message: format!("S3 {} 返回 {}: {}", api, status, detail),
+    }.into())
+}
+
+fn storage_error(err: std::io::Error) -> AppError {
+    AppError::Storage {
+        message: err.to_string(),
+    }
+}
@@ -6,10 +6,16 @@
 //! - 提供Reranking服务的具体实现
 //! - 提供向量数据库的具体实现
 //! - 提供LLM服务的具体实现
-//! - 提供关系数据库访问服务
+//! - 提供关系数据库访问服务（PostgreSQL、SQLite、内存）
+//! - 提供附件存储（本地目录、S3兼容对象存储）与病毒扫描的具体实现
 
 pub mod embedding;
 pub mod reranking;
 pub mod vector_db;
 pub mod llm;
-pub mod database; 
\ No newline at end of file
+pub mod llm_router;
+pub mod database;
+pub mod sqlite_database;
+pub mod memory_database; 
+pub mod attachment_storage;
+pub mod virus_scan;
@@ -1,16 +1,373 @@
 //! # LLM服务实现模块
-//! 
+//!
 //! 提供不同大语言模型的具体实现
 
 use rag_deps::*;
-use rag_core::traits::{LLMService, llm::{LLMResponse, TokenUsage}};
+use rag_core::traits::{LLMService, llm::{LLMResponse, TokenUsage, GenerationOptions, ResponseFormat, LLMStream, LLMStreamEvent}};
 use rag_core::traits::{embedding::ModelInfo, reranking::RerankResult};
-use rag_core::models::Ticket;
+use rag_core::{models::Ticket, errors::AppError, prompt::{RenderedPrompt, TokenEstimator}};
+use futures::{Stream, StreamExt, TryStreamExt};
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::time::Duration;
 
-/// Qwen LLM服务实现
+/// 未提供结构化置信度时使用的默认值
+const DEFAULT_CONFIDENCE: f32 = 0.5;
+
+/// 默认批量生成并发数
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+
+/// Qwen默认上下文窗口
+const QWEN_CONTEXT_WINDOW: usize = 32768;
+
+/// OpenAI兼容服务默认上下文窗口，本地模型通常较小
+const OPENAI_CONTEXT_WINDOW: usize = 8192;
+
+/// 推理模型（如Qwen3）输出思考过程使用的标签
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// 将渲染后的Prompt转换为对话消息
+fn prompt_messages(prompt: &RenderedPrompt) -> Vec<ChatMessage> {
+    let mut messages = Vec::with_capacity(2);
+    if !prompt.system.is_empty() {
+        messages.push(ChatMessage::system(&prompt.system));
+    }
+    messages.push(ChatMessage::user(&prompt.user));
+    messages
+}
+
+/// 去掉推理模型输出中的 `<think>…</think>` 思考过程，结构化解析只看最终回答
+///
+/// 对话模板已预置开始标签时输出只有结束标签，去掉结束标签之前的全部内容；
+/// 未闭合的思考过程视为输出被截断，一并去掉
+fn strip_think(text: &str) -> String {
+    if !text.contains(THINK_OPEN) && !text.contains(THINK_CLOSE) {
+        return text.to_string();
+    }
+    let mut rest = text;
+    if let Some(end) = rest.find(THINK_CLOSE).filter(|end| !rest[..*end].contains(THINK_OPEN)) {
+        rest = &rest[end + THINK_CLOSE.len()..];
+    }
+    let mut output = String::with_capacity(rest.len());
+    while let Some(start) = rest.find(THINK_OPEN) {
+        output.push_str(&rest[..start]);
+        rest = match rest[start..].find(THINK_CLOSE) {
+            Some(end) => &rest[start + end + THINK_CLOSE.len()..],
+            None => "",
+        };
+    }
+    output.push_str(rest);
+    output.trim().to_string()
+}
+
+/// 流式输出的思考过程过滤器
+///
+/// 标签可能被拆到多个数据块中，可能构成标签开头的尾部先保留，等后续数据到达再判断。
+/// 已输出的文本无法撤回，流式输出不处理对话模板预置开始标签的情况，单独的结束标签直接去掉
+#[derive(Debug, Default)]
+struct ThinkFilter {
+    pending: String,
+    thinking: bool,
+    started: bool,
+}
+
+impl ThinkFilter {
+    /// 接收一段增量文本，返回可以输出的部分
+    fn push(&mut self, delta: &str) -> String {
+        self.pending.push_str(delta);
+        let mut output = String::new();
+        loop {
+            if self.thinking {
+                match self.pending.find(THINK_CLOSE) {
+                    Some(end) => {
+                        self.pending.drain(..end + THINK_CLOSE.len());
+                        self.thinking = false;
+                    }
+                    None => {
+                        let keep = partial_tag_len(&self.pending, &[THINK_CLOSE]);
+                        self.pending.drain(..self.pending.len() - keep);
+                        break;
+                    }
+                }
+                continue;
+            }
+            let open = self.pending.find(THINK_OPEN).map(|start| (start, THINK_OPEN));
+            let close = self.pending.find(THINK_CLOSE).map(|start| (start, THINK_CLOSE));
+            match open.into_iter().chain(close).min() {
+                Some((start, tag)) => {
+                    output.push_str(&self.pending[..start]);
+                    self.pending.drain(..start + tag.len());
+                    self.thinking = tag == THINK_OPEN;
+                }
+                None => {
+                    let keep = partial_tag_len(&self.pending, &[THINK_OPEN, THINK_CLOSE]);
+                    output.extend(self.pending.drain(..self.pending.len() - keep));
+                    break;
+                }
+            }
+        }
+        self.visible(output)
+    }
+
+    /// 输出结束，返回剩余的文本；未闭合的思考过程丢弃
+    fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        if self.thinking {
+            return String::new();
+        }
+        self.visible(rest)
+    }
+
+    /// 去掉回答开头的空白，与非流式结果一致
+    fn visible(&mut self, text: String) -> String {
+        if self.started {
+            return text;
+        }
+        let text = text.trim_start();
+        self.started = !text.is_empty();
+        text.to_string()
+    }
+}
+
+/// `text` 末尾可能是某个标签开头的最长长度
+fn partial_tag_len(text: &str, tags: &[&str]) -> usize {
+    tags.iter()
+        .flat_map(|tag| (1..tag.len()).rev().filter(move |len| text.ends_with(&tag[..*len])))
+        .max()
+        .unwrap_or(0)
+}
+
+/// 非流式生成结果
+struct Completion {
+    content: String,
+    model: String,
+    token_usage: Option<TokenUsage>,
+    avg_logprob: Option<f32>,
+}
+
+impl From<Completion> for LLMResponse {
+    fn from(completion: Completion) -> Self {
+        Self {
+            content: completion.content,
+            model: completion.model,
+            confidence: DEFAULT_CONFIDENCE,
+            reasoning: String::new(),
+            token_usage: completion.token_usage,
+            avg_logprob: completion.avg_logprob,
+        }
+    }
+}
+
+/// 创建带超时的HTTP客户端
+fn http_client(timeout_secs: u64) -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs))
+        .build()?)
+}
+
+/// 检查HTTP响应状态，失败时带上响应体
+async fn ensure_success(response: reqwest::Response, api: &str) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let detail = response.text().await.unwrap_or_default();
+    Err(AppError::LLMService {
+        message: format!("{} 返回 {}: {}", api, status, detail),
+    }.into())
+}
+
+/// 流式响应中的单个数据块
+struct StreamChunk {
+    delta: Option<String>,
+    usage: Option<TokenUsage>,
+}
+
+/// 将SSE响应体拆分为 `data:` 负载
 /// 
+/// 按字节缓冲到换行再解码，避免多字节字符被分块截断
+fn sse_data(response: reqwest::Response) -> impl Stream<Item = Result<String>> + Send {
+    let state = (Box::pin(response.bytes_stream()), Vec::<u8>::new(), VecDeque::<String>::new(), false);
+    
+    futures::stream::unfold(state, |(mut bytes, mut buffer, mut pending, mut finished)| async move {
+        loop {
+            if let Some(data) = pending.pop_front() {
+                return Some((Ok(data), (bytes, buffer, pending, finished)));
+            }
+            if finished {
+                return None;
+            }
+            match bytes.next().await {
+                Some(Ok(chunk)) => {
+                    buffer.extend_from_slice(&chunk);
+                    while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                        let line: Vec<u8> = buffer.drain(..=pos).collect();
+                        let line = String::from_utf8_lossy(&line);
+                        if let Some(data) = line.trim_end().strip_prefix("data:") {
+                            pending.push_back(data.trim_start().to_string());
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    finished = true;
+                    return Some((Err(e.into()), (bytes, buffer, pending, finished)));
+                }
+                None => finished = true,
+            }
+        }
+    })
+}
+
+/// 将SSE数据流转换为LLM流式事件，结束时输出最后一次收到的token统计
+///
+/// 增量文本经过 [`ThinkFilter`]，思考过程不会输出给调用方
+fn llm_event_stream(
+    response: reqwest::Response,
+    parse: fn(&str) -> Result<StreamChunk>,
+    model: String,
+    confidence: f32,
+    reasoning: String,
+) -> LLMStream {
+    let data: Pin<Box<dyn Stream<Item = Result<String>> + Send>> = Box::pin(sse_data(response));
+    let state = (data, ThinkFilter::default(), None::<TokenUsage>, Some(reasoning));
+    
+    let events = futures::stream::unfold(state, move |(mut data, mut filter, mut usage, mut reasoning)| {
+        let model = model.clone();
+        async move {
+            let finish = |usage: Option<TokenUsage>, reasoning: Option<String>| LLMStreamEvent::Done {
+                model: model.clone(),
+                confidence,
+                reasoning: reasoning.unwrap_or_default(),
+                token_usage: usage,
+            };
+            loop {
+                // reasoning 被取走表示已经输出过结束事件
+                reasoning.as_ref()?;
+                match data.next().await {
+                    Some(Ok(payload)) if payload == "[DONE]" => {
+                        let rest = filter.finish();
+                        if !rest.is_empty() {
+                            // 下一次读取到数据流结束时输出结束事件
+                            data = Box::pin(futures::stream::empty());
+                            return Some((Ok(LLMStreamEvent::Delta(rest)), (data, filter, usage, reasoning)));
+                        }
+                        let done = finish(usage.take(), reasoning.take());
+                        return Some((Ok(done), (data, filter, usage, reasoning)));
+                    }
+                    Some(Ok(payload)) => match parse(&payload) {
+                        Ok(chunk) => {
+                            if chunk.usage.is_some() {
+                                usage = chunk.usage;
+                            }
+                            let delta = chunk.delta.map(|delta| filter.push(&delta)).unwrap_or_default();
+                            if !delta.is_empty() {
+                                return Some((Ok(LLMStreamEvent::Delta(delta)), (data, filter, usage, reasoning)));
+                            }
+                        }
+                        Err(e) => {
+                            reasoning = None;
+                            return Some((Err(e), (data, filter, usage, reasoning)));
+                        }
+                    },
+                    Some(Err(e)) => {
+                        reasoning = None;
+                        return Some((Err(e), (data, filter, usage, reasoning)));
+                    }
+                    None => {
+                        let rest = filter.finish();
+                        if !rest.is_empty() {
+                            return Some((Ok(LLMStreamEvent::Delta(rest)), (data, filter, usage, reasoning)));
+                        }
+                        let done = finish(usage.take(), reasoning.take());
+                        return Some((Ok(done), (data, filter, usage, reasoning)));
+                    }
+                }
+            }
+        }
+    });
+    Box::pin(events)
+}
+
+/// 对话消息
+///
+/// OpenAI兼容服务在只返回工具调用或拒答时 `content` 为null
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ChatMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// 流式响应中的增量消息
+#[derive(Debug, Deserialize)]
+struct DeltaMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl ChatMessage {
+    fn system(content: &str) -> Self {
+        Self { role: "system".to_string(), content: Some(content.to_string()) }
+    }
+
+    fn user(content: &str) -> Self {
+        Self { role: "user".to_string(), content: Some(content.to_string()) }
+    }
+}
+
+/// 输出格式参数，两种接口的写法相同
+#[derive(Debug, Serialize)]
+struct ResponseFormatParam {
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+impl From<ResponseFormat> for ResponseFormatParam {
+    fn from(format: ResponseFormat) -> Self {
+        let kind = match format {
+            ResponseFormat::Text => "text",
+            ResponseFormat::JsonObject => "json_object",
+        };
+        Self { kind }
+    }
+}
+
+/// 合并服务默认值后的生成参数
+#[derive(Debug, Serialize)]
+struct SamplingParams {
+    max_tokens: usize,
+    temperature: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormatParam>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    logprobs: bool,
+}
+
+impl SamplingParams {
+    fn resolve(options: &GenerationOptions, max_tokens: usize, temperature: f32) -> Self {
+        Self {
+            max_tokens: options.max_tokens.unwrap_or(max_tokens),
+            temperature: options.temperature.unwrap_or(temperature),
+            top_p: options.top_p,
+            stop: options.stop.clone(),
+            seed: options.seed,
+            response_format: options.response_format.map(Into::into),
+            logprobs: options.logprobs,
+        }
+    }
+}
+
+/// Qwen LLM服务实现
+///
 /// 职责：
-/// - 调用Qwen大语言模型API
+/// - 调用Qwen大语言模型API（DashScope原生接口）
 /// - 生成工单处理建议
 /// - 管理token使用和成本
 pub struct QwenLLMService {
@@ -20,6 +377,86 @@ pub struct QwenLLMService {
     endpoint: String,
     max_tokens: usize,
     temperature: f32,
+    max_concurrency: usize,
+    context_window: usize,
+}
+
+/// DashScope文本生成请求体
+#[derive(Debug, Serialize)]
+struct DashScopeRequest<'a> {
+    model: &'a str,
+    input: DashScopeInput,
+    parameters: DashScopeParameters,
+}
+
+#[derive(Debug, Serialize)]
+struct DashScopeInput {
+    messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Serialize)]
+struct DashScopeParameters {
+    result_format: &'static str,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    incremental_output: bool,
+    #[serde(flatten)]
+    sampling: SamplingParams,
+}
+
+/// DashScope文本生成响应体
+#[derive(Debug, Deserialize)]
+struct DashScopeResponse {
+    output: DashScopeOutput,
+    usage: Option<DashScopeUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DashScopeOutput {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DashScopeUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+    total_tokens: Option<u32>,
+}
+
+impl From<DashScopeUsage> for TokenUsage {
+    fn from(u: DashScopeUsage) -> Self {
+        Self {
+            prompt_tokens: u.input_tokens,
+            completion_tokens: u.output_tokens,
+            total_tokens: u.total_tokens.unwrap_or(u.input_tokens + u.output_tokens),
+        }
+    }
+}
+
+/// DashScope流式响应块（增量输出模式）
+#[derive(Debug, Deserialize)]
+struct DashScopeStreamChunk {
+    output: DashScopeStreamOutput,
+    usage: Option<DashScopeUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DashScopeStreamOutput {
+    #[serde(default)]
+    choices: Vec<DashScopeStreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DashScopeStreamChoice {
+    message: DeltaMessage,
+}
+
+/// 解析DashScope流式响应块
+fn parse_dashscope_chunk(payload: &str) -> Result<StreamChunk> {
+    let chunk: DashScopeStreamChunk = serde_json::from_str(payload)?;
+    Ok(StreamChunk {
+        delta: chunk.output.choices.into_iter().next().and_then(|c| c.message.content),
+        usage: chunk.usage.map(Into::into),
+    })
 }
 
 impl QwenLLMService {
@@ -29,76 +466,486 @@ impl QwenLLMService {
         endpoint: String,
         max_tokens: usize,
         temperature: f32,
-    ) -> Self {
-        Self {
-            client: reqwest::Client::new(),
+        timeout_secs: u64,
+        max_concurrency: Option<usize>,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: http_client(timeout_secs)?,
             api_key,
             model,
             endpoint,
             max_tokens,
             temperature,
+            max_concurrency: max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY).max(1),
+            context_window: QWEN_CONTEXT_WINDOW,
+        })
+    }
+
+    /// 设置模型上下文窗口大小
+    pub fn with_context_window(mut self, context_window: usize) -> Self {
+        self.context_window = context_window;
+        self
+    }
+
+    /// 构建DashScope文本生成请求，流式时开启增量输出
+    fn request(
+        &self,
+        messages: Vec<ChatMessage>,
+        options: &GenerationOptions,
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        let body = DashScopeRequest {
+            model: &self.model,
+            input: DashScopeInput { messages },
+            parameters: DashScopeParameters {
+                result_format: "message",
+                incremental_output: stream,
+                sampling: SamplingParams::resolve(options, self.max_tokens, self.temperature),
+            },
+        };
+
+        let request = self.client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&body);
+        if stream {
+            request.header("X-DashScope-SSE", "enable")
+        } else {
+            request
         }
     }
+
+    /// 调用DashScope文本生成接口
+    async fn complete(
+        &self,
+        messages: Vec<ChatMessage>,
+        options: &GenerationOptions,
+    ) -> Result<Completion> {
+        let response = self.request(messages, options, false).send().await?;
+        let generation: DashScopeResponse = ensure_success(response, "DashScope")
+            .await?
+            .json()
+            .await?;
+
+        let choice = generation.output.choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::LLMService {
+                message: "DashScope 响应中没有choices".to_string(),
+            })?;
+
+        Ok(choice.into_completion(&self.model, generation.usage.map(Into::into)))
+    }
 }
 
 #[async_trait]
 impl LLMService for QwenLLMService {
-    async fn generate_solution(
-        &self, 
-        ticket: &Ticket, 
-        similar_cases: &[RerankResult]
+    async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
     ) -> Result<LLMResponse> {
-        // TODO: 实现解决方案生成
-        // 1. 构建prompt
-        // 2. 调用Qwen API
-        // 3. 解析响应
-        // 4. 计算置信度
-        todo!("实现Qwen解决方案生成")
+        Ok(self
+            .complete(prompt_messages(prompt), options)
+            .await?
+            .into())
     }
-    
+
+    async fn generate_stream(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        let response = self
+            .request(prompt_messages(prompt), options, true)
+            .send()
+            .await?;
+        let response = ensure_success(response, "DashScope").await?;
+
+        Ok(llm_event_stream(
+            response,
+            parse_dashscope_chunk,
+            self.model.clone(),
+            DEFAULT_CONFIDENCE,
+            String::new(),
+        ))
+    }
+
     async fn generate_solutions_batch(
         &self,
-        requests: &[(Ticket, Vec<RerankResult>)]
+        requests: &[(Ticket, Vec<RerankResult>)],
+        options: &GenerationOptions,
     ) -> Result<Vec<LLMResponse>> {
-        // TODO: 实现批量解决方案生成
-        todo!("实现Qwen批量解决方案生成")
+        let tasks: Vec<_> = requests.iter()
+            .map(|(ticket, cases)| self.generate_solution(ticket, cases, options))
+            .collect();
+
+        futures::stream::iter(tasks)
+            .buffered(self.max_concurrency)
+            .try_collect()
+            .await
     }
-    
-    async fn chat(&self, prompt: &str) -> Result<LLMResponse> {
-        // TODO: 实现通用聊天接口
-        todo!("实现Qwen通用聊天")
+
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse> {
+        Ok(self
+            .complete(vec![ChatMessage::user(prompt)], options)
+            .await?
+            .into())
     }
-    
+
     fn model_info(&self) -> ModelInfo {
         ModelInfo {
             name: self.model.clone(),
             version: "2.5".to_string(),
             provider: "Qwen".to_string(),
-            max_tokens: self.max_tokens,
+            max_tokens: self.context_window,
             cost_per_call: Some(0.001),
         }
     }
-    
+
+    fn token_estimator(&self) -> TokenEstimator {
+        // Qwen分词器词表包含大量中文词组，汉字平均不足1个token
+        TokenEstimator {
+            cjk_tokens_per_char: 0.7,
+            chars_per_token: 4.0,
+        }
+    }
+
     async fn health_check(&self) -> Result<bool> {
-        // TODO: 实现健康检查
-        todo!("实现Qwen健康检查")
+        // DashScope没有轻量的模型列表接口，用最小生成请求探测
+        let options = GenerationOptions {
+            max_tokens: Some(1),
+            ..Default::default()
+        };
+        Ok(self.complete(vec![ChatMessage::user("ping")], &options).await.is_ok())
     }
 }
 
-/// OpenAI LLM服务实现
-/// 
+/// OpenAI兼容LLM服务实现
+///
 /// 职责：
-/// - 调用OpenAI GPT模型
-/// - 作为备选方案
+/// - 调用 `/v1/chat/completions` 接口（OpenAI、Ollama、vLLM均兼容）
+/// - 接入本地部署的Qwen3模型
+/// - 限制批量生成的并发数
 pub struct OpenAILLMService {
-    // TODO: 实现OpenAI LLM服务
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    base_url: String,
+    max_tokens: usize,
+    temperature: f32,
+    max_concurrency: usize,
+    context_window: usize,
 }
 
 /// 本地LLM服务实现
-/// 
-/// 职责：
-/// - 使用本地部署的大语言模型
-/// - 提供离线推理能力
-pub struct LocalLLMService {
-    // TODO: 实现本地LLM服务
-} 
\ No newline at end of file
+///
+/// Ollama和vLLM都提供OpenAI兼容接口，直接复用 [`OpenAILLMService`]
+pub type LocalLLMService = OpenAILLMService;
+
+/// Chat Completions请求体
+#[derive(Debug, Serialize)]
+struct ChatCompletionRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage>,
+    #[serde(flatten)]
+    sampling: SamplingParams,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+/// 流式请求选项，要求在最后一个数据块中返回token统计
+#[derive(Debug, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+/// Chat Completions响应体
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    choices: Vec<ChatChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+    #[serde(default)]
+    logprobs: Option<ChoiceLogprobs>,
+}
+
+/// token对数概率，两种接口的结构相同
+#[derive(Debug, Deserialize)]
+struct ChoiceLogprobs {
+    #[serde(default)]
+    content: Vec<TokenLogprob>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenLogprob {
+    logprob: f32,
+}
+
+impl ChatChoice {
+    /// 生成内容的平均token对数概率
+    fn avg_logprob(&self) -> Option<f32> {
+        let tokens = &self.logprobs.as_ref()?.content;
+        if tokens.is_empty() {
+            return None;
+        }
+        Some(tokens.iter().map(|t| t.logprob).sum::<f32>() / tokens.len() as f32)
+    }
+
+    fn into_completion(self, model: &str, token_usage: Option<TokenUsage>) -> Completion {
+        Completion {
+            avg_logprob: self.avg_logprob(),
+            content: strip_think(&self.message.content.unwrap_or_default()),
+            model: model.to_string(),
+            token_usage,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    total_tokens: u32,
+}
+
+impl From<ChatUsage> for TokenUsage {
+    fn from(u: ChatUsage) -> Self {
+        Self {
+            prompt_tokens: u.prompt_tokens,
+            completion_tokens: u.completion_tokens,
+            total_tokens: u.total_tokens,
+        }
+    }
+}
+
+/// Chat Completions流式响应块
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    delta: DeltaMessage,
+}
+
+/// 解析Chat Completions流式响应块
+fn parse_chat_completion_chunk(payload: &str) -> Result<StreamChunk> {
+    let chunk: ChatCompletionChunk = serde_json::from_str(payload)?;
+    Ok(StreamChunk {
+        delta: chunk.choices.into_iter().next().and_then(|c| c.delta.content),
+        usage: chunk.usage.map(Into::into),
+    })
+}
+
+impl OpenAILLMService {
+    /// `endpoint` 为服务根地址，如 `http://localhost:11434` 或 `http://localhost:8000/v1`；
+    /// `api_key` 为空时不发送认证头
+    pub fn new(
+        api_key: String,
+        model: String,
+        endpoint: String,
+        max_tokens: usize,
+        temperature: f32,
+        timeout_secs: u64,
+        max_concurrency: Option<usize>,
+    ) -> Result<Self> {
+        let base_url = endpoint.trim_end_matches('/');
+        let base_url = base_url.strip_suffix("/v1").unwrap_or(base_url).to_string();
+
+        Ok(Self {
+            client: http_client(timeout_secs)?,
+            api_key,
+            model,
+            base_url,
+            max_tokens,
+            temperature,
+            max_concurrency: max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY).max(1),
+            context_window: OPENAI_CONTEXT_WINDOW,
+        })
+    }
+
+    /// 设置模型上下文窗口大小
+    pub fn with_context_window(mut self, context_window: usize) -> Self {
+        self.context_window = context_window;
+        self
+    }
+
+    /// 附加认证头
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if self.api_key.trim().is_empty() {
+            request
+        } else {
+            request.bearer_auth(&self.api_key)
+        }
+    }
+
+    /// 构建Chat Completions请求
+    fn request(
+        &self,
+        messages: Vec<ChatMessage>,
+        options: &GenerationOptions,
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        let body = ChatCompletionRequest {
+            model: &self.model,
+            messages,
+            sampling: SamplingParams::resolve(options, self.max_tokens, self.temperature),
+            stream,
+            stream_options: stream.then_some(StreamOptions { include_usage: true }),
+        };
+
+        self.authorize(self.client.post(format!("{}/v1/chat/completions", self.base_url)))
+            .json(&body)
+    }
+
+    /// 调用Chat Completions接口
+    async fn complete(
+        &self,
+        messages: Vec<ChatMessage>,
+        options: &GenerationOptions,
+    ) -> Result<Completion> {
+        let response = self.request(messages, options, false).send().await?;
+        let completion: ChatCompletionResponse = ensure_success(response, "chat/completions")
+            .await?
+            .json()
+            .await?;
+
+        let choice = completion.choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::LLMService {
+                message: "chat/completions 响应中没有choices".to_string(),
+            })?;
+
+        Ok(choice.into_completion(&self.model, completion.usage.map(Into::into)))
+    }
+}
+
+#[async_trait]
+impl LLMService for OpenAILLMService {
+    async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        Ok(self
+            .complete(prompt_messages(prompt), options)
+            .await?
+            .into())
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        let response = self
+            .request(prompt_messages(prompt), options, true)
+            .send()
+            .await?;
+        let response = ensure_success(response, "chat/completions").await?;
+
+        Ok(llm_event_stream(
+            response,
+            parse_chat_completion_chunk,
+            self.model.clone(),
+            DEFAULT_CONFIDENCE,
+            String::new(),
+        ))
+    }
+
+    async fn generate_solutions_batch(
+        &self,
+        requests: &[(Ticket, Vec<RerankResult>)],
+        options: &GenerationOptions,
+    ) -> Result<Vec<LLMResponse>> {
+        let tasks: Vec<_> = requests.iter()
+            .map(|(ticket, cases)| self.generate_solution(ticket, cases, options))
+            .collect();
+
+        futures::stream::iter(tasks)
+            .buffered(self.max_concurrency)
+            .try_collect()
+            .await
+    }
+
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse> {
+        Ok(self
+            .complete(vec![ChatMessage::user(prompt)], options)
+            .await?
+            .into())
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        ModelInfo {
+            name: self.model.clone(),
+            version: "v1".to_string(),
+            provider: "OpenAI-compatible".to_string(),
+            max_tokens: self.context_window,
+            cost_per_call: None,
+        }
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        let response = self
+            .authorize(self.client.get(format!("{}/v1/models", self.base_url)))
+            .send()
+            .await?;
+        Ok(response.status().is_success())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 逐块送入过滤器，返回拼接后的输出
+    fn filter_chunks(chunks: &[&str]) -> String {
+        let mut filter = ThinkFilter::default();
+        let mut output: String = chunks.iter().map(|chunk| filter.push(chunk)).collect();
+        output.push_str(&filter.finish());
+        output
+    }
+
+    #[test]
+    fn think_filter_handles_tags_split_across_chunks() {
+        let cases: &[(&[&str], &str)] = &[
+            (&["<think>嗯", "</think>\n", "重启服务"], "重启服务"),
+            (&["<th", "ink>想一下</th", "ink>答案"], "答案"),
+            (&["<", "t", "h", "i", "n", "k", ">", "x", "<", "/", "think", ">", "结果"], "结果"),
+            (&["前<think>x</think>后", "<think>y", "</think>尾"], "前后尾"),
+            (&["普通", "回答"], "普通回答"),
+            (&["a < b", "，c <th"], "a < b，c <th"),
+            (&["<think>未完", "的思考"], ""),
+            (&["想一下</thi", "nk>答案"], "想一下答案"),
+        ];
+        for (chunks, expected) in cases {
+            assert_eq!(filter_chunks(chunks), *expected, "输入: {:?}", chunks);
+        }
+    }
+
+    #[test]
+    fn think_filter_matches_strip_think_on_whole_text() {
+        let text = "<think>先分析日志</think>\n\n1. 重启服务\n2. 检查<think>再想想</think>配置";
+        let chunked: Vec<String> = text.chars().map(String::from).collect();
+        let chunks: Vec<&str> = chunked.iter().map(String::as_str).collect();
+        assert_eq!(filter_chunks(&chunks), strip_think(text));
+    }
+
+    #[test]
+    fn think_filter_holds_partial_tags_until_resolved() {
+        let mut filter = ThinkFilter::default();
+        assert_eq!(filter.push("答案<thi"), "答案");
+        assert_eq!(filter.push("s is"), "<this is");
+        assert_eq!(filter.finish(), "");
+    }
+}
@@ -0,0 +1,189 @@
+//! # LLM模型路由模块
+//!
+//! 在多个模型后端之间按规则选择，对调用方表现为单一的LLM服务
+
+use rag_deps::*;
+use rag_core::traits::{LLMService, llm::{LLMResponse, GenerationOptions, LLMStream, RouteHints}};
+use rag_core::traits::{embedding::ModelInfo, reranking::RerankResult};
+use rag_core::{
+    config::{AbTestConfig, RoutingConfig, RoutingRule},
+    models::Ticket,
+    errors::AppError,
+    prompt::{RenderedPrompt, TokenEstimator},
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 路由选择结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum RouteReason {
+    /// 请求显式指定
+    Override,
+    /// 命中第几条规则
+    Rule(usize),
+    /// A/B分流
+    AbTest,
+    /// 默认后端
+    Default,
+}
+
+/// 模型路由LLM服务
+///
+/// 职责：
+/// - 按显式指定 → 规则 → A/B分流 → 默认后端的顺序选择模型
+/// - 将生成请求转发给选中的后端，响应中记录实际使用的模型
+/// - 上下文窗口和token估算取所有后端中最保守的值，保证组装的Prompt任一后端都能容纳
+pub struct RoutingLLMService {
+    backends: HashMap<String, Arc<dyn LLMService + Send + Sync>>,
+    default_backend: String,
+    rules: Vec<RoutingRule>,
+    ab_test: Option<AbTestConfig>,
+}
+
+impl RoutingLLMService {
+    /// 规则、A/B分流和默认后端引用的后端必须存在
+    pub fn new(
+        backends: HashMap<String, Arc<dyn LLMService + Send + Sync>>,
+        config: &RoutingConfig,
+    ) -> Result<Self> {
+        let referenced = std::iter::once(&config.default_backend)
+            .chain(config.rules.iter().map(|rule| &rule.backend))
+            .chain(config.ab_test.iter().map(|ab| &ab.backend));
+        for name in referenced {
+            if !backends.contains_key(name) {
+                return Err(AppError::Configuration {
+                    message: format!("模型路由引用了未配置的后端: {}", name),
+                }.into());
+            }
+        }
+        if let Some(ab) = &config.ab_test {
+            if ab.percent > 100 {
+                return Err(AppError::Configuration {
+                    message: format!("A/B分流比例超出范围: {}", ab.percent),
+                }.into());
+            }
+        }
+
+        Ok(Self {
+            backends,
+            default_backend: config.default_backend.clone(),
+            rules: config.rules.clone(),
+            ab_test: config.ab_test.clone(),
+        })
+    }
+
+    /// 为本次生成选择后端
+    pub fn select(&self, options: &GenerationOptions) -> Result<(&str, RouteReason)> {
+        if let Some(name) = &options.model {
+            let Some((name, _)) = self.backends.get_key_value(name) else {
+                return Err(AppError::validation("model", format!("未配置的模型后端: {}", name)).into());
+            };
+            return Ok((name, RouteReason::Override));
+        }
+
+        let hints = options.route.clone().unwrap_or_default();
+        if let Some(index) = self.rules.iter().position(|rule| rule_matches(rule, &hints)) {
+            return Ok((&self.rules[index].backend, RouteReason::Rule(index)));
+        }
+        if let Some(ab) = &self.ab_test {
+            let ticket_id = hints.ticket_id.unwrap_or_else(Uuid::new_v4);
+            if (ticket_id.as_u128() % 100) < ab.percent as u128 {
+                return Ok((&ab.backend, RouteReason::AbTest));
+            }
+        }
+        Ok((&self.default_backend, RouteReason::Default))
+    }
+
+    fn route(&self, options: &GenerationOptions) -> Result<&Arc<dyn LLMService + Send + Sync>> {
+        let (name, reason) = self.select(options)?;
+        debug!("模型路由: {} ({:?})", name, reason);
+        Ok(&self.backends[name])
+    }
+
+    fn default_service(&self) -> &Arc<dyn LLMService + Send + Sync> {
+        &self.backends[&self.default_backend]
+    }
+}
+
+/// 规则中已设置的条件是否全部满足，缺少对应依据时视为不满足
+fn rule_matches(rule: &RoutingRule, hints: &RouteHints) -> bool {
+    let category = rule.categories.is_empty()
+        || hints.category.as_ref().is_some_and(|c| rule.categories.contains(c));
+    let priority = within(hints.priority, rule.min_priority, rule.max_priority);
+    let length = within(Some(hints.ticket_chars), rule.min_ticket_chars, rule.max_ticket_chars);
+    let confidence = within(
+        hints.retrieval_confidence,
+        rule.min_retrieval_confidence,
+        rule.max_retrieval_confidence,
+    );
+    category && priority && length && confidence
+}
+
+/// 值是否落在闭区间内，未设置上下限时总是满足
+fn within<T: PartialOrd>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+    if min.is_none() && max.is_none() {
+        return true;
+    }
+    let Some(value) = value else { return false };
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+#[async_trait]
+impl LLMService for RoutingLLMService {
+    async fn generate(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        self.route(options)?.generate(prompt, options).await
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &RenderedPrompt,
+        options: &GenerationOptions,
+    ) -> Result<LLMStream> {
+        self.route(options)?.generate_stream(prompt, options).await
+    }
+
+    async fn generate_solutions_batch(
+        &self,
+        requests: &[(Ticket, Vec<RerankResult>)],
+        options: &GenerationOptions,
+    ) -> Result<Vec<LLMResponse>> {
+        self.route(options)?.generate_solutions_batch(requests, options).await
+    }
+
+    async fn chat(&self, prompt: &str, options: &GenerationOptions) -> Result<LLMResponse> {
+        self.route(options)?.chat(prompt, options).await
+    }
+
+    fn model_info(&self) -> ModelInfo {
+        let mut info = self.default_service().model_info();
+        info.max_tokens = self.backends.values()
+            .map(|backend| backend.model_info().max_tokens)
+            .min()
+            .unwrap_or(info.max_tokens);
+        info
+    }
+
+    fn token_estimator(&self) -> TokenEstimator {
+        self.backends.values()
+            .map(|backend| backend.token_estimator())
+            .reduce(|a, b| TokenEstimator {
+                cjk_tokens_per_char: a.cjk_tokens_per_char.max(b.cjk_tokens_per_char),
+                chars_per_token: a.chars_per_token.min(b.chars_per_token),
+            })
+            .unwrap_or_default()
+    }
+
+    async fn health_check(&self) -> Result<bool> {
+        for (name, backend) in &self.backends {
+            if !backend.health_check().await? {
+                warn!("模型后端不可用: {}", name);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
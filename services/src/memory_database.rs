@@ -0,0 +1,420 @@
+//! # 内存数据库服务实现模块
+//!
+//! 数据只保存在进程内，重启后丢失，适用于测试和本地演示
+
+use rag_deps::*;
+use rag_core::{
+    models::*,
+    errors::AppError,
+    traits::*,
+    traits::repository::{DatabaseStatistics, FinetuneDataFilter, MAX_PAGE_SIZE},
+};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// 内存中的各表数据
+#[derive(Default)]
+struct Tables {
+    tickets: HashMap<Uuid, Ticket>,
+    comments: HashMap<Uuid, TicketComment>,
+    attachments: HashMap<Uuid, Attachment>,
+    solutions: HashMap<Uuid, TicketSolution>,
+    feedback: Vec<Feedback>,
+    finetune_data: Vec<FinetuneData>,
+    usage_records: Vec<UsageRecord>,
+    index_events: Vec<IndexEvent>,
+    audit_log: Vec<AuditEntry>,
+}
+
+/// 内存数据库服务
+///
+/// 职责：
+/// - 在进程内保存工单、解决方案等结构化数据
+/// - 查询语义与关系数据库后端保持一致（过滤、排序、分页）
+#[derive(Default)]
+pub struct InMemoryDatabase {
+    tables: RwLock<Tables>,
+}
+
+impl InMemoryDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TicketRepository for InMemoryDatabase {
+    async fn insert_ticket(&self, ticket: &Ticket, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        if tables.tickets.contains_key(&ticket.id) {
+            return Err(AppError::Database {
+                message: format!("工单已存在: {}", ticket.id),
+            }.into());
+        }
+        tables.tickets.insert(ticket.id, ticket.clone());
+        tables.index_events.push(IndexEvent::new(ticket.id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn get_ticket(&self, id: Uuid) -> Result<Option<Ticket>> {
+        Ok(self.tables.read().unwrap().tickets.get(&id).cloned())
+    }
+
+    async fn update_ticket(&self, ticket: &Ticket, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        let stored = tables.tickets.get_mut(&ticket.id)
+            .ok_or_else(|| AppError::not_found("ticket", ticket.id))?;
+        if stored.version != ticket.version {
+            return Err(AppError::VersionConflict { expected: ticket.version, actual: stored.version }.into());
+        }
+        // 与SQL后端一致，创建时间不随更新改变
+        *stored = Ticket { created_at: stored.created_at, version: ticket.version + 1, ..ticket.clone() };
+        tables.index_events.push(IndexEvent::new(ticket.id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn delete_ticket(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        if tables.tickets.remove(&id).is_none() {
+            return Err(AppError::not_found("ticket", id).into());
+        }
+        // 对应SQL后端的级联删除
+        let Tables { comments, attachments, solutions, feedback, .. } = &mut *tables;
+        comments.retain(|_, c| c.ticket_id != id);
+        attachments.retain(|_, a| a.ticket_id != id);
+        solutions.retain(|_, s| s.ticket_id != id);
+        feedback.retain(|f| solutions.contains_key(&f.solution_id));
+        tables.index_events.push(IndexEvent::new(id, IndexEventKind::Delete));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn set_ticket_embedding(&self, id: Uuid, embedding: Option<&[f32]>) -> Result<()> {
+        if let Some(ticket) = self.tables.write().unwrap().tickets.get_mut(&id) {
+            ticket.embedding = embedding.map(<[f32]>::to_vec);
+        }
+        Ok(())
+    }
+
+    async fn scroll_tickets(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Ticket>> {
+        let tables = self.tables.read().unwrap();
+        let mut tickets: Vec<&Ticket> = tables.tickets.values()
+            .filter(|ticket| after.is_none_or(|after| ticket.id > after))
+            .collect();
+        tickets.sort_by_key(|ticket| ticket.id);
+        Ok(tickets.into_iter().take(limit).cloned().collect())
+    }
+
+    async fn list_tickets(
+        &self,
+        filter: &QueryFilter,
+        pagination: &Pagination
+    ) -> Result<PagedResult<Ticket>> {
+        let page = pagination.page.max(1);
+        let page_size = pagination.page_size.clamp(1, MAX_PAGE_SIZE);
+
+        let tables = self.tables.read().unwrap();
+        let mut tickets: Vec<&Ticket> = tables.tickets.values()
+            .filter(|ticket| filter.matches(ticket))
+            .collect();
+        tickets.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(a.id.cmp(&b.id)));
+
+        let total = tickets.len() as u64;
+        let data = tickets.into_iter()
+            .skip((page as usize - 1) * page_size as usize)
+            .take(page_size as usize)
+            .cloned()
+            .collect();
+        Ok(PagedResult::new(data, total, page, page_size))
+    }
+}
+
+#[async_trait]
+impl CommentRepository for InMemoryDatabase {
+    async fn insert_comment(&self, comment: &TicketComment, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        if !tables.tickets.contains_key(&comment.ticket_id) {
+            return Err(AppError::not_found("ticket", comment.ticket_id).into());
+        }
+        if tables.comments.contains_key(&comment.id) {
+            return Err(AppError::Database {
+                message: format!("评论已存在: {}", comment.id),
+            }.into());
+        }
+        tables.comments.insert(comment.id, comment.clone());
+        tables.index_events.push(IndexEvent::new(comment.ticket_id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn get_comment(&self, id: Uuid) -> Result<Option<TicketComment>> {
+        Ok(self.tables.read().unwrap().comments.get(&id).cloned())
+    }
+
+    async fn update_comment(&self, comment: &TicketComment, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        let stored = tables.comments.get_mut(&comment.id)
+            .ok_or_else(|| AppError::not_found("comment", comment.id))?;
+        // 与SQL后端一致，只更新正文、可见范围和更新时间
+        stored.body = comment.body.clone();
+        stored.internal = comment.internal;
+        stored.updated_at = comment.updated_at;
+        let ticket_id = stored.ticket_id;
+        tables.index_events.push(IndexEvent::new(ticket_id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn delete_comment(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        let comment = tables.comments.remove(&id)
+            .ok_or_else(|| AppError::not_found("comment", id))?;
+        tables.index_events.push(IndexEvent::new(comment.ticket_id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn list_comments(&self, ticket_id: Uuid) -> Result<Vec<TicketComment>> {
+        let tables = self.tables.read().unwrap();
+        let mut comments: Vec<TicketComment> = tables.comments.values()
+            .filter(|c| c.ticket_id == ticket_id)
+            .cloned()
+            .collect();
+        comments.sort_by_key(|c| (c.created_at, c.id));
+        Ok(comments)
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for InMemoryDatabase {
+    async fn insert_attachment(&self, attachment: &Attachment, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        if !tables.tickets.contains_key(&attachment.ticket_id) {
+            return Err(AppError::not_found("ticket", attachment.ticket_id).into());
+        }
+        if tables.attachments.contains_key(&attachment.id) {
+            return Err(AppError::Database {
+                message: format!("附件已存在: {}", attachment.id),
+            }.into());
+        }
+        tables.attachments.insert(attachment.id, attachment.clone());
+        tables.index_events.push(IndexEvent::new(attachment.ticket_id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn get_attachment(&self, id: Uuid) -> Result<Option<Attachment>> {
+        Ok(self.tables.read().unwrap().attachments.get(&id).cloned())
+    }
+
+    async fn delete_attachment(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        let attachment = tables.attachments.remove(&id)
+            .ok_or_else(|| AppError::not_found("attachment", id))?;
+        tables.index_events.push(IndexEvent::new(attachment.ticket_id, IndexEventKind::Upsert));
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn list_attachments(&self, ticket_id: Uuid) -> Result<Vec<Attachment>> {
+        let tables = self.tables.read().unwrap();
+        let mut attachments: Vec<Attachment> = tables.attachments.values()
+            .filter(|a| a.ticket_id == ticket_id)
+            .cloned()
+            .collect();
+        attachments.sort_by_key(|a| (a.created_at, a.id));
+        Ok(attachments)
+    }
+}
+
+#[async_trait]
+impl SolutionRepository for InMemoryDatabase {
+    async fn insert_solution(&self, solution: &TicketSolution) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        if !tables.tickets.contains_key(&solution.ticket_id) {
+            // 对应SQL后端的外键约束
+            return Err(AppError::Database {
+                message: format!("解决方案关联的工单不存在: {}", solution.ticket_id),
+            }.into());
+        }
+        if tables.solutions.contains_key(&solution.id) {
+            return Err(AppError::Database {
+                message: format!("解决方案已存在: {}", solution.id),
+            }.into());
+        }
+        tables.solutions.insert(solution.id, solution.clone());
+        Ok(())
+    }
+
+    async fn get_solution(&self, id: Uuid) -> Result<Option<TicketSolution>> {
+        Ok(self.tables.read().unwrap().solutions.get(&id).cloned())
+    }
+
+    async fn get_solutions_by_ticket(&self, ticket_id: Uuid) -> Result<Vec<TicketSolution>> {
+        let tables = self.tables.read().unwrap();
+        Ok(newest_first(tables.solutions.values().filter(|s| s.ticket_id == ticket_id)))
+    }
+
+    async fn list_reviewed_solutions(&self, limit: usize) -> Result<Vec<TicketSolution>> {
+        let tables = self.tables.read().unwrap();
+        let mut solutions = newest_first(
+            tables.solutions.values().filter(|s| s.is_accepted || s.feedback_score.is_some()),
+        );
+        solutions.truncate(limit);
+        Ok(solutions)
+    }
+}
+
+#[async_trait]
+impl FeedbackRepository for InMemoryDatabase {
+    async fn update_solution_feedback(
+        &self,
+        solution_id: Uuid,
+        feedback: &Feedback,
+        audit: &[AuditEntry],
+    ) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        let solution = tables.solutions.get_mut(&solution_id)
+            .ok_or_else(|| AppError::not_found("solution", solution_id))?;
+        solution.is_accepted = feedback.is_accepted;
+        solution.feedback_score = feedback.score;
+        solution.feedback_comment = feedback.comment.clone();
+        tables.feedback.push(Feedback { solution_id, ..feedback.clone() });
+        tables.audit_log.extend_from_slice(audit);
+        Ok(())
+    }
+
+    async fn list_feedback(&self, solution_id: Uuid) -> Result<Vec<Feedback>> {
+        let tables = self.tables.read().unwrap();
+        let mut feedback: Vec<Feedback> = tables.feedback.iter()
+            .filter(|f| f.solution_id == solution_id)
+            .cloned()
+            .collect();
+        feedback.sort_by_key(|f| f.created_at);
+        Ok(feedback)
+    }
+}
+
+#[async_trait]
+impl FinetuneRepository for InMemoryDatabase {
+    async fn insert_finetune_data(&self, data: &FinetuneData) -> Result<()> {
+        self.tables.write().unwrap().finetune_data.push(data.clone());
+        Ok(())
+    }
+
+    async fn export_finetune_data(
+        &self,
+        filter: &FinetuneDataFilter
+    ) -> Result<Vec<FinetuneData>> {
+        let tables = self.tables.read().unwrap();
+        let mut data: Vec<FinetuneData> = tables.finetune_data.iter()
+            .filter(|d| filter.matches(d))
+            .cloned()
+            .collect();
+        data.sort_by_key(|d| d.created_at);
+        Ok(data)
+    }
+}
+
+#[async_trait]
+impl UsageRepository for InMemoryDatabase {
+    async fn insert_usage_record(&self, record: &UsageRecord) -> Result<()> {
+        self.tables.write().unwrap().usage_records.push(record.clone());
+        Ok(())
+    }
+
+    async fn list_usage_records(&self, query: &UsageQuery) -> Result<Vec<UsageRecord>> {
+        let tables = self.tables.read().unwrap();
+        let mut records: Vec<UsageRecord> = tables.usage_records.iter()
+            .filter(|record| query.matches(record))
+            .cloned()
+            .collect();
+        records.sort_by_key(|record| record.created_at);
+        Ok(records)
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for InMemoryDatabase {
+    async fn enqueue_index_event(&self, event: &IndexEvent) -> Result<()> {
+        self.tables.write().unwrap().index_events.push(event.clone());
+        Ok(())
+    }
+
+    async fn due_index_events(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<IndexEvent>> {
+        let tables = self.tables.read().unwrap();
+        let mut events: Vec<&IndexEvent> = tables.index_events.iter()
+            .filter(|e| e.status == IndexEventStatus::Pending && e.next_attempt_at <= now)
+            .collect();
+        events.sort_by_key(|e| (e.created_at, e.id));
+        Ok(events.into_iter().take(limit).cloned().collect())
+    }
+
+    async fn update_index_event(&self, event: &IndexEvent) -> Result<()> {
+        let mut tables = self.tables.write().unwrap();
+        if let Some(stored) = tables.index_events.iter_mut().find(|e| e.id == event.id) {
+            *stored = event.clone();
+        }
+        Ok(())
+    }
+
+    async fn complete_index_event(&self, id: Uuid) -> Result<()> {
+        self.tables.write().unwrap().index_events.retain(|e| e.id != id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditRepository for InMemoryDatabase {
+    async fn append_audit_entries(&self, entries: &[AuditEntry]) -> Result<()> {
+        self.tables.write().unwrap().audit_log.extend_from_slice(entries);
+        Ok(())
+    }
+
+    async fn list_audit_entries(&self, ticket_id: Uuid) -> Result<Vec<AuditEntry>> {
+        let tables = self.tables.read().unwrap();
+        Ok(tables.audit_log.iter().filter(|e| e.ticket_id == ticket_id).cloned().collect())
+    }
+}
+
+#[async_trait]
+impl Repository for InMemoryDatabase {
+    async fn get_statistics(&self) -> Result<DatabaseStatistics> {
+        let tables = self.tables.read().unwrap();
+        let total_solutions = tables.solutions.len();
+        let average_confidence = if total_solutions == 0 {
+            0.0
+        } else {
+            tables.solutions.values().map(|s| s.confidence).sum::<f32>() / total_solutions as f32
+        };
+
+        Ok(DatabaseStatistics {
+            total_tickets: tables.tickets.len() as u64,
+            total_solutions: total_solutions as u64,
+            accepted_solutions: tables.solutions.values().filter(|s| s.is_accepted).count() as u64,
+            average_confidence,
+            finetune_data_count: tables.finetune_data.len() as u64,
+            pending_index_events: count_events(&tables.index_events, IndexEventStatus::Pending),
+            failed_index_events: count_events(&tables.index_events, IndexEventStatus::Failed),
+        })
+    }
+
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+}
+
+/// 统计指定状态的索引事件
+fn count_events(events: &[IndexEvent], status: IndexEventStatus) -> u64 {
+    events.iter().filter(|e| e.status == status).count() as u64
+}
+
+/// 按创建时间倒序排列解决方案
+fn newest_first<'a>(solutions: impl Iterator<Item = &'a TicketSolution>) -> Vec<TicketSolution> {
+    let mut solutions: Vec<TicketSolution> = solutions.cloned().collect();
+    solutions.sort_by_key(|s| Reverse(s.created_at));
+    solutions
+}
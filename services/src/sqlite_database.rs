@@ -0,0 +1,973 @@
+//! # SQLite数据库服务实现模块
+//!
+//! 适用于单机部署和本地开发，表结构由 `migrations/sqlite/` 下的版本化SQL迁移维护
+
+use rag_deps::*;
+use rag_core::{
+    models::*,
+    config::DatabaseConfig,
+    errors::AppError,
+    traits::*,
+    traits::repository::{DatabaseStatistics, FinetuneDataFilter, MAX_PAGE_SIZE},
+};
+use crate::database::{enum_text, enum_from_text, escape_like, update_conflict};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteConnection, SqlitePool, SqlitePoolOptions, SqliteRow},
+    types::Json,
+    QueryBuilder, Row, Sqlite,
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// 编译时嵌入的数据库迁移
+static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!("./migrations/sqlite");
+
+const TICKET_COLUMNS: &str =
+    "id, title, description, category, priority, status, tags, embedding, created_at, updated_at, resolution_note, version";
+
+const SOLUTION_COLUMNS: &str = "id, ticket_id, solution, confidence, reasoning, is_accepted, \
+    feedback_score, feedback_comment, prompt_version, citations, raw_confidence, guardrail, created_at";
+
+const INDEX_EVENT_COLUMNS: &str =
+    "id, ticket_id, kind, status, attempts, last_error, next_attempt_at, created_at";
+
+const AUDIT_COLUMNS: &str =
+    "id, ticket_id, solution_id, actor, action, field, old_value, new_value, created_at";
+
+const COMMENT_COLUMNS: &str =
+    "id, ticket_id, author, role, body, internal, created_at, updated_at";
+
+const ATTACHMENT_COLUMNS: &str = "id, ticket_id, filename, content_type, size_bytes, storage_key, \
+    extracted_text, error_lines, uploaded_by, created_at";
+
+/// SQLite数据库服务
+///
+/// 职责：
+/// - 以单个数据库文件保存工单、解决方案等结构化数据
+/// - 提供与PostgreSQL后端一致的CRUD操作接口
+/// - 内存数据库（`sqlite::memory:`）只保留一个连接，避免各连接看到不同的库
+pub struct SqliteDatabase {
+    pool: SqlitePool,
+}
+
+impl SqliteDatabase {
+    /// 按配置打开数据库（文件不存在时创建）并执行未应用的迁移
+    pub async fn new(config: &DatabaseConfig) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(&config.url)
+            .map_err(AppError::from)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+        let in_memory = config.url.contains(":memory:") || config.url.contains("mode=memory");
+        let pool_options = if in_memory {
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new()
+                .max_connections(config.max_connections)
+                .min_connections(config.min_connections)
+        };
+        let pool = pool_options
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout))
+            .connect_with(options)
+            .await
+            .map_err(AppError::from)?;
+        Self::create_tables(&pool).await?;
+        Ok(Self { pool })
+    }
+
+    /// 执行嵌入的版本化迁移，已应用的版本会跳过
+    async fn create_tables(pool: &SqlitePool) -> Result<()> {
+        MIGRATOR.run(pool).await.map_err(|e| AppError::Database {
+            message: format!("数据库迁移失败: {}", e),
+        })?;
+        info!("数据库迁移完成，共 {} 个版本", MIGRATOR.iter().count());
+        Ok(())
+    }
+
+    /// 连接池，供同库的其他存储使用
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl TicketRepository for SqliteDatabase {
+    /// 插入新工单，同一事务中写入索引事件
+    async fn insert_ticket(&self, ticket: &Ticket, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        sqlx::query(&format!(
+            "INSERT INTO tickets ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            TICKET_COLUMNS,
+        ))
+        .bind(ticket.id)
+        .bind(&ticket.title)
+        .bind(&ticket.description)
+        .bind(&ticket.category)
+        .bind(ticket.priority)
+        .bind(enum_text(&ticket.status))
+        .bind(Json(&ticket.tags))
+        .bind(ticket.embedding.as_ref().map(Json))
+        .bind(ticket.created_at)
+        .bind(ticket.updated_at)
+        .bind(&ticket.resolution_note)
+        .bind(ticket.version)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+        insert_index_event(&mut tx, &IndexEvent::new(ticket.id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询工单
+    async fn get_ticket(&self, id: Uuid) -> Result<Option<Ticket>> {
+        let row = sqlx::query(&format!("SELECT {} FROM tickets WHERE id = ?", TICKET_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        row.as_ref().map(ticket_from_row).transpose()
+    }
+
+    /// 按版本条件更新工单，同一事务中写入索引事件
+    async fn update_ticket(&self, ticket: &Ticket, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let result = sqlx::query(
+            "UPDATE tickets SET title = ?, description = ?, category = ?, priority = ?, status = ?, \
+             tags = ?, embedding = ?, updated_at = ?, resolution_note = ?, version = version + 1 \
+             WHERE id = ? AND version = ?",
+        )
+        .bind(&ticket.title)
+        .bind(&ticket.description)
+        .bind(&ticket.category)
+        .bind(ticket.priority)
+        .bind(enum_text(&ticket.status))
+        .bind(Json(&ticket.tags))
+        .bind(ticket.embedding.as_ref().map(Json))
+        .bind(ticket.updated_at)
+        .bind(&ticket.resolution_note)
+        .bind(ticket.id)
+        .bind(ticket.version)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+        if result.rows_affected() == 0 {
+            let current: Option<i64> = sqlx::query_scalar("SELECT version FROM tickets WHERE id = ?")
+                .bind(ticket.id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(AppError::from)?;
+            return Err(update_conflict(ticket, current).into());
+        }
+        insert_index_event(&mut tx, &IndexEvent::new(ticket.id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 删除工单，同一事务中写入索引事件
+    async fn delete_ticket(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let result = sqlx::query("DELETE FROM tickets WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("ticket", id).into());
+        }
+        insert_index_event(&mut tx, &IndexEvent::new(id, IndexEventKind::Delete)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 回写工单向量
+    async fn set_ticket_embedding(&self, id: Uuid, embedding: Option<&[f32]>) -> Result<()> {
+        sqlx::query("UPDATE tickets SET embedding = ? WHERE id = ?")
+            .bind(embedding.map(Json))
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 按ID升序分页遍历工单
+    async fn scroll_tickets(&self, after: Option<Uuid>, limit: usize) -> Result<Vec<Ticket>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM tickets WHERE ?1 IS NULL OR id > ?1 ORDER BY id LIMIT ?2",
+            TICKET_COLUMNS,
+        ))
+        .bind(after)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(ticket_from_row).collect()
+    }
+
+    /// 查询工单列表，按创建时间倒序
+    async fn list_tickets(
+        &self,
+        filter: &QueryFilter,
+        pagination: &Pagination
+    ) -> Result<PagedResult<Ticket>> {
+        let page = pagination.page.max(1);
+        let page_size = pagination.page_size.clamp(1, MAX_PAGE_SIZE);
+
+        let mut count = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM tickets");
+        push_ticket_filter(&mut count, filter);
+        let total: i64 = count.build_query_scalar()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+
+        let mut query = QueryBuilder::<Sqlite>::new(format!("SELECT {} FROM tickets", TICKET_COLUMNS));
+        push_ticket_filter(&mut query, filter);
+        query.push(" ORDER BY created_at DESC, id LIMIT ")
+            .push_bind(page_size as i64)
+            .push(" OFFSET ")
+            .push_bind((page as i64 - 1) * page_size as i64);
+        let rows = query.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        let tickets = rows.iter().map(ticket_from_row).collect::<Result<Vec<_>>>()?;
+
+        Ok(PagedResult::new(tickets, total as u64, page, page_size))
+    }
+}
+
+#[async_trait]
+impl CommentRepository for SqliteDatabase {
+    /// 插入评论，同一事务中写入工单的索引事件
+    async fn insert_comment(&self, comment: &TicketComment, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let result = sqlx::query(&format!(
+            "INSERT INTO ticket_comments ({}) SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8 \
+             WHERE EXISTS (SELECT 1 FROM tickets WHERE id = ?2)",
+            COMMENT_COLUMNS,
+        ))
+        .bind(comment.id)
+        .bind(comment.ticket_id)
+        .bind(&comment.author)
+        .bind(enum_text(&comment.role))
+        .bind(&comment.body)
+        .bind(comment.internal)
+        .bind(comment.created_at)
+        .bind(comment.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("ticket", comment.ticket_id).into());
+        }
+        insert_index_event(&mut tx, &IndexEvent::new(comment.ticket_id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询评论
+    async fn get_comment(&self, id: Uuid) -> Result<Option<TicketComment>> {
+        let row = sqlx::query(&format!("SELECT {} FROM ticket_comments WHERE id = ?", COMMENT_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        row.as_ref().map(comment_from_row).transpose()
+    }
+
+    /// 更新评论，同一事务中写入工单的索引事件
+    async fn update_comment(&self, comment: &TicketComment, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let result = sqlx::query(
+            "UPDATE ticket_comments SET body = ?, internal = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(&comment.body)
+        .bind(comment.internal)
+        .bind(comment.updated_at)
+        .bind(comment.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("comment", comment.id).into());
+        }
+        insert_index_event(&mut tx, &IndexEvent::new(comment.ticket_id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 删除评论，同一事务中写入工单的索引事件
+    async fn delete_comment(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let ticket_id: Option<Uuid> = sqlx::query_scalar("DELETE FROM ticket_comments WHERE id = ? RETURNING ticket_id")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+        let ticket_id = ticket_id.ok_or_else(|| AppError::not_found("comment", id))?;
+        insert_index_event(&mut tx, &IndexEvent::new(ticket_id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询工单的评论，按创建时间正序
+    async fn list_comments(&self, ticket_id: Uuid) -> Result<Vec<TicketComment>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM ticket_comments WHERE ticket_id = ? ORDER BY created_at, id",
+            COMMENT_COLUMNS,
+        ))
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(comment_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl AttachmentRepository for SqliteDatabase {
+    /// 插入附件，同一事务中写入工单的索引事件
+    async fn insert_attachment(&self, attachment: &Attachment, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let result = sqlx::query(&format!(
+            "INSERT INTO ticket_attachments ({}) SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10 \
+             WHERE EXISTS (SELECT 1 FROM tickets WHERE id = ?2)",
+            ATTACHMENT_COLUMNS,
+        ))
+        .bind(attachment.id)
+        .bind(attachment.ticket_id)
+        .bind(&attachment.filename)
+        .bind(&attachment.content_type)
+        .bind(attachment.size_bytes)
+        .bind(&attachment.storage_key)
+        .bind(&attachment.extracted_text)
+        .bind(Json(&attachment.error_lines))
+        .bind(&attachment.uploaded_by)
+        .bind(attachment.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("ticket", attachment.ticket_id).into());
+        }
+        insert_index_event(&mut tx, &IndexEvent::new(attachment.ticket_id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询附件
+    async fn get_attachment(&self, id: Uuid) -> Result<Option<Attachment>> {
+        let row = sqlx::query(&format!("SELECT {} FROM ticket_attachments WHERE id = ?", ATTACHMENT_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        row.as_ref().map(attachment_from_row).transpose()
+    }
+
+    /// 删除附件，同一事务中写入工单的索引事件
+    async fn delete_attachment(&self, id: Uuid, audit: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let ticket_id: Option<Uuid> = sqlx::query_scalar("DELETE FROM ticket_attachments WHERE id = ? RETURNING ticket_id")
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+        let ticket_id = ticket_id.ok_or_else(|| AppError::not_found("attachment", id))?;
+        insert_index_event(&mut tx, &IndexEvent::new(ticket_id, IndexEventKind::Upsert)).await?;
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询工单的附件，按上传时间正序
+    async fn list_attachments(&self, ticket_id: Uuid) -> Result<Vec<Attachment>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM ticket_attachments WHERE ticket_id = ? ORDER BY created_at, id",
+            ATTACHMENT_COLUMNS,
+        ))
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(attachment_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl SolutionRepository for SqliteDatabase {
+    /// 插入解决方案
+    async fn insert_solution(&self, solution: &TicketSolution) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO solutions ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            SOLUTION_COLUMNS,
+        ))
+        .bind(solution.id)
+        .bind(solution.ticket_id)
+        .bind(&solution.solution)
+        .bind(solution.confidence)
+        .bind(&solution.reasoning)
+        .bind(solution.is_accepted)
+        .bind(solution.feedback_score)
+        .bind(&solution.feedback_comment)
+        .bind(&solution.prompt_version)
+        .bind(Json(&solution.citations))
+        .bind(solution.raw_confidence)
+        .bind(solution.guardrail.as_ref().map(Json))
+        .bind(solution.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询解决方案
+    async fn get_solution(&self, id: Uuid) -> Result<Option<TicketSolution>> {
+        let row = sqlx::query(&format!("SELECT {} FROM solutions WHERE id = ?", SOLUTION_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        row.as_ref().map(solution_from_row).transpose()
+    }
+
+    /// 查询工单的解决方案，按创建时间倒序
+    async fn get_solutions_by_ticket(&self, ticket_id: Uuid) -> Result<Vec<TicketSolution>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM solutions WHERE ticket_id = ? ORDER BY created_at DESC",
+            SOLUTION_COLUMNS,
+        ))
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(solution_from_row).collect()
+    }
+
+    /// 一次查询所有候选工单的解决方案
+    async fn solutions_for_tickets(
+        &self,
+        ticket_ids: &[Uuid]
+    ) -> Result<HashMap<Uuid, Vec<TicketSolution>>> {
+        let mut solutions: HashMap<Uuid, Vec<TicketSolution>> =
+            ticket_ids.iter().map(|id| (*id, Vec::new())).collect();
+        if ticket_ids.is_empty() {
+            return Ok(solutions);
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(format!(
+            "SELECT {} FROM solutions WHERE ticket_id IN (",
+            SOLUTION_COLUMNS,
+        ));
+        let mut ids = query.separated(", ");
+        for id in ticket_ids {
+            ids.push_bind(*id);
+        }
+        query.push(") ORDER BY created_at DESC");
+        let rows = query.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+
+        for row in &rows {
+            let solution = solution_from_row(row)?;
+            solutions.entry(solution.ticket_id).or_default().push(solution);
+        }
+        Ok(solutions)
+    }
+
+    /// 查询最近有反馈的解决方案（已采纳或已评分），按创建时间倒序
+    async fn list_reviewed_solutions(&self, limit: usize) -> Result<Vec<TicketSolution>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM solutions WHERE is_accepted OR feedback_score IS NOT NULL \
+             ORDER BY created_at DESC LIMIT ?",
+            SOLUTION_COLUMNS,
+        ))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(solution_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl FeedbackRepository for SqliteDatabase {
+    /// 更新解决方案反馈，同时保留一条反馈记录
+    async fn update_solution_feedback(
+        &self,
+        solution_id: Uuid,
+        feedback: &Feedback,
+        audit: &[AuditEntry],
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        let result = sqlx::query(
+            "UPDATE solutions SET is_accepted = ?, feedback_score = ?, feedback_comment = ? WHERE id = ?",
+        )
+        .bind(feedback.is_accepted)
+        .bind(feedback.score)
+        .bind(&feedback.comment)
+        .bind(solution_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::not_found("solution", solution_id).into());
+        }
+
+        sqlx::query(
+            "INSERT INTO feedback (id, solution_id, is_accepted, score, comment, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(solution_id)
+        .bind(feedback.is_accepted)
+        .bind(feedback.score)
+        .bind(&feedback.comment)
+        .bind(feedback.created_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+
+        insert_audit_entries(&mut tx, audit).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询解决方案的反馈记录
+    async fn list_feedback(&self, solution_id: Uuid) -> Result<Vec<Feedback>> {
+        let rows = sqlx::query(
+            "SELECT solution_id, is_accepted, score, comment, created_at FROM feedback \
+             WHERE solution_id = ? ORDER BY created_at",
+        )
+        .bind(solution_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter()
+            .map(|row| Ok(Feedback {
+                solution_id: row.try_get("solution_id")?,
+                is_accepted: row.try_get("is_accepted")?,
+                score: row.try_get("score")?,
+                comment: row.try_get("comment")?,
+                created_at: row.try_get("created_at")?,
+            }))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl FinetuneRepository for SqliteDatabase {
+    /// 插入微调数据
+    async fn insert_finetune_data(&self, data: &FinetuneData) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO finetune_data (id, input_text, target_output, data_source, quality_score, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(data.id)
+        .bind(&data.input_text)
+        .bind(&data.target_output)
+        .bind(enum_text(&data.data_source))
+        .bind(data.quality_score)
+        .bind(data.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 导出微调数据，按创建时间排序
+    async fn export_finetune_data(
+        &self,
+        filter: &FinetuneDataFilter
+    ) -> Result<Vec<FinetuneData>> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT id, input_text, target_output, data_source, quality_score, created_at FROM finetune_data WHERE 1",
+        );
+        if let Some(min) = filter.quality_score_min {
+            query.push(" AND quality_score >= ").push_bind(min);
+        }
+        if let Some(source) = &filter.data_source {
+            query.push(" AND data_source = ").push_bind(enum_text(source));
+        }
+        if let Some(from) = filter.date_from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = filter.date_to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+        query.push(" ORDER BY created_at");
+
+        let rows = query.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        rows.iter()
+            .map(|row| Ok(FinetuneData {
+                id: row.try_get("id")?,
+                input_text: row.try_get("input_text")?,
+                target_output: row.try_get("target_output")?,
+                data_source: enum_from_text(row.try_get("data_source")?)?,
+                quality_score: row.try_get("quality_score")?,
+                created_at: row.try_get("created_at")?,
+            }))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl UsageRepository for SqliteDatabase {
+    /// 插入模型调用用量记录
+    async fn insert_usage_record(&self, record: &UsageRecord) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO usage_records (id, ticket_id, category, kind, provider, model, prompt_tokens, \
+             completion_tokens, total_tokens, estimated, latency_ms, cost, success, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(record.id)
+        .bind(record.ticket_id)
+        .bind(&record.category)
+        .bind(enum_text(&record.kind))
+        .bind(&record.provider)
+        .bind(&record.model)
+        .bind(record.prompt_tokens)
+        .bind(record.completion_tokens)
+        .bind(record.total_tokens)
+        .bind(record.estimated)
+        .bind(record.latency_ms as i64)
+        .bind(record.cost)
+        .bind(record.success)
+        .bind(record.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 按条件查询用量记录，按时间排序
+    async fn list_usage_records(&self, query: &UsageQuery) -> Result<Vec<UsageRecord>> {
+        let mut sql = QueryBuilder::<Sqlite>::new("SELECT * FROM usage_records WHERE 1");
+        if let Some(from) = query.from {
+            sql.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = query.to {
+            sql.push(" AND created_at < ").push_bind(to);
+        }
+        if let Some(ticket_id) = query.ticket_id {
+            sql.push(" AND ticket_id = ").push_bind(ticket_id);
+        }
+        if let Some(category) = &query.category {
+            sql.push(" AND category = ").push_bind(category.clone());
+        }
+        if let Some(kind) = &query.kind {
+            sql.push(" AND kind = ").push_bind(enum_text(kind));
+        }
+        if let Some(model) = &query.model {
+            sql.push(" AND model = ").push_bind(model.clone());
+        }
+        sql.push(" ORDER BY created_at");
+
+        let rows = sql.build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        rows.iter()
+            .map(|row| Ok(UsageRecord {
+                id: row.try_get("id")?,
+                ticket_id: row.try_get("ticket_id")?,
+                category: row.try_get("category")?,
+                kind: enum_from_text(row.try_get("kind")?)?,
+                provider: row.try_get("provider")?,
+                model: row.try_get("model")?,
+                prompt_tokens: row.try_get("prompt_tokens")?,
+                completion_tokens: row.try_get("completion_tokens")?,
+                total_tokens: row.try_get("total_tokens")?,
+                estimated: row.try_get("estimated")?,
+                latency_ms: row.try_get::<i64, _>("latency_ms")? as u64,
+                cost: row.try_get("cost")?,
+                success: row.try_get("success")?,
+                created_at: row.try_get("created_at")?,
+            }))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for SqliteDatabase {
+    /// 写入索引事件
+    async fn enqueue_index_event(&self, event: &IndexEvent) -> Result<()> {
+        let mut conn = self.pool.acquire().await.map_err(AppError::from)?;
+        insert_index_event(&mut conn, event).await
+    }
+
+    /// 查询到期的待处理事件
+    async fn due_index_events(&self, now: DateTime<Utc>, limit: usize) -> Result<Vec<IndexEvent>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM index_events WHERE status = 'pending' AND next_attempt_at <= ? \
+             ORDER BY created_at, id LIMIT ?",
+            INDEX_EVENT_COLUMNS,
+        ))
+        .bind(now)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(index_event_from_row).collect()
+    }
+
+    /// 保存事件的重试状态
+    async fn update_index_event(&self, event: &IndexEvent) -> Result<()> {
+        sqlx::query(
+            "UPDATE index_events SET status = ?, attempts = ?, last_error = ?, next_attempt_at = ? WHERE id = ?",
+        )
+        .bind(enum_text(&event.status))
+        .bind(event.attempts)
+        .bind(&event.last_error)
+        .bind(event.next_attempt_at)
+        .bind(event.id)
+        .execute(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 删除已处理的事件
+    async fn complete_index_event(&self, id: Uuid) -> Result<()> {
+        sqlx::query("DELETE FROM index_events WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(AppError::from)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AuditRepository for SqliteDatabase {
+    /// 在同一事务中追加审计记录
+    async fn append_audit_entries(&self, entries: &[AuditEntry]) -> Result<()> {
+        let mut tx = self.pool.begin().await.map_err(AppError::from)?;
+        insert_audit_entries(&mut tx, entries).await?;
+        tx.commit().await.map_err(AppError::from)?;
+        Ok(())
+    }
+
+    /// 查询工单的审计记录，按追加顺序
+    async fn list_audit_entries(&self, ticket_id: Uuid) -> Result<Vec<AuditEntry>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM audit_log WHERE ticket_id = ? ORDER BY seq",
+            AUDIT_COLUMNS,
+        ))
+        .bind(ticket_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+        rows.iter().map(audit_entry_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl Repository for SqliteDatabase {
+    /// 获取统计信息
+    async fn get_statistics(&self) -> Result<DatabaseStatistics> {
+        let row = sqlx::query(
+            "SELECT \
+                (SELECT COUNT(*) FROM tickets) AS total_tickets, \
+                (SELECT COUNT(*) FROM solutions) AS total_solutions, \
+                (SELECT COUNT(*) FROM solutions WHERE is_accepted) AS accepted_solutions, \
+                (SELECT COALESCE(AVG(confidence), 0.0) FROM solutions) AS average_confidence, \
+                (SELECT COUNT(*) FROM finetune_data) AS finetune_data_count, \
+                (SELECT COUNT(*) FROM index_events WHERE status = 'pending') AS pending_index_events, \
+                (SELECT COUNT(*) FROM index_events WHERE status = 'failed') AS failed_index_events",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::from)?;
+
+        Ok(DatabaseStatistics {
+            total_tickets: row.try_get::<i64, _>("total_tickets")? as u64,
+            total_solutions: row.try_get::<i64, _>("total_solutions")? as u64,
+            accepted_solutions: row.try_get::<i64, _>("accepted_solutions")? as u64,
+            average_confidence: row.try_get::<f64, _>("average_confidence")? as f32,
+            finetune_data_count: row.try_get::<i64, _>("finetune_data_count")? as u64,
+            pending_index_events: row.try_get::<i64, _>("pending_index_events")? as u64,
+            failed_index_events: row.try_get::<i64, _>("failed_index_events")? as u64,
+        })
+    }
+
+    fn backend(&self) -> &'static str {
+        "sqlite"
+    }
+}
+
+/// 在调用方的事务中写入审计记录，与对应变更一同提交或回滚
+async fn insert_audit_entries(conn: &mut SqliteConnection, entries: &[AuditEntry]) -> Result<()> {
+    for entry in entries {
+        sqlx::query(&format!("INSERT INTO audit_log ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)", AUDIT_COLUMNS))
+            .bind(entry.id)
+            .bind(entry.ticket_id)
+            .bind(entry.solution_id)
+            .bind(&entry.actor)
+            .bind(enum_text(&entry.action))
+            .bind(&entry.field)
+            .bind(entry.old_value.as_ref().map(Json))
+            .bind(entry.new_value.as_ref().map(Json))
+            .bind(entry.created_at)
+            .execute(&mut *conn)
+            .await
+            .map_err(AppError::from)?;
+    }
+    Ok(())
+}
+
+/// 写入索引事件，与工单变更共用事务
+async fn insert_index_event(conn: &mut SqliteConnection, event: &IndexEvent) -> Result<()> {
+    sqlx::query(&format!(
+        "INSERT INTO index_events ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        INDEX_EVENT_COLUMNS,
+    ))
+    .bind(event.id)
+    .bind(event.ticket_id)
+    .bind(enum_text(&event.kind))
+    .bind(enum_text(&event.status))
+    .bind(event.attempts)
+    .bind(&event.last_error)
+    .bind(event.next_attempt_at)
+    .bind(event.created_at)
+    .execute(conn)
+    .await
+    .map_err(AppError::from)?;
+    Ok(())
+}
+
+/// 追加工单查询条件
+fn push_ticket_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &QueryFilter) {
+    query.push(" WHERE 1");
+    if let Some(category) = &filter.category {
+        query.push(" AND category = ").push_bind(category.clone());
+    }
+    if let Some(status) = &filter.status {
+        query.push(" AND LOWER(status) = LOWER(").push_bind(status.clone()).push(")");
+    }
+    if let Some(min) = filter.priority_min {
+        query.push(" AND priority >= ").push_bind(min);
+    }
+    if let Some(max) = filter.priority_max {
+        query.push(" AND priority <= ").push_bind(max);
+    }
+    if let Some(from) = filter.date_from {
+        query.push(" AND created_at >= ").push_bind(from);
+    }
+    if let Some(to) = filter.date_to {
+        query.push(" AND created_at < ").push_bind(to);
+    }
+    // SQLite的LIKE只对ASCII不区分大小写，与其他后端保持一致需先统一转小写
+    for keyword in filter.keywords.iter().flat_map(|k| k.split_whitespace()) {
+        let pattern = format!("%{}%", escape_like(&keyword.to_lowercase()));
+        query.push(" AND (LOWER(title) LIKE ").push_bind(pattern.clone())
+            .push(" ESCAPE '\\' OR LOWER(description) LIKE ").push_bind(pattern)
+            .push(" ESCAPE '\\')");
+    }
+}
+
+fn ticket_from_row(row: &SqliteRow) -> Result<Ticket> {
+    Ok(Ticket {
+        id: row.try_get("id")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        category: row.try_get("category")?,
+        priority: row.try_get("priority")?,
+        status: enum_from_text(row.try_get("status")?)?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        embedding: row.try_get::<Option<Json<Vec<f32>>>, _>("embedding")?.map(|e| e.0),
+        tags: row.try_get::<Json<Vec<String>>, _>("tags")?.0,
+        resolution_note: row.try_get("resolution_note")?,
+        version: row.try_get("version")?,
+    })
+}
+
+fn index_event_from_row(row: &SqliteRow) -> Result<IndexEvent> {
+    Ok(IndexEvent {
+        id: row.try_get("id")?,
+        ticket_id: row.try_get("ticket_id")?,
+        kind: enum_from_text(row.try_get("kind")?)?,
+        status: enum_from_text(row.try_get("status")?)?,
+        attempts: row.try_get("attempts")?,
+        last_error: row.try_get("last_error")?,
+        next_attempt_at: row.try_get("next_attempt_at")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn comment_from_row(row: &SqliteRow) -> Result<TicketComment> {
+    Ok(TicketComment {
+        id: row.try_get("id")?,
+        ticket_id: row.try_get("ticket_id")?,
+        author: row.try_get("author")?,
+        role: enum_from_text(row.try_get("role")?)?,
+        body: row.try_get("body")?,
+        internal: row.try_get("internal")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn attachment_from_row(row: &SqliteRow) -> Result<Attachment> {
+    Ok(Attachment {
+        id: row.try_get("id")?,
+        ticket_id: row.try_get("ticket_id")?,
+        filename: row.try_get("filename")?,
+        content_type: row.try_get("content_type")?,
+        size_bytes: row.try_get("size_bytes")?,
+        storage_key: row.try_get("storage_key")?,
+        extracted_text: row.try_get("extracted_text")?,
+        error_lines: row.try_get::<Json<Vec<String>>, _>("error_lines")?.0,
+        uploaded_by: row.try_get("uploaded_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn audit_entry_from_row(row: &SqliteRow) -> Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.try_get("id")?,
+        ticket_id: row.try_get("ticket_id")?,
+        solution_id: row.try_get("solution_id")?,
+        actor: row.try_get("actor")?,
+        action: enum_from_text(row.try_get("action")?)?,
+        field: row.try_get("field")?,
+        old_value: row.try_get::<Option<Json<serde_json::Value>>, _>("old_value")?.map(|v| v.0),
+        new_value: row.try_get::<Option<Json<serde_json::Value>>, _>("new_value")?.map(|v| v.0),
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+fn solution_from_row(row: &SqliteRow) -> Result<TicketSolution> {
+    Ok(TicketSolution {
+        id: row.try_get("id")?,
+        ticket_id: row.try_get("ticket_id")?,
+        solution: row.try_get("solution")?,
+        confidence: row.try_get("confidence")?,
+        reasoning: row.try_get("reasoning")?,
+        is_accepted: row.try_get("is_accepted")?,
+        created_at: row.try_get("created_at")?,
+        feedback_score: row.try_get("feedback_score")?,
+        feedback_comment: row.try_get("feedback_comment")?,
+        prompt_version: row.try_get("prompt_version")?,
+        citations: row.try_get::<Json<Vec<Citation>>, _>("citations")?.0,
+        raw_confidence: row.try_get("raw_confidence")?,
+        guardrail: row.try_get::<Option<Json<GuardrailReport>>, _>("guardrail")?.map(|g| g.0),
+    })
+}
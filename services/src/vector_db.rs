@@ -3,6 +3,7 @@
 //! 提供不同向量数据库的具体实现
 
 use rag_deps::*;
+use rag_core::errors::AppError;
 use rag_core::traits::{
     VectorDatabase, 
     vector_db::{VectorRecord, VectorMetadata, SearchResult, VectorFilter, DatabaseStats, DatabaseInfo}
@@ -13,6 +14,7 @@ use rag_core::traits::{
 /// 职责：
 /// - 使用SQLite + vec0扩展实现向量存储
 /// - 适用于开发和小规模部署
+/// - 不支持原生混合检索，由工厂套上内存倒排索引（`KeywordIndexedVectorDatabase`）
 pub struct SqliteVectorDB {
     // connection_pool: SqlitePool,
     dimension: usize,
@@ -51,17 +53,6 @@ impl VectorDatabase for SqliteVectorDB {
         todo!("实现SQLite向量搜索")
     }
     
-    async fn hybrid_search(
-        &self, 
-        query_vector: &[f32], 
-        keywords: &[String], 
-        limit: usize, 
-        filter: Option<VectorFilter>
-    ) -> Result<Vec<SearchResult>> {
-        // TODO: 实现混合搜索
-        todo!("实现SQLite混合搜索")
-    }
-    
     async fn delete(&self, id: Uuid) -> Result<()> {
         // TODO: 实现向量删除
         todo!("实现SQLite向量删除")
@@ -77,6 +68,13 @@ impl VectorDatabase for SqliteVectorDB {
         todo!("实现SQLite向量查询")
     }
     
+    async fn scroll(&self, _after: Option<Uuid>, _limit: usize) -> Result<Vec<Uuid>> {
+        // TODO: 实现按ID分页遍历
+        Err(AppError::VectorDatabase {
+            message: "SQLite向量库暂不支持按ID遍历".to_string(),
+        }.into())
+    }
+    
     async fn stats(&self) -> Result<DatabaseStats> {
         // TODO: 实现统计信息
         todo!("实现SQLite统计信息")
@@ -168,6 +166,13 @@ impl VectorDatabase for QdrantVectorDB {
         todo!("实现Qdrant向量查询")
     }
     
+    async fn scroll(&self, _after: Option<Uuid>, _limit: usize) -> Result<Vec<Uuid>> {
+        // TODO: 实现按ID分页遍历
+        Err(AppError::VectorDatabase {
+            message: "Qdrant向量库暂不支持按ID遍历".to_string(),
+        }.into())
+    }
+    
     async fn stats(&self) -> Result<DatabaseStats> {
         // TODO: 实现统计信息
         todo!("实现Qdrant统计信息")
@@ -259,6 +264,13 @@ impl VectorDatabase for PostgresVectorDB {
         todo!("实现PostgreSQL向量查询")
     }
     
+    async fn scroll(&self, _after: Option<Uuid>, _limit: usize) -> Result<Vec<Uuid>> {
+        // TODO: 实现按ID分页遍历
+        Err(AppError::VectorDatabase {
+            message: "PostgreSQL向量库暂不支持按ID遍历".to_string(),
+        }.into())
+    }
+    
     async fn stats(&self) -> Result<DatabaseStats> {
         // TODO: 实现统计信息
         todo!("实现PostgreSQL统计信息")
@@ -0,0 +1,92 @@
+//! # 病毒扫描实现模块
+//!
+//! 提供不扫描的默认实现和基于clamd的扫描实现
+
+use rag_deps::*;
+use rag_core::{errors::AppError, models::ScanVerdict, traits::VirusScanner};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use std::time::Duration;
+
+/// clamd INSTREAM单个数据块大小，需小于clamd的StreamMaxLength
+const CLAMD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// clamd扫描超时时间
+const CLAMD_TIMEOUT_SECS: u64 = 60;
+
+/// 不扫描，所有附件视为安全
+pub struct NoopVirusScanner;
+
+#[async_trait]
+impl VirusScanner for NoopVirusScanner {
+    async fn scan(&self, _filename: &str, _data: &[u8]) -> Result<ScanVerdict> {
+        Ok(ScanVerdict::Clean)
+    }
+
+    fn name(&self) -> &'static str {
+        "none"
+    }
+}
+
+/// clamd病毒扫描
+///
+/// 职责：
+/// - 通过TCP连接clamd，以INSTREAM命令分块发送附件内容
+/// - 解析扫描结果，clamd返回错误时扫描失败
+pub struct ClamdScanner {
+    address: String,
+}
+
+impl ClamdScanner {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self { address: address.into() }
+    }
+
+    async fn instream(&self, data: &[u8]) -> Result<String> {
+        let mut stream = TcpStream::connect(&self.address).await.map_err(scan_error)?;
+        stream.write_all(b"zINSTREAM\0").await.map_err(scan_error)?;
+        for chunk in data.chunks(CLAMD_CHUNK_SIZE) {
+            stream.write_all(&(chunk.len() as u32).to_be_bytes()).await.map_err(scan_error)?;
+            stream.write_all(chunk).await.map_err(scan_error)?;
+        }
+        stream.write_all(&0u32.to_be_bytes()).await.map_err(scan_error)?;
+
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await.map_err(scan_error)?;
+        Ok(String::from_utf8_lossy(&reply).trim_end_matches(['\0', '\n']).to_string())
+    }
+}
+
+#[async_trait]
+impl VirusScanner for ClamdScanner {
+    async fn scan(&self, filename: &str, data: &[u8]) -> Result<ScanVerdict> {
+        let reply = tokio::time::timeout(Duration::from_secs(CLAMD_TIMEOUT_SECS), self.instream(data))
+            .await
+            .map_err(|_| AppError::Storage { message: format!("clamd扫描 {} 超时", filename) })??;
+        parse_clamd_reply(&reply)
+    }
+
+    fn name(&self) -> &'static str {
+        "clamd"
+    }
+}
+
+/// 解析clamd回复，如 `stream: OK`、`stream: Eicar-Signature FOUND`
+fn parse_clamd_reply(reply: &str) -> Result<ScanVerdict> {
+    let result = reply.split_once(": ").map_or(reply, |(_, result)| result);
+    if result == "OK" {
+        return Ok(ScanVerdict::Clean);
+    }
+    if let Some(signature) = result.strip_suffix(" FOUND") {
+        return Ok(ScanVerdict::Infected { signature: signature.to_string() });
+    }
+    Err(AppError::Storage {
+        message: format!("clamd扫描失败: {}", reply),
+    }.into())
+}
+
+fn scan_error(err: std::io::Error) -> AppError {
+    AppError::Storage {
+        message: format!("clamd连接失败: {}", err),
+    }
+}
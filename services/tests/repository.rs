@@ -0,0 +1,605 @@
+//! # 数据存储共享测试
+//!
+//! 同一组用例依次在内存、SQLite和PostgreSQL后端上运行，保证各后端行为一致。
+//! PostgreSQL用例需设置 `TEST_DATABASE_URL`，未设置时跳过
+
+use rag_deps::*;
+use rag_core::{
+    config::DatabaseConfig,
+    errors::AppError,
+    models::*,
+    traits::*,
+    traits::repository::FinetuneDataFilter,
+};
+use rag_services::{
+    database::PostgresDatabase,
+    memory_database::InMemoryDatabase,
+    sqlite_database::SqliteDatabase,
+};
+
+fn config(url: &str) -> DatabaseConfig {
+    DatabaseConfig {
+        url: url.to_string(),
+        max_connections: 4,
+        min_connections: 1,
+        acquire_timeout: 10,
+    }
+}
+
+/// 整秒时间，避免各后端时间精度不同
+fn at(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap()
+}
+
+/// 每次运行使用随机的时间基准和分类，共享的PostgreSQL库中互不干扰
+fn base_time() -> i64 {
+    1_000_000_000 + (Uuid::new_v4().as_u128() % 100_000_000) as i64 * 60
+}
+
+fn filter(category: &str) -> QueryFilter {
+    QueryFilter {
+        category: Some(category.to_string()),
+        status: None,
+        priority_min: None,
+        priority_max: None,
+        date_from: None,
+        date_to: None,
+        keywords: None,
+    }
+}
+
+fn ticket(category: &str, title: &str, priority: i32, created_at: DateTime<Utc>) -> Ticket {
+    let mut ticket = Ticket::new(NewTicket {
+        title: title.to_string(),
+        description: format!("{} 的详细描述 50%_off", title),
+        category: category.to_string(),
+        priority,
+        tags: vec!["数据库".to_string(), "p1".to_string()],
+    });
+    ticket.created_at = created_at;
+    ticket.updated_at = created_at;
+    ticket
+}
+
+fn solution(ticket_id: Uuid, text: &str, confidence: f32, created_at: DateTime<Utc>) -> TicketSolution {
+    let mut solution = TicketSolution::new(
+        ticket_id,
+        text.to_string(),
+        confidence,
+        "参考历史工单".to_string(),
+        Some("solution@v1".to_string()),
+    );
+    solution.created_at = created_at;
+    solution
+}
+
+fn usage(ticket_id: Uuid, kind: UsageKind, model: &str, created_at: DateTime<Utc>) -> UsageRecord {
+    UsageRecord {
+        id: Uuid::new_v4(),
+        ticket_id: Some(ticket_id),
+        category: Some("network".to_string()),
+        kind,
+        provider: "qwen".to_string(),
+        model: model.to_string(),
+        prompt_tokens: 120,
+        completion_tokens: 30,
+        total_tokens: 150,
+        estimated: false,
+        latency_ms: 850,
+        cost: 0.0125,
+        success: true,
+        created_at,
+    }
+}
+
+fn is_not_found(error: &AnyhowError) -> bool {
+    matches!(error.downcast_ref::<AppError>(), Some(AppError::NotFound { .. }))
+}
+
+fn json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+async fn ticket_cases(repo: &dyn Repository, category: &str, base: i64) -> Vec<Ticket> {
+    let tickets = vec![
+        ticket(category, "磁盘空间不足", 4, at(base)),
+        ticket(category, "Disk IO 告警", 2, at(base + 60)),
+        ticket(category, "网络抖动", 3, at(base + 120)),
+    ];
+    for t in &tickets {
+        repo.insert_ticket(t, &[]).await.unwrap();
+    }
+    assert!(repo.insert_ticket(&tickets[0], &[]).await.is_err(), "重复ID应插入失败");
+
+    let mut stored = repo.get_ticket(tickets[0].id).await.unwrap().expect("工单应存在");
+    assert_eq!(json(&stored), json(&tickets[0]));
+    assert!(repo.get_ticket(Uuid::new_v4()).await.unwrap().is_none());
+
+    stored.status = TicketStatus::Resolved;
+    stored.title = "磁盘空间不足（已扩容）".to_string();
+    stored.embedding = Some(vec![0.25, -1.5, 3.0]);
+    stored.resolution_note = Some("已扩容磁盘".to_string());
+    stored.updated_at = at(base + 300);
+    repo.update_ticket(&stored, &[]).await.unwrap();
+    let updated = repo.get_ticket(stored.id).await.unwrap().unwrap();
+    assert_eq!(updated.version, stored.version + 1, "更新后版本应加一");
+    assert_eq!(json(&updated), json(&Ticket { version: updated.version, ..stored.clone() }));
+
+    stored.title = "基于旧版本的修改".to_string();
+    let conflict = AppError::from(repo.update_ticket(&stored, &[]).await.unwrap_err());
+    assert!(
+        matches!(conflict, AppError::VersionConflict { expected, actual } if expected == stored.version && actual == updated.version),
+        "旧版本更新应返回版本冲突: {:?}", conflict,
+    );
+    assert_eq!(repo.get_ticket(stored.id).await.unwrap().unwrap().title, updated.title, "冲突的更新不应写入");
+
+    repo.set_ticket_embedding(tickets[1].id, Some(&[1.0, 2.0])).await.unwrap();
+    let indexed = repo.get_ticket(tickets[1].id).await.unwrap().unwrap();
+    assert_eq!(indexed.embedding, Some(vec![1.0, 2.0]));
+    assert_eq!(indexed.updated_at, tickets[1].updated_at, "回写向量不应改变更新时间");
+    repo.set_ticket_embedding(tickets[1].id, None).await.unwrap();
+    assert!(repo.get_ticket(tickets[1].id).await.unwrap().unwrap().embedding.is_none());
+
+    let mut ids: Vec<Uuid> = tickets.iter().map(|t| t.id).collect();
+    ids.sort();
+    let scrolled: Vec<Uuid> = repo.scroll_tickets(Some(ids[0]), 100_000).await.unwrap()
+        .iter()
+        .map(|t| t.id)
+        .collect();
+    assert!(scrolled.windows(2).all(|w| w[0] < w[1]), "应按ID升序");
+    assert!(!scrolled.contains(&ids[0]) && scrolled.contains(&ids[1]) && scrolled.contains(&ids[2]));
+    assert_eq!(repo.scroll_tickets(None, 2).await.unwrap().len(), 2);
+
+    let missing = ticket(category, "不存在", 1, at(base));
+    assert!(is_not_found(&repo.update_ticket(&missing, &[]).await.unwrap_err()));
+
+    let all = repo.list_tickets(&filter(category), &Pagination { page: 1, page_size: 10 }).await.unwrap();
+    assert_eq!(all.total, 3);
+    let ids: Vec<Uuid> = all.data.iter().map(|t| t.id).collect();
+    assert_eq!(ids, vec![tickets[2].id, tickets[1].id, tickets[0].id], "应按创建时间倒序");
+
+    let page = repo.list_tickets(&filter(category), &Pagination { page: 2, page_size: 2 }).await.unwrap();
+    assert_eq!((page.total, page.total_pages, page.data.len()), (3, 2, 1));
+    assert_eq!(page.data[0].id, tickets[0].id);
+
+    let by_status = QueryFilter { status: Some("resolved".to_string()), ..filter(category) };
+    let result = repo.list_tickets(&by_status, &Pagination { page: 1, page_size: 10 }).await.unwrap();
+    assert_eq!(result.data.iter().map(|t| t.id).collect::<Vec<_>>(), vec![tickets[0].id]);
+
+    let by_keyword = QueryFilter { keywords: Some("disk 告警".to_string()), ..filter(category) };
+    let result = repo.list_tickets(&by_keyword, &Pagination { page: 1, page_size: 10 }).await.unwrap();
+    assert_eq!(result.data.iter().map(|t| t.id).collect::<Vec<_>>(), vec![tickets[1].id]);
+
+    let literal = QueryFilter { keywords: Some("50%_off".to_string()), ..filter(category) };
+    assert_eq!(repo.list_tickets(&literal, &Pagination { page: 1, page_size: 10 }).await.unwrap().total, 3);
+    let wildcard = QueryFilter { keywords: Some("5_%".to_string()), ..filter(category) };
+    assert_eq!(repo.list_tickets(&wildcard, &Pagination { page: 1, page_size: 10 }).await.unwrap().total, 0,
+        "关键词中的通配符应按字面匹配");
+
+    let by_priority = QueryFilter { priority_min: Some(3), priority_max: Some(3), ..filter(category) };
+    let result = repo.list_tickets(&by_priority, &Pagination { page: 1, page_size: 10 }).await.unwrap();
+    assert_eq!(result.data.iter().map(|t| t.id).collect::<Vec<_>>(), vec![tickets[2].id]);
+
+    let by_date = QueryFilter { date_from: Some(at(base + 60)), date_to: Some(at(base + 120)), ..filter(category) };
+    let result = repo.list_tickets(&by_date, &Pagination { page: 1, page_size: 10 }).await.unwrap();
+    assert_eq!(result.data.iter().map(|t| t.id).collect::<Vec<_>>(), vec![tickets[1].id]);
+
+    tickets
+}
+
+async fn solution_cases(repo: &dyn Repository, tickets: &[Ticket], base: i64) {
+    let mut older = solution(tickets[0].id, "清理日志目录", 0.6, at(base + 10));
+    older.citations = vec![Citation {
+        ticket_id: tickets[1].id,
+        title: tickets[1].title.clone(),
+        solution_id: None,
+        solution: Some("扩容后恢复".to_string()),
+        score: 0.82,
+    }];
+    let mut newer = solution(tickets[0].id, "扩容数据盘", 0.9, at(base + 20));
+    newer.raw_confidence = Some(0.95);
+    newer.guardrail = Some(GuardrailReport {
+        action: GuardrailAction::Flag,
+        findings: vec![GuardrailFinding {
+            rule: "drop_object".to_string(),
+            category: "destructive_command".to_string(),
+            action: GuardrailAction::Flag,
+            excerpt: "DROP TABLE".to_string(),
+        }],
+        original_confidence: Some(0.95),
+    });
+    repo.insert_solution(&older).await.unwrap();
+    repo.insert_solution(&newer).await.unwrap();
+    assert!(repo.insert_solution(&solution(Uuid::new_v4(), "孤立方案", 0.5, at(base))).await.is_err(),
+        "关联工单不存在时应插入失败");
+
+    let stored = repo.get_solutions_by_ticket(tickets[0].id).await.unwrap();
+    assert_eq!(json(&stored), json(&vec![newer.clone(), older.clone()]));
+    assert_eq!(json(&repo.get_solution(newer.id).await.unwrap()), json(&Some(newer.clone())));
+    assert!(repo.get_solution(Uuid::new_v4()).await.unwrap().is_none());
+
+    let ids = [tickets[0].id, tickets[2].id];
+    let batch = repo.solutions_for_tickets(&ids).await.unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[&tickets[0].id].len(), 2);
+    assert!(batch[&tickets[2].id].is_empty());
+    assert!(repo.solutions_for_tickets(&[]).await.unwrap().is_empty());
+
+    let feedback = Feedback {
+        solution_id: newer.id,
+        is_accepted: true,
+        score: Some(5),
+        comment: Some("有效".to_string()),
+        created_at: at(base + 30),
+    };
+    repo.update_solution_feedback(newer.id, &feedback, &[]).await.unwrap();
+    let reviewed = repo.get_solutions_by_ticket(tickets[0].id).await.unwrap();
+    assert!(reviewed[0].is_accepted);
+    assert_eq!(reviewed[0].feedback_score, Some(5));
+    assert_eq!(reviewed[0].feedback_comment.as_deref(), Some("有效"));
+    assert!(!reviewed[1].is_accepted);
+
+    let recorded = repo.list_feedback(newer.id).await.unwrap();
+    assert_eq!(json(&recorded), json(&vec![feedback.clone()]));
+    assert!(repo.list_feedback(older.id).await.unwrap().is_empty());
+    assert!(is_not_found(&repo.update_solution_feedback(Uuid::new_v4(), &feedback, &[]).await.unwrap_err()));
+
+    let reviewed = repo.list_reviewed_solutions(100_000).await.unwrap();
+    assert!(reviewed.iter().any(|s| s.id == newer.id));
+    assert!(!reviewed.iter().any(|s| s.id == older.id), "无反馈的方案不应计入");
+    assert!(reviewed.windows(2).all(|w| w[0].created_at >= w[1].created_at));
+    assert_eq!(repo.list_reviewed_solutions(0).await.unwrap().len(), 0);
+}
+
+async fn finetune_cases(repo: &dyn Repository, base: i64) {
+    let data: Vec<FinetuneData> = [
+        (0.9, DataSource::UserFeedback, 20),
+        (0.5, DataSource::SystemGenerated, 10),
+        (0.8, DataSource::ExpertLabeling, 30),
+    ]
+    .into_iter()
+    .map(|(quality_score, data_source, offset)| FinetuneData {
+        id: Uuid::new_v4(),
+        input_text: "工单内容".to_string(),
+        target_output: "处理方案".to_string(),
+        data_source,
+        quality_score,
+        created_at: at(base + offset),
+    })
+    .collect();
+    for d in &data {
+        repo.insert_finetune_data(d).await.unwrap();
+    }
+
+    let window = FinetuneDataFilter {
+        date_from: Some(at(base)),
+        date_to: Some(at(base + 3600)),
+        ..Default::default()
+    };
+    let all = repo.export_finetune_data(&window).await.unwrap();
+    assert_eq!(json(&all), json(&vec![data[1].clone(), data[0].clone(), data[2].clone()]), "应按创建时间排序");
+
+    let quality = FinetuneDataFilter { quality_score_min: Some(0.8), ..window.clone() };
+    let ids: Vec<Uuid> = repo.export_finetune_data(&quality).await.unwrap().iter().map(|d| d.id).collect();
+    assert_eq!(ids, vec![data[0].id, data[2].id]);
+
+    let source = FinetuneDataFilter { data_source: Some(DataSource::ExpertLabeling), ..window };
+    let ids: Vec<Uuid> = repo.export_finetune_data(&source).await.unwrap().iter().map(|d| d.id).collect();
+    assert_eq!(ids, vec![data[2].id]);
+}
+
+async fn usage_cases(repo: &dyn Repository, base: i64) {
+    let ticket_id = Uuid::new_v4();
+    let records = vec![
+        usage(ticket_id, UsageKind::Llm, "qwen-max", at(base + 20)),
+        usage(ticket_id, UsageKind::Embedding, "text-embedding-v2", at(base)),
+        usage(ticket_id, UsageKind::Llm, "qwen-turbo", at(base + 40)),
+    ];
+    for r in &records {
+        repo.insert_usage_record(r).await.unwrap();
+    }
+
+    let by_ticket = UsageQuery { ticket_id: Some(ticket_id), ..Default::default() };
+    let stored = repo.list_usage_records(&by_ticket).await.unwrap();
+    assert_eq!(json(&stored), json(&vec![records[1].clone(), records[0].clone(), records[2].clone()]));
+
+    let by_kind = UsageQuery { kind: Some(UsageKind::Llm), ..by_ticket.clone() };
+    assert_eq!(repo.list_usage_records(&by_kind).await.unwrap().len(), 2);
+
+    let by_model = UsageQuery { model: Some("qwen-max".to_string()), ..by_ticket.clone() };
+    let ids: Vec<Uuid> = repo.list_usage_records(&by_model).await.unwrap().iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![records[0].id]);
+
+    let by_time = UsageQuery { from: Some(at(base + 20)), to: Some(at(base + 40)), ..by_ticket };
+    let ids: Vec<Uuid> = repo.list_usage_records(&by_time).await.unwrap().iter().map(|r| r.id).collect();
+    assert_eq!(ids, vec![records[0].id]);
+}
+
+/// 在给定后端上运行全部用例
+/// 共享库中可能有其他工单的事件，只看指定工单
+async fn due_events(repo: &dyn Repository, ticket_id: Uuid) -> Vec<IndexEvent> {
+    repo.due_index_events(Utc::now(), 100_000).await.unwrap()
+        .into_iter()
+        .filter(|e| e.ticket_id == ticket_id)
+        .collect()
+}
+
+async fn outbox_cases(repo: &dyn Repository, category: &str, base: i64) {
+    let mut t = ticket(category, "索引事件", 1, at(base));
+    repo.insert_ticket(&t, &[]).await.unwrap();
+    let events = due_events(repo, t.id).await;
+    assert_eq!(events.len(), 1, "插入工单应写入索引事件");
+    assert_eq!((events[0].kind, events[0].status, events[0].attempts), (IndexEventKind::Upsert, IndexEventStatus::Pending, 0));
+
+    repo.set_ticket_embedding(t.id, Some(&[0.5])).await.unwrap();
+    assert_eq!(due_events(repo, t.id).await.len(), 1, "回写向量不应写入索引事件");
+
+    let mut event = events[0].clone();
+    event.attempts = 1;
+    event.last_error = Some("向量库不可用".to_string());
+    event.next_attempt_at = Utc::now() + std::time::Duration::from_secs(3600);
+    repo.update_index_event(&event).await.unwrap();
+    assert!(due_events(repo, t.id).await.is_empty(), "未到重试时间的事件不应返回");
+
+    let before = repo.get_statistics().await.unwrap();
+    event.status = IndexEventStatus::Failed;
+    event.next_attempt_at = at(base);
+    repo.update_index_event(&event).await.unwrap();
+    assert!(due_events(repo, t.id).await.is_empty(), "失败的事件不应返回");
+    let after = repo.get_statistics().await.unwrap();
+    assert_eq!(after.failed_index_events - before.failed_index_events, 1);
+    repo.complete_index_event(event.id).await.unwrap();
+
+    t.title = "索引事件（已更新）".to_string();
+    repo.update_ticket(&t, &[]).await.unwrap();
+    let events = due_events(repo, t.id).await;
+    assert_eq!(events.len(), 1, "更新工单应写入索引事件");
+    repo.complete_index_event(events[0].id).await.unwrap();
+
+    repo.delete_ticket(t.id, &[]).await.unwrap();
+    assert!(repo.get_ticket(t.id).await.unwrap().is_none());
+    assert!(is_not_found(&repo.delete_ticket(t.id, &[]).await.unwrap_err()));
+    let events = due_events(repo, t.id).await;
+    assert_eq!(events.iter().map(|e| e.kind).collect::<Vec<_>>(), vec![IndexEventKind::Delete]);
+    repo.complete_index_event(events[0].id).await.unwrap();
+
+    let orphan = IndexEvent::new(Uuid::new_v4(), IndexEventKind::Delete);
+    repo.enqueue_index_event(&orphan).await.unwrap();
+    let events = due_events(repo, orphan.ticket_id).await;
+    assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![orphan.id]);
+    repo.complete_index_event(orphan.id).await.unwrap();
+    assert!(due_events(repo, orphan.ticket_id).await.is_empty());
+}
+
+async fn audit_cases(repo: &dyn Repository, category: &str, base: i64) {
+    let t = ticket(category, "审计记录", 2, at(base));
+    let mut created = AuditEntry::new(t.id, "alice", AuditAction::Created);
+    created.created_at = at(base);
+    repo.insert_ticket(&t, std::slice::from_ref(&created)).await.unwrap();
+    let solution_id = Uuid::new_v4();
+    let mut entries = vec![
+        AuditEntry::new(t.id, "alice", AuditAction::FieldChanged)
+            .with_change("tags", Some(serde_json::json!(["a"])), Some(serde_json::json!(["a", "b"]))),
+        AuditEntry::new(t.id, SYSTEM_ACTOR, AuditAction::StatusChanged)
+            .with_change("status", Some(serde_json::json!("New")), Some(serde_json::json!("Processing"))),
+    ];
+    for entry in &mut entries {
+        entry.created_at = at(base);
+    }
+    let mut updated = t.clone();
+    updated.tags.push("b".to_string());
+    repo.update_ticket(&updated, &entries).await.unwrap();
+    let mut later = AuditEntry::new(t.id, "bob", AuditAction::SolutionAccepted).with_solution(solution_id);
+    later.created_at = at(base + 60);
+    repo.append_audit_entries(std::slice::from_ref(&later)).await.unwrap();
+    repo.append_audit_entries(&[]).await.unwrap();
+
+    let expected: Vec<AuditEntry> = [created].into_iter().chain(entries).chain([later]).collect();
+    assert_eq!(json(&repo.list_audit_entries(t.id).await.unwrap()), json(&expected), "应按追加顺序返回");
+
+    // 变更失败时，随之提交的审计记录一同回滚
+    let orphan = AuditEntry::new(t.id, "mallory", AuditAction::FieldChanged);
+    assert!(repo.update_ticket(&updated, std::slice::from_ref(&orphan)).await.is_err(), "旧版本更新应失败");
+    let missing = comment(Uuid::new_v4(), "mallory", "工单不存在", false, at(base));
+    assert!(repo.insert_comment(&missing, std::slice::from_ref(&orphan)).await.is_err());
+    let feedback = Feedback {
+        solution_id,
+        is_accepted: true,
+        score: None,
+        comment: None,
+        created_at: at(base),
+    };
+    assert!(repo.update_solution_feedback(solution_id, &feedback, std::slice::from_ref(&orphan)).await.is_err());
+    assert_eq!(repo.list_audit_entries(t.id).await.unwrap().len(), 4, "失败的变更不应留下审计记录");
+
+    repo.delete_ticket(t.id, &[AuditEntry::new(t.id, "alice", AuditAction::Deleted)]).await.unwrap();
+    let remaining = repo.list_audit_entries(t.id).await.unwrap();
+    assert_eq!(remaining.len(), 5, "工单删除后仍保留审计记录");
+    assert_eq!(remaining[4].action, AuditAction::Deleted);
+    assert!(repo.list_audit_entries(Uuid::new_v4()).await.unwrap().is_empty());
+}
+
+fn comment(ticket_id: Uuid, author: &str, body: &str, internal: bool, created_at: DateTime<Utc>) -> TicketComment {
+    TicketComment {
+        created_at,
+        updated_at: created_at,
+        ..TicketComment::new(ticket_id, author, NewComment {
+            role: CommentRole::Agent,
+            body: body.to_string(),
+            internal,
+        })
+    }
+}
+
+/// 处理并清除工单的索引事件，返回事件数量
+async fn drain_events(repo: &dyn Repository, ticket_id: Uuid) -> usize {
+    let events = due_events(repo, ticket_id).await;
+    for event in &events {
+        repo.complete_index_event(event.id).await.unwrap();
+    }
+    events.len()
+}
+
+async fn comment_cases(repo: &dyn Repository, category: &str, base: i64) {
+    let t = ticket(category, "评论", 2, at(base));
+    repo.insert_ticket(&t, &[]).await.unwrap();
+    drain_events(repo, t.id).await;
+
+    let later = comment(t.id, "alice", "已重启服务", false, at(base + 20));
+    let earlier = comment(t.id, "bob", "怀疑是证书过期", true, at(base + 10));
+    repo.insert_comment(&later, &[]).await.unwrap();
+    repo.insert_comment(&earlier, &[]).await.unwrap();
+    assert_eq!(drain_events(repo, t.id).await, 2, "新增评论应写入工单的索引事件");
+
+    let listed = repo.list_comments(t.id).await.unwrap();
+    assert_eq!(listed.iter().map(|c| c.id).collect::<Vec<_>>(), vec![earlier.id, later.id]);
+    assert_eq!(listed[0].role, CommentRole::Agent);
+    assert!(listed[0].internal);
+    assert_eq!(listed[0].created_at, at(base + 10));
+
+    let missing = comment(Uuid::new_v4(), "alice", "工单不存在", false, at(base));
+    assert!(is_not_found(&repo.insert_comment(&missing, &[]).await.unwrap_err()));
+    assert!(repo.get_comment(missing.id).await.unwrap().is_none());
+
+    let edited = TicketComment {
+        body: "已重启服务并续期证书".to_string(),
+        internal: true,
+        updated_at: at(base + 30),
+        ..later.clone()
+    };
+    repo.update_comment(&edited, &[]).await.unwrap();
+    let stored = repo.get_comment(later.id).await.unwrap().unwrap();
+    assert_eq!((stored.body.as_str(), stored.internal), ("已重启服务并续期证书", true));
+    assert_eq!((stored.created_at, stored.updated_at), (at(base + 20), at(base + 30)));
+    assert!(is_not_found(&repo.update_comment(&missing, &[]).await.unwrap_err()));
+    assert_eq!(drain_events(repo, t.id).await, 1, "修改评论应写入工单的索引事件");
+
+    repo.delete_comment(earlier.id, &[]).await.unwrap();
+    assert!(is_not_found(&repo.delete_comment(earlier.id, &[]).await.unwrap_err()));
+    assert_eq!(drain_events(repo, t.id).await, 1, "删除评论应写入工单的索引事件");
+    assert_eq!(repo.list_comments(t.id).await.unwrap().len(), 1);
+
+    repo.delete_ticket(t.id, &[]).await.unwrap();
+    drain_events(repo, t.id).await;
+    assert!(repo.get_comment(later.id).await.unwrap().is_none(), "删除工单应级联删除评论");
+    assert!(repo.list_comments(t.id).await.unwrap().is_empty());
+}
+
+fn attachment(ticket_id: Uuid, filename: &str, error_lines: &[&str], created_at: DateTime<Utc>) -> Attachment {
+    let id = Uuid::new_v4();
+    Attachment {
+        id,
+        ticket_id,
+        filename: filename.to_string(),
+        content_type: "text/plain".to_string(),
+        size_bytes: 42,
+        storage_key: format!("tickets/{}/{}", ticket_id, id),
+        extracted_text: Some(error_lines.join("\n")),
+        error_lines: error_lines.iter().map(|line| line.to_string()).collect(),
+        uploaded_by: "alice".to_string(),
+        created_at,
+    }
+}
+
+async fn attachment_cases(repo: &dyn Repository, category: &str, base: i64) {
+    let t = ticket(category, "附件", 2, at(base));
+    repo.insert_ticket(&t, &[]).await.unwrap();
+    drain_events(repo, t.id).await;
+
+    let later = attachment(t.id, "app.log", &["ERROR 连接超时", "FATAL 服务退出"], at(base + 20));
+    let earlier = Attachment {
+        content_type: "image/png".to_string(),
+        extracted_text: None,
+        ..attachment(t.id, "截图.png", &[], at(base + 10))
+    };
+    repo.insert_attachment(&later, &[]).await.unwrap();
+    repo.insert_attachment(&earlier, &[]).await.unwrap();
+    assert_eq!(drain_events(repo, t.id).await, 2, "上传附件应写入工单的索引事件");
+
+    let listed = repo.list_attachments(t.id).await.unwrap();
+    assert_eq!(listed.iter().map(|a| a.id).collect::<Vec<_>>(), vec![earlier.id, later.id]);
+    assert_eq!(json(&listed[1]), json(&later));
+    assert!(listed[0].extracted_text.is_none() && listed[0].error_lines.is_empty());
+
+    let missing = attachment(Uuid::new_v4(), "missing.log", &[], at(base));
+    assert!(is_not_found(&repo.insert_attachment(&missing, &[]).await.unwrap_err()));
+    assert!(repo.get_attachment(missing.id).await.unwrap().is_none());
+
+    repo.delete_attachment(earlier.id, &[]).await.unwrap();
+    assert!(is_not_found(&repo.delete_attachment(earlier.id, &[]).await.unwrap_err()));
+    assert_eq!(drain_events(repo, t.id).await, 1, "删除附件应写入工单的索引事件");
+    assert_eq!(repo.get_attachment(later.id).await.unwrap().unwrap().error_lines, later.error_lines);
+
+    repo.delete_ticket(t.id, &[]).await.unwrap();
+    drain_events(repo, t.id).await;
+    assert!(repo.get_attachment(later.id).await.unwrap().is_none(), "删除工单应级联删除附件");
+    assert!(repo.list_attachments(t.id).await.unwrap().is_empty());
+}
+
+async fn run_suite(repo: &dyn Repository) {
+    let category = format!("suite-{}", Uuid::new_v4());
+    let base = base_time();
+    let before = repo.get_statistics().await.unwrap();
+
+    let tickets = ticket_cases(repo, &category, base).await;
+    solution_cases(repo, &tickets, base).await;
+    finetune_cases(repo, base).await;
+    usage_cases(repo, base).await;
+    outbox_cases(repo, &category, base).await;
+    audit_cases(repo, &category, base).await;
+    comment_cases(repo, &category, base).await;
+    attachment_cases(repo, &category, base).await;
+
+    let after = repo.get_statistics().await.unwrap();
+    assert_eq!(after.total_tickets - before.total_tickets, 3);
+    assert_eq!(after.total_solutions - before.total_solutions, 2);
+    assert_eq!(after.accepted_solutions - before.accepted_solutions, 1);
+    assert_eq!(after.finetune_data_count - before.finetune_data_count, 3);
+    assert!(after.average_confidence > 0.0);
+    assert_eq!(after.pending_index_events - before.pending_index_events, 7, "4次插入、2次更新与1次删除");
+}
+
+#[tokio::test]
+async fn memory_repository() {
+    let repo = InMemoryDatabase::new();
+    assert_eq!(repo.backend(), "memory");
+    assert_eq!(repo.get_statistics().await.unwrap().average_confidence, 0.0);
+    run_suite(&repo).await;
+}
+
+#[tokio::test]
+async fn sqlite_repository() {
+    let repo = SqliteDatabase::new(&config("sqlite::memory:")).await.unwrap();
+    assert_eq!(repo.backend(), "sqlite");
+    assert_eq!(repo.get_statistics().await.unwrap().average_confidence, 0.0);
+    run_suite(&repo).await;
+}
+
+#[tokio::test]
+async fn sqlite_file_repository_reopens() {
+    let path = std::env::temp_dir().join(format!("rag-repository-{}.db", Uuid::new_v4()));
+    let url = format!("sqlite://{}", path.display());
+
+    let repo = SqliteDatabase::new(&config(&url)).await.unwrap();
+    let saved = ticket("reopen", "重启后仍可读取", 3, at(base_time()));
+    repo.insert_ticket(&saved, &[]).await.unwrap();
+    repo.pool().close().await;
+
+    // 重新打开时已应用的迁移会跳过
+    let reopened = SqliteDatabase::new(&config(&url)).await.unwrap();
+    let loaded = reopened.get_ticket(saved.id).await.unwrap().expect("工单应已持久化");
+    assert_eq!(json(&loaded), json(&saved));
+    reopened.pool().close().await;
+    let _ = std::fs::remove_file(path);
+}
+
+#[tokio::test]
+async fn postgres_repository() {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("未设置 TEST_DATABASE_URL，跳过PostgreSQL用例");
+        return;
+    };
+    let repo = PostgresDatabase::new(&config(&url)).await.unwrap();
+    assert_eq!(repo.backend(), "postgres");
+    run_suite(&repo).await;
+}